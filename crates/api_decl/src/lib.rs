@@ -1,12 +1,12 @@
 mod namespaces;
 
 pub use namespaces::{
-    AnvilNamespaceServer, AnvilZksNamespaceServer, ConfigNamespaceServer, EthTestNamespaceServer,
-    EvmNamespaceServer,
+    AnvilNamespaceServer, AnvilZksNamespaceServer, ConfigNamespaceServer, DebugNamespaceServer,
+    EthTestNamespaceServer, EvmNamespaceServer, OtsNamespaceServer, PersonalNamespaceServer,
+    TraceNamespaceServer,
 };
 
 // Re-export available namespaces from zksync-era
 pub use zksync_web3_decl::namespaces::{
-    DebugNamespaceServer, EthNamespaceServer, NetNamespaceServer, Web3NamespaceServer,
-    ZksNamespaceServer,
+    EthNamespaceServer, NetNamespaceServer, Web3NamespaceServer, ZksNamespaceServer,
 };
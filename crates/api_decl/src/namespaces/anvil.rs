@@ -1,10 +1,13 @@
-use anvil_zksync_types::api::{DetailedTransaction, ResetRequest};
+use anvil_zksync_types::api::{
+    DetailedTransaction, ForkFollowStatus, GasProfile, GasReport, ResetRequest, TokenMetadata,
+};
 use anvil_zksync_types::Numeric;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
 use zksync_types::api::Block;
 use zksync_types::web3::Bytes;
 use zksync_types::{Address, H256, U256, U64};
+use zksync_web3_decl::types::Token;
 
 #[rpc(server, namespace = "anvil")]
 pub trait AnvilNamespace {
@@ -47,6 +50,21 @@ pub trait AnvilNamespace {
     #[method(name = "setRpcUrl")]
     async fn set_rpc_url(&self, url: String) -> RpcResult<()>;
 
+    /// Pauses or resumes following the forked chain's head. Only available if the node was started
+    /// with `fork --fork-follow`.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - `true` resumes applying new remote blocks, `false` pauses it
+    #[method(name = "setForkFollowing")]
+    async fn set_fork_following(&self, enabled: bool) -> RpcResult<()>;
+
+    /// Returns the progress of following the forked chain's head along with remote transactions
+    /// whose outcome changed when re-executed locally. Only available if the node was started with
+    /// `fork --fork-follow`.
+    #[method(name = "getForkFollowStatus")]
+    async fn get_fork_follow_status(&self) -> RpcResult<ForkFollowStatus>;
+
     /// Sets the base fee of the next block.
     ///
     /// # Arguments
@@ -203,6 +221,16 @@ pub trait AnvilNamespace {
     #[method(name = "autoImpersonateAccount", aliases = ["hardhat_autoImpersonateAccount"])]
     async fn auto_impersonate_account(&self, enabled: bool) -> RpcResult<()>;
 
+    /// Sets auto-funding status. When enabled, the initiator of every transaction that cannot
+    /// cover `gas_limit * max_fee_per_gas + value` gets its balance topped up when the transaction
+    /// is sealed.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - `true` enables auto-funding, `false` disables it
+    #[method(name = "setAutoFund")]
+    async fn set_auto_fund(&self, enabled: bool) -> RpcResult<()>;
+
     /// Sets the balance of the given address to the given balance.
     ///
     /// # Arguments
@@ -318,4 +346,77 @@ pub trait AnvilNamespace {
     /// * `id` - The chain id to be set.
     #[method(name = "setChainId")]
     async fn set_chain_id(&self, id: u32) -> RpcResult<()>;
+
+    /// Returns `console.log` messages emitted by the transaction, in the order they were emitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The transaction hash
+    ///
+    /// # Returns
+    /// Decoded console messages or `null` if the transaction is unknown.
+    #[method(name = "getConsoleLogs")]
+    async fn get_console_logs(&self, hash: H256) -> RpcResult<Option<Vec<String>>>;
+
+    /// Returns gas usage aggregated by contract and function across all transactions executed
+    /// during this session.
+    ///
+    /// # Returns
+    /// Per-function min, average, median and max gas used along with call counts and pubdata cost.
+    #[method(name = "getGasReport")]
+    async fn get_gas_report(&self) -> RpcResult<GasReport>;
+
+    /// Returns gas profile of the transaction's call tree. Frames are labelled with decoded
+    /// contract and function names, gas spent on pubdata and bootloader overhead are reported as
+    /// separate frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The transaction hash
+    ///
+    /// # Returns
+    /// Profile in folded-stack format and as an SVG flamegraph or `null` if the transaction is
+    /// unknown. Fails if the node was started without `--gas-profile` or `--gas-profile-dir`.
+    #[method(name = "getGasProfile")]
+    async fn get_gas_profile(&self, hash: H256) -> RpcResult<Option<GasProfile>>;
+
+    /// Sets the ERC20 balance of an account by writing directly into the token's storage (Foundry's
+    /// `deal`). The balance slot is discovered by tracing a `balanceOf` call, so this works for
+    /// both locally deployed and forked tokens that keep balances in a plain storage slot.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The address of the ERC20 token
+    /// * `account` - The account whose balance will be edited
+    /// * `amount` - The new token balance
+    /// * `adjust_total_supply` - (Optional) Whether to adjust `totalSupply` by the balance
+    ///   difference, `false` by default
+    #[method(name = "setErc20Balance", aliases = ["anvil_dealERC20"])]
+    async fn set_erc20_balance(
+        &self,
+        token: Address,
+        account: Address,
+        amount: U256,
+        adjust_total_supply: Option<bool>,
+    ) -> RpcResult<()>;
+
+    /// Registers a token so that it is reported by `zks_getConfirmedTokens` and
+    /// `zks_getAllAccountBalances`. Tokens that emit an ERC20 `Transfer` event are registered
+    /// automatically, this is only needed for tokens that have not been transferred yet or whose
+    /// metadata cannot be read from the contract.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The L2 address of the token
+    /// * `metadata` - (Optional) L1 address, name, symbol and decimals overriding the ones read
+    ///   from the token contract
+    ///
+    /// # Returns
+    /// The registered token.
+    #[method(name = "registerToken")]
+    async fn register_token(
+        &self,
+        address: Address,
+        metadata: Option<TokenMetadata>,
+    ) -> RpcResult<Token>;
 }
@@ -1,5 +1,6 @@
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
+use zksync_types::transaction_request::CallRequest;
 use zksync_types::web3::Bytes;
 use zksync_types::{L1BatchNumber, H256};
 
@@ -53,4 +54,17 @@ pub trait AnvilZksNamespace {
     /// Bytes with the witness that can be passed to proving system.
     #[method(name = "getBoojumWitness")]
     async fn get_boojum_witness(&self, batch_number: L1BatchNumber) -> RpcResult<Bytes>;
+
+    /// Same as `eth_sendTransaction`: sends a transaction from an impersonated or node-managed
+    /// account, turning it into a type `0x71` transaction when ZKsync EIP-712 fields
+    /// (`eip712Meta`) are set.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Transaction to send
+    ///
+    /// # Returns
+    /// Hash of the submitted transaction
+    #[method(name = "sendTransaction")]
+    async fn send_transaction(&self, tx: CallRequest) -> RpcResult<H256>;
 }
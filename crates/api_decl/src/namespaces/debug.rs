@@ -0,0 +1,77 @@
+use anvil_zksync_types::api::{BlockTraceResult, TraceOptions, TraceResult};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use zksync_types::api::{BlockId, BlockNumber};
+use zksync_types::transaction_request::CallRequest;
+use zksync_types::H256;
+
+/// API bindings for the `debug` namespace. Mirrors zksync-era's declaration but additionally
+/// supports geth's default struct-log tracer (selected with `{ "tracer": "structLogger" }`).
+#[rpc(server, namespace = "debug")]
+pub trait DebugNamespace {
+    /// Traces all transactions in the block with the given number.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Block number
+    /// * `options` - Tracer options
+    ///
+    /// # Returns
+    /// A trace for each transaction in the block.
+    #[method(name = "traceBlockByNumber")]
+    async fn trace_block_by_number(
+        &self,
+        block: BlockNumber,
+        options: Option<TraceOptions>,
+    ) -> RpcResult<BlockTraceResult>;
+
+    /// Traces all transactions in the block with the given hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - Block hash
+    /// * `options` - Tracer options
+    ///
+    /// # Returns
+    /// A trace for each transaction in the block.
+    #[method(name = "traceBlockByHash")]
+    async fn trace_block_by_hash(
+        &self,
+        hash: H256,
+        options: Option<TraceOptions>,
+    ) -> RpcResult<BlockTraceResult>;
+
+    /// Executes a call on top of the latest block and traces it.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Call request
+    /// * `block` - Block to execute the call on, only `latest` is supported
+    /// * `options` - Tracer options
+    ///
+    /// # Returns
+    /// A trace of the call.
+    #[method(name = "traceCall")]
+    async fn trace_call(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+        options: Option<TraceOptions>,
+    ) -> RpcResult<TraceResult>;
+
+    /// Traces a previously executed transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Transaction hash
+    /// * `options` - Tracer options
+    ///
+    /// # Returns
+    /// A trace of the transaction or `null` if the transaction is unknown.
+    #[method(name = "traceTransaction")]
+    async fn trace_transaction(
+        &self,
+        tx_hash: H256,
+        options: Option<TraceOptions>,
+    ) -> RpcResult<Option<TraceResult>>;
+}
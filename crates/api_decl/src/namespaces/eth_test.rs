@@ -1,11 +1,90 @@
+use anvil_zksync_types::api::{SimulatePayload, SimulatedBlock};
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
+use zksync_types::api::BlockIdVariant;
 use zksync_types::transaction_request::CallRequest;
-use zksync_types::H256;
+use zksync_types::web3::Bytes;
+use zksync_types::{Address, H256};
 
 /// API bindings for the `eth` namespace that are not normally supported by core ZKsync.
 #[rpc(server, namespace = "eth")]
 pub trait EthTestNamespace {
+    /// Sends a transaction from an impersonated or node-managed account. ZKsync EIP-712 fields
+    /// (`eip712Meta`: paymaster params, factory deps, gas per pubdata and custom signature) turn
+    /// it into a type `0x71` transaction; missing fee fields are filled in from the fee model.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Transaction to send
+    ///
+    /// # Returns
+    /// Hash of the submitted transaction
     #[method(name = "sendTransaction")]
     async fn send_transaction(&self, tx: CallRequest) -> RpcResult<H256>;
+
+    /// Simulates a sequence of calls across one or more blocks on top of the latest state. Block
+    /// and state overrides can be provided for every simulated block. Nothing is persisted.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - Blocks to simulate along with their overrides and calls
+    /// * `block` - Block to simulate on top of (only `latest` and `pending` are supported)
+    ///
+    /// # Returns
+    /// Simulated blocks with per-call results, logs and call traces
+    #[method(name = "simulateV1")]
+    async fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<Vec<SimulatedBlock>>;
+
+    /// Signs a message with the private key of a node-managed account. The message is prefixed with
+    /// `"\x19Ethereum Signed Message:\n" + len(message)` before being hashed (EIP-191).
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Account to sign with
+    /// * `message` - Message to sign
+    ///
+    /// # Returns
+    /// 65-byte signature
+    #[method(name = "sign")]
+    async fn sign(&self, address: Address, message: Bytes) -> RpcResult<Bytes>;
+
+    /// Signs a transaction with the private key of a node-managed account without submitting it.
+    /// The transaction is always encoded as a ZKsync EIP-712 (type `0x71`) transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Transaction to sign, may include `eip712Meta` (e.g. paymaster params)
+    ///
+    /// # Returns
+    /// Raw signed transaction that can be submitted with `eth_sendRawTransaction`
+    #[method(name = "signTransaction")]
+    async fn sign_transaction(&self, tx: CallRequest) -> RpcResult<Bytes>;
+
+    /// Identical to `eth_signTypedData_v4`.
+    #[method(name = "signTypedData")]
+    async fn sign_typed_data(
+        &self,
+        address: Address,
+        typed_data: serde_json::Value,
+    ) -> RpcResult<Bytes>;
+
+    /// Signs EIP-712 typed data with the private key of a node-managed account.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Account to sign with
+    /// * `typed_data` - Typed data as a JSON object or as a string containing one
+    ///
+    /// # Returns
+    /// 65-byte signature
+    #[method(name = "signTypedData_v4")]
+    async fn sign_typed_data_v4(
+        &self,
+        address: Address,
+        typed_data: serde_json::Value,
+    ) -> RpcResult<Bytes>;
 }
@@ -1,5 +1,7 @@
+use anvil_zksync_types::api::NewAccount;
 use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
+use zksync_types::Address;
 
 /// API bindings for the `evm` namespace. Note that most of the methods are covered by aliases in
 /// [`AnvilNamespace`]. This namespace exclusively contains other methods.
@@ -11,4 +13,28 @@ pub trait EvmNamespace {
     /// The string "0x0".
     #[method(name = "mine")]
     async fn mine(&self) -> RpcResult<String>;
+
+    /// Adds an account managed by the node. Managed accounts are listed by `eth_accounts` and can
+    /// be used with `eth_sendTransaction` and the signing methods.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - Either a hex-encoded private key or an index to derive the account at from
+    ///   the node's mnemonic
+    ///
+    /// # Returns
+    /// The address of the added account.
+    #[method(name = "addAccount")]
+    async fn add_account(&self, account: NewAccount) -> RpcResult<Address>;
+
+    /// Removes an account managed by the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the account to remove
+    ///
+    /// # Returns
+    /// `true` if the account was managed by the node, `false` otherwise.
+    #[method(name = "removeAccount")]
+    async fn remove_account(&self, address: Address) -> RpcResult<bool>;
 }
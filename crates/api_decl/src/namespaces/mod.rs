@@ -1,10 +1,15 @@
 mod anvil;
 mod anvil_zks;
 mod config;
+mod debug;
 mod eth_test;
 mod evm;
+mod ots;
+mod personal;
+mod trace;
 
 pub use self::{
     anvil::AnvilNamespaceServer, anvil_zks::AnvilZksNamespaceServer, config::ConfigNamespaceServer,
-    eth_test::EthTestNamespaceServer, evm::EvmNamespaceServer,
+    debug::DebugNamespaceServer, eth_test::EthTestNamespaceServer, evm::EvmNamespaceServer,
+    ots::OtsNamespaceServer, personal::PersonalNamespaceServer, trace::TraceNamespaceServer,
 };
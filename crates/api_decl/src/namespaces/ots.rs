@@ -0,0 +1,159 @@
+use anvil_zksync_types::api::{
+    OtsBlockDetails, OtsBlockTransactions, OtsContractCreator, OtsInternalOperation,
+    OtsSearchTransactions, OtsTrace,
+};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use zksync_types::api::BlockIdVariant;
+use zksync_types::web3::Bytes;
+use zksync_types::{Address, H256};
+
+/// API bindings for the Otterscan `ots` namespace. Data is derived from blocks and call trees
+/// recorded for transactions executed by the node itself.
+#[rpc(server, namespace = "ots")]
+pub trait OtsNamespace {
+    /// Returns the version of the Otterscan API implemented by the node.
+    ///
+    /// # Returns
+    /// API level as a number.
+    #[method(name = "getApiLevel")]
+    async fn get_api_level(&self) -> RpcResult<u64>;
+
+    /// Returns value transfers and contract creations that happened inside the transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Transaction hash
+    ///
+    /// # Returns
+    /// A list of internal operations.
+    #[method(name = "getInternalOperations")]
+    async fn get_internal_operations(&self, tx_hash: H256) -> RpcResult<Vec<OtsInternalOperation>>;
+
+    /// Checks whether there is code deployed at the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Account address
+    /// * `block` - Block number or tag
+    ///
+    /// # Returns
+    /// `true` if the address has code, `false` otherwise.
+    #[method(name = "hasCode")]
+    async fn has_code(&self, address: Address, block: Option<BlockIdVariant>) -> RpcResult<bool>;
+
+    /// Returns the raw revert data of the transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Transaction hash
+    ///
+    /// # Returns
+    /// Revert data or `0x` if the transaction has succeeded.
+    #[method(name = "getTransactionError")]
+    async fn get_transaction_error(&self, tx_hash: H256) -> RpcResult<Bytes>;
+
+    /// Returns all calls made by the transaction in depth-first order.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Transaction hash
+    ///
+    /// # Returns
+    /// A flat list of calls annotated with their depth.
+    #[method(name = "traceTransaction")]
+    async fn trace_transaction(&self, tx_hash: H256) -> RpcResult<Vec<OtsTrace>>;
+
+    /// Returns the block without its transactions along with its transaction count and fees.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_number` - Block number
+    ///
+    /// # Returns
+    /// Block details or `null` if the block is unknown.
+    #[method(name = "getBlockDetails")]
+    async fn get_block_details(&self, block_number: u64) -> RpcResult<Option<OtsBlockDetails>>;
+
+    /// Returns a page of the block's transactions along with their receipts.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_number` - Block number
+    /// * `page_number` - Zero-based page number
+    /// * `page_size` - Number of transactions per page
+    ///
+    /// # Returns
+    /// Block with the requested page of transactions or `null` if the block is unknown.
+    #[method(name = "getBlockTransactions")]
+    async fn get_block_transactions(
+        &self,
+        block_number: u64,
+        page_number: usize,
+        page_size: usize,
+    ) -> RpcResult<Option<OtsBlockTransactions>>;
+
+    /// Returns transactions the address took part in that were included before the given block.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Account address
+    /// * `block_number` - Block to search before (`0` to search from the most recent block)
+    /// * `page_size` - Minimum number of transactions to return
+    ///
+    /// # Returns
+    /// Transactions and receipts sorted from the newest to the oldest one.
+    #[method(name = "searchTransactionsBefore")]
+    async fn search_transactions_before(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> RpcResult<OtsSearchTransactions>;
+
+    /// Returns transactions the address took part in that were included after the given block.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Account address
+    /// * `block_number` - Block to search after (`0` to search from the oldest block)
+    /// * `page_size` - Minimum number of transactions to return
+    ///
+    /// # Returns
+    /// Transactions and receipts sorted from the newest to the oldest one.
+    #[method(name = "searchTransactionsAfter")]
+    async fn search_transactions_after(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> RpcResult<OtsSearchTransactions>;
+
+    /// Returns the hash of the transaction sent by the given account with the given nonce.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - Transaction initiator
+    /// * `nonce` - Transaction nonce
+    ///
+    /// # Returns
+    /// Transaction hash or `null` if no such transaction is known.
+    #[method(name = "getTransactionBySenderAndNonce")]
+    async fn get_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> RpcResult<Option<H256>>;
+
+    /// Returns the transaction that deployed the contract along with the contract's creator.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Contract address
+    ///
+    /// # Returns
+    /// Transaction hash and creator or `null` if the contract was not deployed on this node.
+    #[method(name = "getContractCreator")]
+    async fn get_contract_creator(&self, address: Address)
+        -> RpcResult<Option<OtsContractCreator>>;
+}
@@ -0,0 +1,21 @@
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use zksync_types::web3::Bytes;
+use zksync_types::Address;
+
+/// API bindings for the `personal` namespace. Only signing with node-managed accounts is supported.
+#[rpc(server, namespace = "personal")]
+pub trait PersonalNamespace {
+    /// Signs a message with the private key of a node-managed account. Same as `eth_sign` but with
+    /// the arguments swapped.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Message to sign
+    /// * `address` - Account to sign with
+    ///
+    /// # Returns
+    /// 65-byte signature
+    #[method(name = "sign")]
+    async fn sign(&self, message: Bytes, address: Address) -> RpcResult<Bytes>;
+}
@@ -0,0 +1,61 @@
+use anvil_zksync_types::api::{LocalizedTransactionTrace, TraceFilter, TraceResults};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use zksync_types::api::BlockNumber;
+use zksync_types::H256;
+
+/// API bindings for the Parity-style `trace` namespace. Traces are derived from call trees
+/// recorded for transactions executed by the node itself.
+#[rpc(server, namespace = "trace")]
+pub trait TraceNamespace {
+    /// Returns all traces of the transaction with the given hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Transaction hash
+    ///
+    /// # Returns
+    /// Flattened call traces or `null` if the transaction is unknown.
+    #[method(name = "transaction")]
+    async fn transaction(&self, tx_hash: H256)
+        -> RpcResult<Option<Vec<LocalizedTransactionTrace>>>;
+
+    /// Returns traces of all transactions in the given block.
+    ///
+    /// # Arguments
+    ///
+    /// * `block` - Block number
+    ///
+    /// # Returns
+    /// Flattened call traces or `null` if the block is unknown.
+    #[method(name = "block")]
+    async fn block(&self, block: BlockNumber) -> RpcResult<Option<Vec<LocalizedTransactionTrace>>>;
+
+    /// Replays the transaction with the given hash. Only the `trace` trace type is supported,
+    /// requesting `stateDiff` or `vmTrace` fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Transaction hash
+    /// * `trace_types` - Requested trace types (`trace`, `stateDiff`, `vmTrace`)
+    ///
+    /// # Returns
+    /// Transaction output and traces or `null` if the transaction is unknown.
+    #[method(name = "replayTransaction")]
+    async fn replay_transaction(
+        &self,
+        tx_hash: H256,
+        trace_types: Vec<String>,
+    ) -> RpcResult<Option<TraceResults>>;
+
+    /// Returns traces matching the given filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Block range, from/to addresses and pagination
+    ///
+    /// # Returns
+    /// Matching flattened call traces.
+    #[method(name = "filter")]
+    async fn filter(&self, filter: TraceFilter) -> RpcResult<Vec<LocalizedTransactionTrace>>;
+}
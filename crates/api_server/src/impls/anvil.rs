@@ -1,12 +1,15 @@
 use anvil_zksync_api_decl::AnvilNamespaceServer;
 use anvil_zksync_common::sh_warn;
 use anvil_zksync_core::node::InMemoryNode;
-use anvil_zksync_types::api::{DetailedTransaction, ResetRequest};
+use anvil_zksync_types::api::{
+    DetailedTransaction, ForkFollowStatus, GasProfile, GasReport, ResetRequest, TokenMetadata,
+};
 use anvil_zksync_types::Numeric;
 use jsonrpsee::core::{async_trait, RpcResult};
 use zksync_types::api::Block;
 use zksync_types::web3::Bytes;
 use zksync_types::{Address, H256, U256, U64};
+use zksync_web3_decl::types::Token;
 
 use crate::error::{rpc_unsupported, RpcErrorAdapter};
 
@@ -50,6 +53,18 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .map_err(RpcErrorAdapter::into)
     }
 
+    async fn set_fork_following(&self, enabled: bool) -> RpcResult<()> {
+        self.node
+            .set_fork_following(enabled)
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_fork_follow_status(&self) -> RpcResult<ForkFollowStatus> {
+        self.node
+            .get_fork_follow_status()
+            .map_err(RpcErrorAdapter::into)
+    }
+
     async fn set_next_block_base_fee_per_gas(&self, base_fee: U256) -> RpcResult<()> {
         self.node
             .set_next_block_base_fee_per_gas(base_fee)
@@ -157,6 +172,11 @@ impl AnvilNamespaceServer for AnvilNamespace {
         Ok(())
     }
 
+    async fn set_auto_fund(&self, enabled: bool) -> RpcResult<()> {
+        self.node.set_auto_fund(enabled).await;
+        Ok(())
+    }
+
     async fn set_balance(&self, address: Address, balance: U256) -> RpcResult<bool> {
         self.node
             .set_balance(address, balance)
@@ -219,4 +239,49 @@ impl AnvilNamespaceServer for AnvilNamespace {
             .await
             .map_err(RpcErrorAdapter::into)
     }
+
+    async fn get_console_logs(&self, hash: H256) -> RpcResult<Option<Vec<String>>> {
+        self.node
+            .get_console_logs(hash)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_gas_report(&self) -> RpcResult<GasReport> {
+        self.node
+            .get_gas_report()
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_gas_profile(&self, hash: H256) -> RpcResult<Option<GasProfile>> {
+        self.node
+            .get_gas_profile(hash)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn set_erc20_balance(
+        &self,
+        token: Address,
+        account: Address,
+        amount: U256,
+        adjust_total_supply: Option<bool>,
+    ) -> RpcResult<()> {
+        self.node
+            .set_erc20_balance_impl(token, account, amount, adjust_total_supply.unwrap_or(false))
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn register_token(
+        &self,
+        address: Address,
+        metadata: Option<TokenMetadata>,
+    ) -> RpcResult<Token> {
+        self.node
+            .register_token_impl(address, metadata)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
 }
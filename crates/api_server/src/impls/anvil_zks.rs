@@ -1,19 +1,21 @@
 use anvil_zksync_api_decl::AnvilZksNamespaceServer;
-use anvil_zksync_core::node::boojumos_get_batch_witness;
+use anvil_zksync_core::node::{boojumos_get_batch_witness, InMemoryNode};
 use anvil_zksync_l1_sidecar::L1Sidecar;
 use jsonrpsee::core::{async_trait, RpcResult};
+use zksync_types::transaction_request::CallRequest;
 use zksync_types::web3::Bytes;
 use zksync_types::{L1BatchNumber, H256};
 
 use crate::error::{rpc_invalid_params, RpcErrorAdapter};
 
 pub struct AnvilZksNamespace {
+    node: InMemoryNode,
     l1_sidecar: L1Sidecar,
 }
 
 impl AnvilZksNamespace {
-    pub fn new(l1_sidecar: L1Sidecar) -> Self {
-        Self { l1_sidecar }
+    pub fn new(node: InMemoryNode, l1_sidecar: L1Sidecar) -> Self {
+        Self { node, l1_sidecar }
     }
 }
 
@@ -47,4 +49,11 @@ impl AnvilZksNamespaceServer for AnvilZksNamespace {
             ))?
             .into())
     }
+
+    async fn send_transaction(&self, tx: CallRequest) -> RpcResult<H256> {
+        self.node
+            .send_transaction_impl(tx)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
 }
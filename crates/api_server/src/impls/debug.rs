@@ -1,7 +1,8 @@
 use anvil_zksync_api_decl::DebugNamespaceServer;
 use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::{BlockTraceResult, TraceOptions, TraceResult};
 use jsonrpsee::core::{async_trait, RpcResult};
-use zksync_types::api::{BlockNumber, CallTracerBlockResult, CallTracerResult, TracerConfig};
+use zksync_types::api::BlockNumber;
 use zksync_types::transaction_request::CallRequest;
 use zksync_types::{api, H256};
 
@@ -15,6 +16,26 @@ impl DebugNamespace {
     pub fn new(node: InMemoryNode) -> Self {
         Self { node }
     }
+
+    async fn trace_block(
+        &self,
+        block_id: api::BlockId,
+        options: Option<TraceOptions>,
+    ) -> RpcResult<BlockTraceResult> {
+        match options.as_ref().and_then(TraceOptions::struct_log_config) {
+            Some(config) => self
+                .node
+                .trace_block_struct_logs_impl(block_id, config)
+                .await
+                .map(BlockTraceResult::StructLogs),
+            None => self
+                .node
+                .trace_block_impl(block_id, options.and_then(TraceOptions::call_tracer_config))
+                .await
+                .map(BlockTraceResult::CallTrace),
+        }
+        .map_err(RpcErrorAdapter::into)
+    }
 }
 
 #[async_trait]
@@ -22,45 +43,61 @@ impl DebugNamespaceServer for DebugNamespace {
     async fn trace_block_by_number(
         &self,
         block: BlockNumber,
-        options: Option<TracerConfig>,
-    ) -> RpcResult<CallTracerBlockResult> {
-        self.node
-            .trace_block_impl(api::BlockId::Number(block), options)
-            .await
-            .map_err(RpcErrorAdapter::into)
+        options: Option<TraceOptions>,
+    ) -> RpcResult<BlockTraceResult> {
+        self.trace_block(api::BlockId::Number(block), options).await
     }
 
     async fn trace_block_by_hash(
         &self,
         hash: H256,
-        options: Option<TracerConfig>,
-    ) -> RpcResult<CallTracerBlockResult> {
-        self.node
-            .trace_block_impl(api::BlockId::Hash(hash), options)
-            .await
-            .map_err(RpcErrorAdapter::into)
+        options: Option<TraceOptions>,
+    ) -> RpcResult<BlockTraceResult> {
+        self.trace_block(api::BlockId::Hash(hash), options).await
     }
 
     async fn trace_call(
         &self,
         request: CallRequest,
         block: Option<api::BlockId>,
-        options: Option<TracerConfig>,
-    ) -> RpcResult<CallTracerResult> {
-        self.node
-            .trace_call_impl(request, block, options)
-            .await
-            .map_err(RpcErrorAdapter::into)
+        options: Option<TraceOptions>,
+    ) -> RpcResult<TraceResult> {
+        match options.as_ref().and_then(TraceOptions::struct_log_config) {
+            Some(config) => self
+                .node
+                .trace_call_struct_logs_impl(request, block, config)
+                .await
+                .map(TraceResult::StructLogs),
+            None => self
+                .node
+                .trace_call_impl(
+                    request,
+                    block,
+                    options.and_then(TraceOptions::call_tracer_config),
+                )
+                .await
+                .map(TraceResult::CallTrace),
+        }
+        .map_err(RpcErrorAdapter::into)
     }
 
     async fn trace_transaction(
         &self,
         tx_hash: H256,
-        options: Option<TracerConfig>,
-    ) -> RpcResult<Option<CallTracerResult>> {
-        self.node
-            .trace_transaction_impl(tx_hash, options)
-            .await
-            .map_err(RpcErrorAdapter::into)
+        options: Option<TraceOptions>,
+    ) -> RpcResult<Option<TraceResult>> {
+        match options.as_ref().and_then(TraceOptions::struct_log_config) {
+            Some(config) => self
+                .node
+                .trace_transaction_struct_logs_impl(tx_hash, config)
+                .await
+                .map(|trace| trace.map(TraceResult::StructLogs)),
+            None => self
+                .node
+                .trace_transaction_impl(tx_hash, options.and_then(TraceOptions::call_tracer_config))
+                .await
+                .map(|trace| trace.map(TraceResult::CallTrace)),
+        }
+        .map_err(RpcErrorAdapter::into)
     }
 }
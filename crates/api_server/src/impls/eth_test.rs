@@ -1,8 +1,11 @@
 use anvil_zksync_api_decl::EthTestNamespaceServer;
 use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::{SimulatePayload, SimulatedBlock};
 use jsonrpsee::core::{async_trait, RpcResult};
+use zksync_types::api::BlockIdVariant;
 use zksync_types::transaction_request::CallRequest;
-use zksync_types::H256;
+use zksync_types::web3::Bytes;
+use zksync_types::{Address, H256};
 
 use crate::error::RpcErrorAdapter;
 
@@ -24,4 +27,51 @@ impl EthTestNamespaceServer for EthTestNamespace {
             .await
             .map_err(RpcErrorAdapter::into)
     }
+
+    async fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        block: Option<BlockIdVariant>,
+    ) -> RpcResult<Vec<SimulatedBlock>> {
+        self.node
+            .simulate_v1_impl(payload, block)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn sign(&self, address: Address, message: Bytes) -> RpcResult<Bytes> {
+        self.node
+            .sign_impl(address, message)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn sign_transaction(&self, tx: CallRequest) -> RpcResult<Bytes> {
+        self.node
+            .sign_transaction_impl(tx)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn sign_typed_data(
+        &self,
+        address: Address,
+        typed_data: serde_json::Value,
+    ) -> RpcResult<Bytes> {
+        self.node
+            .sign_typed_data_impl(address, typed_data)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn sign_typed_data_v4(
+        &self,
+        address: Address,
+        typed_data: serde_json::Value,
+    ) -> RpcResult<Bytes> {
+        self.node
+            .sign_typed_data_impl(address, typed_data)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
 }
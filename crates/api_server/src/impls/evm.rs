@@ -1,6 +1,8 @@
 use anvil_zksync_api_decl::EvmNamespaceServer;
 use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::NewAccount;
 use jsonrpsee::core::{async_trait, RpcResult};
+use zksync_types::Address;
 
 use crate::error::RpcErrorAdapter;
 
@@ -23,4 +25,18 @@ impl EvmNamespaceServer for EvmNamespace {
             .map_err(RpcErrorAdapter::into)?;
         Ok("0x0".to_string())
     }
+
+    async fn add_account(&self, account: NewAccount) -> RpcResult<Address> {
+        self.node
+            .add_account(account)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn remove_account(&self, address: Address) -> RpcResult<bool> {
+        self.node
+            .remove_account(address)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
 }
@@ -6,11 +6,15 @@ mod eth;
 mod eth_test;
 mod evm;
 mod net;
+mod ots;
+mod personal;
+mod trace;
 mod web3;
 mod zks;
 
 pub use self::{
     anvil::AnvilNamespace, anvil_zks::AnvilZksNamespace, config::ConfigNamespace,
     debug::DebugNamespace, eth::EthNamespace, eth_test::EthTestNamespace, evm::EvmNamespace,
-    net::NetNamespace, web3::Web3Namespace, zks::ZksNamespace,
+    net::NetNamespace, ots::OtsNamespace, personal::PersonalNamespace, trace::TraceNamespace,
+    web3::Web3Namespace, zks::ZksNamespace,
 };
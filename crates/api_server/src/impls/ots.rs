@@ -0,0 +1,121 @@
+use anvil_zksync_api_decl::OtsNamespaceServer;
+use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::{
+    OtsBlockDetails, OtsBlockTransactions, OtsContractCreator, OtsInternalOperation,
+    OtsSearchTransactions, OtsTrace,
+};
+use jsonrpsee::core::{async_trait, RpcResult};
+use zksync_types::api::BlockIdVariant;
+use zksync_types::web3::Bytes;
+use zksync_types::{Address, H256};
+
+use crate::error::RpcErrorAdapter;
+
+pub struct OtsNamespace {
+    node: InMemoryNode,
+}
+
+impl OtsNamespace {
+    pub fn new(node: InMemoryNode) -> Self {
+        Self { node }
+    }
+}
+
+#[async_trait]
+impl OtsNamespaceServer for OtsNamespace {
+    async fn get_api_level(&self) -> RpcResult<u64> {
+        Ok(self.node.ots_get_api_level_impl())
+    }
+
+    async fn get_internal_operations(&self, tx_hash: H256) -> RpcResult<Vec<OtsInternalOperation>> {
+        self.node
+            .ots_get_internal_operations_impl(tx_hash)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn has_code(&self, address: Address, block: Option<BlockIdVariant>) -> RpcResult<bool> {
+        self.node
+            .ots_has_code_impl(address, block)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_transaction_error(&self, tx_hash: H256) -> RpcResult<Bytes> {
+        self.node
+            .ots_get_transaction_error_impl(tx_hash)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn trace_transaction(&self, tx_hash: H256) -> RpcResult<Vec<OtsTrace>> {
+        self.node
+            .ots_trace_transaction_impl(tx_hash)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_block_details(&self, block_number: u64) -> RpcResult<Option<OtsBlockDetails>> {
+        self.node
+            .ots_get_block_details_impl(block_number)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_block_transactions(
+        &self,
+        block_number: u64,
+        page_number: usize,
+        page_size: usize,
+    ) -> RpcResult<Option<OtsBlockTransactions>> {
+        self.node
+            .ots_get_block_transactions_impl(block_number, page_number, page_size)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn search_transactions_before(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> RpcResult<OtsSearchTransactions> {
+        self.node
+            .ots_search_transactions_before_impl(address, block_number, page_size)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn search_transactions_after(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> RpcResult<OtsSearchTransactions> {
+        self.node
+            .ots_search_transactions_after_impl(address, block_number, page_size)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> RpcResult<Option<H256>> {
+        self.node
+            .ots_get_transaction_by_sender_and_nonce_impl(sender, nonce)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn get_contract_creator(
+        &self,
+        address: Address,
+    ) -> RpcResult<Option<OtsContractCreator>> {
+        self.node
+            .ots_get_contract_creator_impl(address)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+}
@@ -0,0 +1,27 @@
+use anvil_zksync_api_decl::PersonalNamespaceServer;
+use anvil_zksync_core::node::InMemoryNode;
+use jsonrpsee::core::{async_trait, RpcResult};
+use zksync_types::web3::Bytes;
+use zksync_types::Address;
+
+use crate::error::RpcErrorAdapter;
+
+pub struct PersonalNamespace {
+    node: InMemoryNode,
+}
+
+impl PersonalNamespace {
+    pub fn new(node: InMemoryNode) -> Self {
+        Self { node }
+    }
+}
+
+#[async_trait]
+impl PersonalNamespaceServer for PersonalNamespace {
+    async fn sign(&self, message: Bytes, address: Address) -> RpcResult<Bytes> {
+        self.node
+            .sign_impl(address, message)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+}
@@ -0,0 +1,56 @@
+use anvil_zksync_api_decl::TraceNamespaceServer;
+use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_types::api::{LocalizedTransactionTrace, TraceFilter, TraceResults};
+use jsonrpsee::core::{async_trait, RpcResult};
+use zksync_types::api::BlockNumber;
+use zksync_types::H256;
+
+use crate::error::RpcErrorAdapter;
+
+pub struct TraceNamespace {
+    node: InMemoryNode,
+}
+
+impl TraceNamespace {
+    pub fn new(node: InMemoryNode) -> Self {
+        Self { node }
+    }
+}
+
+#[async_trait]
+impl TraceNamespaceServer for TraceNamespace {
+    async fn transaction(
+        &self,
+        tx_hash: H256,
+    ) -> RpcResult<Option<Vec<LocalizedTransactionTrace>>> {
+        self.node
+            .parity_trace_transaction_impl(tx_hash)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn block(&self, block: BlockNumber) -> RpcResult<Option<Vec<LocalizedTransactionTrace>>> {
+        self.node
+            .parity_trace_block_impl(block)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn replay_transaction(
+        &self,
+        tx_hash: H256,
+        trace_types: Vec<String>,
+    ) -> RpcResult<Option<TraceResults>> {
+        self.node
+            .parity_replay_transaction_impl(tx_hash, trace_types)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+
+    async fn filter(&self, filter: TraceFilter) -> RpcResult<Vec<LocalizedTransactionTrace>> {
+        self.node
+            .parity_trace_filter_impl(filter)
+            .await
+            .map_err(RpcErrorAdapter::into)
+    }
+}
@@ -70,9 +70,11 @@ impl ZksNamespaceServer for ZksNamespace {
         rpc_unsupported(function_name!())
     }
 
-    #[named]
     async fn get_testnet_paymaster(&self) -> RpcResult<Option<Address>> {
-        rpc_unsupported(function_name!())
+        self.node
+            .get_testnet_paymaster_impl()
+            .await
+            .map_err(RpcErrorAdapter::into)
     }
 
     async fn get_bridge_contracts(&self) -> RpcResult<BridgeAddresses> {
@@ -243,13 +245,17 @@ impl ZksNamespaceServer for ZksNamespace {
         rpc_unsupported(function_name!())
     }
 
-    #[named]
     async fn get_timestamp_asserter(&self) -> RpcResult<Option<Address>> {
-        rpc_unsupported(function_name!())
+        self.node
+            .get_timestamp_asserter_impl()
+            .await
+            .map_err(RpcErrorAdapter::into)
     }
 
-    #[named]
     async fn get_l2_multicall3(&self) -> RpcResult<Option<Address>> {
-        rpc_unsupported(function_name!())
+        self.node
+            .get_l2_multicall3_impl()
+            .await
+            .map_err(RpcErrorAdapter::into)
     }
 }
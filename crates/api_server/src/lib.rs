@@ -4,6 +4,7 @@ mod server;
 
 pub use impls::{
     AnvilNamespace, AnvilZksNamespace, ConfigNamespace, DebugNamespace, EthNamespace,
-    EthTestNamespace, EvmNamespace, NetNamespace, Web3Namespace, ZksNamespace,
+    EthTestNamespace, EvmNamespace, NetNamespace, OtsNamespace, PersonalNamespace, TraceNamespace,
+    Web3Namespace, ZksNamespace,
 };
 pub use server::NodeServerBuilder;
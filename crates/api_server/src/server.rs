@@ -1,23 +1,29 @@
 use crate::{
     AnvilNamespace, AnvilZksNamespace, ConfigNamespace, DebugNamespace, EthNamespace,
-    EthTestNamespace, EvmNamespace, NetNamespace, Web3Namespace, ZksNamespace,
+    EthTestNamespace, EvmNamespace, NetNamespace, OtsNamespace, PersonalNamespace, TraceNamespace,
+    Web3Namespace, ZksNamespace,
 };
 use anvil_zksync_api_decl::{
     AnvilNamespaceServer, AnvilZksNamespaceServer, ConfigNamespaceServer, DebugNamespaceServer,
     EthNamespaceServer, EthTestNamespaceServer, EvmNamespaceServer, NetNamespaceServer,
-    Web3NamespaceServer, ZksNamespaceServer,
+    OtsNamespaceServer, PersonalNamespaceServer, TraceNamespaceServer, Web3NamespaceServer,
+    ZksNamespaceServer,
 };
-use anvil_zksync_core::node::InMemoryNode;
+use anvil_zksync_core::node::{InMemoryNode, CALL_CONSOLE_LOGS};
 use anvil_zksync_l1_sidecar::L1Sidecar;
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use http::Method;
 use jsonrpsee::server::middleware::http::ProxyGetRequestLayer;
 use jsonrpsee::server::middleware::rpc::RpcServiceT;
-use jsonrpsee::server::{MethodResponse, RpcServiceBuilder, ServerBuilder, ServerHandle};
-use jsonrpsee::types::Request;
+use jsonrpsee::server::{
+    HttpRequest, MethodResponse, RpcServiceBuilder, ServerBuilder, ServerHandle,
+};
+use jsonrpsee::types::{Request, ResponsePayload};
 use jsonrpsee::RpcModule;
+use std::cell::RefCell;
 use std::net::SocketAddr;
+use tower::util::MapRequestLayer;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use zksync_telemetry::{get_telemetry, TelemetryProps};
 
@@ -27,9 +33,17 @@ pub struct NodeServerBuilder {
     l1_sidecar: L1Sidecar,
     health_api_enabled: bool,
     cors_enabled: bool,
+    call_console_logs_enabled: bool,
     allow_origin: AllowOrigin,
 }
 
+/// Header that can be set on a request to get `console.log` output alongside `eth_call` result.
+pub const CONSOLE_LOGS_HEADER: &str = "x-anvil-console-logs";
+
+/// Marker inserted into request extensions when [`CONSOLE_LOGS_HEADER`] was set.
+#[derive(Clone, Copy, Debug)]
+struct ConsoleLogsRequested;
+
 impl NodeServerBuilder {
     pub fn new(node: InMemoryNode, l1_sidecar: L1Sidecar, allow_origin: AllowOrigin) -> Self {
         Self {
@@ -37,6 +51,7 @@ impl NodeServerBuilder {
             l1_sidecar,
             health_api_enabled: false,
             cors_enabled: false,
+            call_console_logs_enabled: false,
             allow_origin,
         }
     }
@@ -49,20 +64,32 @@ impl NodeServerBuilder {
         self.cors_enabled = true;
     }
 
+    /// Always return `console.log` output alongside `eth_call` result, even if request did not
+    /// set [`CONSOLE_LOGS_HEADER`].
+    pub fn enable_call_console_logs(&mut self) {
+        self.call_console_logs_enabled = true;
+    }
+
     fn default_rpc(node: InMemoryNode, l1_sidecar: L1Sidecar) -> RpcModule<()> {
         let mut rpc = RpcModule::new(());
         rpc.merge(EthNamespace::new(node.clone()).into_rpc())
             .unwrap();
         rpc.merge(EthTestNamespace::new(node.clone()).into_rpc())
             .unwrap();
+        rpc.merge(PersonalNamespace::new(node.clone()).into_rpc())
+            .unwrap();
         rpc.merge(AnvilNamespace::new(node.clone()).into_rpc())
             .unwrap();
-        rpc.merge(AnvilZksNamespace::new(l1_sidecar.clone()).into_rpc())
+        rpc.merge(AnvilZksNamespace::new(node.clone(), l1_sidecar.clone()).into_rpc())
             .unwrap();
         rpc.merge(EvmNamespace::new(node.clone()).into_rpc())
             .unwrap();
         rpc.merge(DebugNamespace::new(node.clone()).into_rpc())
             .unwrap();
+        rpc.merge(TraceNamespace::new(node.clone()).into_rpc())
+            .unwrap();
+        rpc.merge(OtsNamespace::new(node.clone()).into_rpc())
+            .unwrap();
         rpc.merge(NetNamespace::new(node.clone()).into_rpc())
             .unwrap();
         rpc.merge(ConfigNamespace::new(node.clone()).into_rpc())
@@ -81,23 +108,39 @@ impl NodeServerBuilder {
             // for more details.
             CorsLayer::new()
                 .allow_origin(self.allow_origin.clone())
-                .allow_headers([http::header::CONTENT_TYPE])
+                .allow_headers([
+                    http::header::CONTENT_TYPE,
+                    http::HeaderName::from_static(CONSOLE_LOGS_HEADER),
+                ])
                 .allow_methods([Method::GET, Method::POST])
         }));
         let health_api_layer = tower::util::option_layer(
             self.health_api_enabled
                 .then(|| ProxyGetRequestLayer::new("/health", "web3_clientVersion").unwrap()),
         );
+        let console_logs_layer = MapRequestLayer::new(|mut request: HttpRequest| {
+            if request.headers().contains_key(CONSOLE_LOGS_HEADER) {
+                request.extensions_mut().insert(ConsoleLogsRequested);
+            }
+            request
+        });
+        let call_console_logs_enabled = self.call_console_logs_enabled;
         let server_builder = ServerBuilder::default()
             .http_only()
             .set_http_middleware(
                 tower::ServiceBuilder::new()
                     .layer(cors_layers)
-                    .layer(health_api_layer),
+                    .layer(health_api_layer)
+                    .layer(console_logs_layer),
             )
             .set_rpc_middleware(RpcServiceBuilder::new().rpc_logger(100))
             .set_rpc_middleware(
-                RpcServiceBuilder::new().layer_fn(move |service| TelemetryReporter { service }),
+                RpcServiceBuilder::new()
+                    .layer_fn(move |service| TelemetryReporter { service })
+                    .layer_fn(move |service| CallConsoleLogsReporter {
+                        service,
+                        always_enabled: call_console_logs_enabled,
+                    }),
             );
 
         match server_builder.build(addr).await {
@@ -168,3 +211,53 @@ where
         .boxed()
     }
 }
+
+/// Wraps successful `eth_call` responses into `{ "returnData": ..., "consoleLogs": [...] }` when
+/// console logs were requested (either globally or via [`CONSOLE_LOGS_HEADER`]).
+#[derive(Clone)]
+pub struct CallConsoleLogsReporter<S> {
+    service: S,
+    always_enabled: bool,
+}
+
+impl<'a, S> RpcServiceT<'a> for CallConsoleLogsReporter<S>
+where
+    S: RpcServiceT<'a> + Send + Sync + Clone + 'static,
+{
+    type Future = BoxFuture<'a, MethodResponse>;
+
+    fn call(&self, req: Request<'a>) -> Self::Future {
+        let service = self.service.clone();
+        let requested =
+            self.always_enabled || req.extensions().get::<ConsoleLogsRequested>().is_some();
+        if req.method_name() != "eth_call" || !requested {
+            return async move { service.call(req).await }.boxed();
+        }
+
+        let id = req.id().into_owned();
+        CALL_CONSOLE_LOGS
+            .scope(RefCell::new(Vec::new()), async move {
+                let response = service.call(req).await;
+                let console_logs = CALL_CONSOLE_LOGS.with(|logs| logs.take());
+                if !response.is_success() {
+                    return response;
+                }
+                let Some(return_data) =
+                    serde_json::from_str::<serde_json::Value>(response.as_result())
+                        .ok()
+                        .and_then(|mut value| value.get_mut("result").map(|v| v.take()))
+                else {
+                    return response;
+                };
+                MethodResponse::response(
+                    id,
+                    ResponsePayload::success(serde_json::json!({
+                        "returnData": return_data,
+                        "consoleLogs": console_logs,
+                    })),
+                    usize::MAX,
+                )
+            })
+            .boxed()
+    }
+}
@@ -1,5 +1,6 @@
 use crate::utils::{
-    get_cli_command_telemetry_props, parse_genesis_file, TELEMETRY_SENSITIVE_VALUE,
+    get_cli_command_telemetry_props, parse_fork_endpoints_file, parse_genesis_file,
+    TELEMETRY_SENSITIVE_VALUE,
 };
 use alloy::signers::local::coins_bip39::{English, Mnemonic};
 use anvil_zksync_common::{
@@ -7,18 +8,20 @@ use anvil_zksync_common::{
     sh_err, sh_warn,
     utils::io::write_json_file,
 };
-use anvil_zksync_config::types::{AccountGenerator, Genesis, SystemContractsOptions};
+use anvil_zksync_config::types::{
+    AccountGenerator, ForkEndpointsFile, ForkRpcConfig, Genesis, SystemContractsOptions,
+};
 use anvil_zksync_config::{
     constants::{DEFAULT_MNEMONIC, TEST_NODE_NETWORK_ID},
     types::BoojumConfig,
 };
 use anvil_zksync_config::{BaseTokenConfig, L1Config, TestNodeConfig};
-use anvil_zksync_core::node::fork::ForkConfig;
+pub use anvil_zksync_core::node::fork_url::{fork_urls_to_config, BuiltinNetwork, ForkUrl};
 use anvil_zksync_core::node::{InMemoryNode, VersionedState};
 use anvil_zksync_types::{
     LogLevel, ShowGasDetails, ShowStorageLogs, ShowVMDetails, TransactionOrder,
 };
-use clap::{arg, command, ArgAction, Parser, Subcommand, ValueEnum};
+use clap::{arg, command, ArgAction, Parser, Subcommand};
 use flate2::read::GzDecoder;
 use futures::FutureExt;
 use num::rational::Ratio;
@@ -27,9 +30,8 @@ use std::collections::HashMap;
 use std::env;
 use std::io::Read;
 use std::net::IpAddr;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::time::Duration;
 use std::{
     future::Future,
@@ -37,7 +39,6 @@ use std::{
     task::{Context, Poll},
 };
 use tokio::time::{Instant, Interval};
-use url::Url;
 use zksync_telemetry::TelemetryProps;
 use zksync_types::fee_model::BaseTokenConversionRatio;
 use zksync_types::{ProtocolVersionId, H256, U256};
@@ -111,6 +112,29 @@ pub struct Cli {
     /// Show gas details information.
     pub show_gas_details: Option<ShowGasDetails>,
 
+    #[arg(long, help_heading = "Debugging Options")]
+    /// Print gas usage aggregated by contract and function on shutdown.
+    pub gas_report: bool,
+
+    #[arg(long, help_heading = "Debugging Options")]
+    /// Record a gas profile for every executed transaction, to be served by `anvil_getGasProfile`.
+    pub gas_profile: bool,
+
+    #[arg(long, value_name = "DIR", help_heading = "Debugging Options")]
+    /// Directory to write per-transaction gas profiles to (folded stacks and SVG flamegraph).
+    /// Implies `--gas-profile`.
+    pub gas_profile_dir: Option<PathBuf>,
+
+    #[arg(long, help_heading = "Debugging Options")]
+    /// Record instruction-level struct logs (EraVM instructions and interpreted EVM opcodes) for
+    /// every executed transaction, to be served by `debug_traceTransaction`. Slows down execution.
+    pub struct_logs: bool,
+
+    #[arg(long, value_name = "NUM", help_heading = "Debugging Options")]
+    /// Maximum number of struct logs recorded per transaction, `0` means unlimited
+    /// (default: 100000). Later instructions are dropped.
+    pub struct_logs_limit: Option<usize>,
+
     /// Increments verbosity each time it is used. (-vv, -vvv)
     ///
     /// Example usage:
@@ -172,6 +196,17 @@ pub struct Cli {
     /// Enables EVM interpreter.
     pub evm_interpreter: bool,
 
+    #[arg(long, help_heading = "System Configuration")]
+    /// Predeploy a testnet paymaster, an approval-based ERC20 paymaster and a dev ERC20 token at
+    /// genesis, funded from the dev accounts. Requires `--evm-interpreter` (not available in fork
+    /// mode).
+    pub testnet_paymaster: bool,
+
+    #[arg(long, default_missing_value = "true", num_args(0..=1), help_heading = "System Configuration")]
+    /// Install Multicall3 and the deterministic deployment proxy at their canonical addresses on a
+    /// new local network. Requires `--evm-interpreter` (default: same as `--evm-interpreter`).
+    pub predeploys: Option<bool>,
+
     #[clap(flatten)]
     /// BoojumOS detailed config.
     pub boojum_group: BoojumGroup,
@@ -202,6 +237,31 @@ pub struct Cli {
     /// Cache directory location for disk cache (default: .cache).
     pub cache_dir: Option<String>,
 
+    // Fork Client Options
+    /// Number of times a failed request to the forked network is retried (default: 5).
+    #[arg(long, value_name = "NUM", help_heading = "Fork Client Options")]
+    pub fork_retries: Option<u32>,
+
+    /// Initial delay in milliseconds before retrying a failed request to the forked network,
+    /// doubled after every attempt (default: 500). A `Retry-After` header sent by the remote
+    /// endpoint takes precedence.
+    #[arg(long, value_name = "MILLIS", help_heading = "Fork Client Options")]
+    pub fork_retry_backoff: Option<u64>,
+
+    /// Timeout in seconds for a single request to the forked network (default: 30).
+    #[arg(long, value_name = "SECONDS", value_parser = duration_from_secs_f64, help_heading = "Fork Client Options")]
+    pub fork_request_timeout: Option<Duration>,
+
+    /// Maximum number of requests per second sent to the forked network (default: unlimited).
+    #[arg(long, value_name = "NUM", help_heading = "Fork Client Options")]
+    pub fork_requests_per_second: Option<NonZeroUsize>,
+
+    /// JSON file with named fork endpoints and their headers, credentials, timeouts and
+    /// concurrency settings. Names can be passed to `--fork-url` and take precedence over the
+    /// builtin networks.
+    #[arg(long, value_name = "FILE", value_parser = parse_fork_endpoints_file, help_heading = "Fork Client Options")]
+    pub fork_endpoints: Option<ForkEndpointsFile>,
+
     /// Number of dev accounts to generate and configure.
     #[arg(
         long,
@@ -265,6 +325,13 @@ pub struct Cli {
     #[arg(long, conflicts_with = "init", default_value = "false")]
     pub preserve_historical_states: bool,
 
+    /// Include private keys of accounts managed by the node (see `evm_addAccount`) when dumping the
+    /// state, so that they are managed again once the state is loaded.
+    ///
+    /// Keys are stored in plain text, only use this with throwaway accounts.
+    #[arg(long, conflicts_with = "init", default_value = "false")]
+    pub dump_signer_accounts: bool,
+
     /// Initialize the chain from a previously saved state snapshot.
     #[arg(long, value_name = "PATH", conflicts_with = "init")]
     pub load_state: Option<PathBuf>,
@@ -302,6 +369,11 @@ pub struct Cli {
     )]
     pub auto_impersonate: bool,
 
+    /// Enables automatic funding of transaction senders. A transaction whose initiator cannot
+    /// cover `gas_limit * max_fee_per_gas + value` gets its balance topped up when it is sealed.
+    #[arg(long, help_heading = "Account Configuration")]
+    pub auto_fund: bool,
+
     /// Block time in seconds for interval sealing.
     /// If unset, node seals a new block as soon as there is at least one transaction.
     #[arg(short, long, value_name = "SECONDS", value_parser = duration_from_secs_f64, help_heading = "Block Sealing")]
@@ -319,6 +391,11 @@ pub struct Cli {
     #[arg(long, conflicts_with = "allow_origin", help_heading = "Server options")]
     pub no_cors: bool,
 
+    /// Return `console.log` output alongside every `eth_call` result. Can also be requested
+    /// per-call by setting the `X-Anvil-Console-Logs` header.
+    #[arg(long, help_heading = "Server options")]
+    pub call_console_logs: bool,
+
     /// Transaction ordering in the mempool.
     #[arg(long, default_value = DEFAULT_TX_ORDER)]
     pub order: TransactionOrder,
@@ -382,6 +459,10 @@ pub enum Command {
     /// Starts a local network that is a fork of another network, and replays a given TX on it.
     #[command(name = "replay_tx")]
     ReplayTx(ReplayArgs),
+    /// Forks another network right before a range of blocks, replays the blocks on it and reports
+    /// any differences from the remote results.
+    #[command(name = "replay_blocks")]
+    ReplayBlocks(ReplayBlocksArgs),
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -400,13 +481,20 @@ pub struct ForkArgs {
     ///   • `wonderchain-testnet`
     ///   • `zkcandy`
     ///  - http://XXX:YY
+    ///  - name of a network from the `--fork-endpoints` file (takes precedence over builtins)
+    ///
+    /// Several endpoints serving the same network can be passed as a comma-separated list (or by
+    /// repeating the flag); requests are then load-balanced across them and failing endpoints are
+    /// temporarily taken out of rotation.
     #[arg(
         long,
         alias = "network",
         value_enum,
-        help = "Which network to fork (builtins) or an HTTP(S) URL"
+        value_delimiter = ',',
+        required_unless_present_any = ["fork_replay", "fork_state"],
+        help = "Which network to fork (builtins) or HTTP(S) URL(s)"
     )]
-    pub fork_url: ForkUrl,
+    pub fork_url: Vec<ForkUrl>,
     // Fork at a given L2 miniblock height.
     // If not set - will use the current finalized block from the network.
     #[arg(
@@ -424,9 +512,87 @@ pub struct ForkArgs {
         long,
         requires = "fork_url",
         value_name = "TRANSACTION",
-        conflicts_with = "fork_block_number"
+        conflicts_with_all = ["fork_block_number", "fork_timestamp", "fork_l1_batch"]
     )]
     pub fork_transaction_hash: Option<H256>,
+
+    /// Fetch state from the last block whose timestamp (in seconds since the Unix epoch) is at or
+    /// before the given one. The block is found by binary searching the remote chain.
+    #[arg(
+        long,
+        requires = "fork_url",
+        value_name = "TIMESTAMP",
+        conflicts_with_all = ["fork_block_number", "fork_l1_batch"]
+    )]
+    pub fork_timestamp: Option<u64>,
+
+    /// Fetch state from the last block of the given L1 batch.
+    #[arg(
+        long,
+        requires = "fork_url",
+        value_name = "BATCH",
+        conflicts_with = "fork_block_number"
+    )]
+    pub fork_l1_batch: Option<u32>,
+
+    /// Record every request made to the forked network (and its response) to a fixture file
+    /// that a later run can be served from with `--fork-replay`.
+    #[arg(long, value_name = "FILE", conflicts_with = "fork_replay")]
+    pub fork_record: Option<PathBuf>,
+
+    /// Serve the fork purely from a fixture recorded with `--fork-record` without any network
+    /// access. Requests missing from the fixture fail.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "fork_url",
+            "fork_block_number",
+            "fork_transaction_hash",
+            "fork_timestamp",
+            "fork_l1_batch",
+        ]
+    )]
+    pub fork_replay: Option<PathBuf>,
+
+    /// Fork from a local file instead of a remote network: either a state dump (as written by
+    /// `--dump-state` or `anvil_dumpState`) or a state snapshot holding storage slots and factory
+    /// deps along with the fork block's metadata. Storage, historical blocks and fee parameters
+    /// are all served from the file.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "fork_url",
+            "fork_block_number",
+            "fork_transaction_hash",
+            "fork_timestamp",
+            "fork_l1_batch",
+            "fork_record",
+            "fork_replay",
+        ]
+    )]
+    pub fork_state: Option<PathBuf>,
+
+    /// Keep following the forked chain's head: new remote blocks are periodically fetched and
+    /// their transactions are re-executed on top of the local state, interleaved with local
+    /// transactions. Remote transactions whose outcome changes locally are reported. Can be paused
+    /// and resumed with `anvil_setForkFollowing`.
+    #[arg(
+        long,
+        conflicts_with_all = ["fork_transaction_hash", "fork_replay", "fork_state"]
+    )]
+    pub fork_follow: bool,
+
+    /// How often (in seconds) the forked chain is polled for new blocks in `--fork-follow` mode.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 2,
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "fork_follow"
+    )]
+    pub fork_follow_interval: u64,
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -446,149 +612,43 @@ pub struct ReplayArgs {
     ///   • `zkcandy`
     ///   • custom HTTP(S) URL
     ///  - http://XXX:YY
+    ///
+    /// See `fork --fork-url` for passing several endpoints.
     #[arg(
         long,
         alias = "network",
         value_enum,
-        help = "Which network to fork (builtins) or an HTTP(S) URL"
+        value_delimiter = ',',
+        required = true,
+        help = "Which network to fork (builtins) or HTTP(S) URL(s)"
     )]
-    pub fork_url: ForkUrl,
+    pub fork_url: Vec<ForkUrl>,
     /// Transaction hash to replay.
     #[arg(help = "Transaction hash to replay.")]
     pub tx: H256,
 }
 
-// Elastic Network ZK Chains
-#[derive(Debug, Clone, ValueEnum)]
-#[value(rename_all = "kebab-case")]
-pub enum BuiltinNetwork {
-    #[value(alias = "mainnet")]
-    Era,
-    #[value(alias = "sepolia-testnet")]
-    EraTestnet,
-    Abstract,
-    AbstractTestnet,
-    #[value(alias = "sophon-mainnet")]
-    Sophon,
-    SophonTestnet,
-    Cronos,
-    CronosTestnet,
-    Lens,
-    LensTestnet,
-    Openzk,
-    OpenzkTestnet,
-    WonderchainTestnet,
-    Zkcandy,
-}
-
-/// ForkUrl is used to specify the URL of the forked network.
-#[derive(Debug, Clone)]
-pub enum ForkUrl {
-    Builtin(BuiltinNetwork),
-    Custom(Url),
-}
-
-impl BuiltinNetwork {
-    /// Converts the BuiltinNetwork to a ForkConfig.
-    pub fn to_fork_config(&self) -> ForkConfig {
-        match self {
-            BuiltinNetwork::Era => ForkConfig {
-                url: "https://mainnet.era.zksync.io".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::EraTestnet => ForkConfig {
-                url: "https://sepolia.era.zksync.dev".parse().unwrap(),
-                estimate_gas_price_scale_factor: 2.0,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::Abstract => ForkConfig {
-                url: "https://api.mainnet.abs.xyz".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::AbstractTestnet => ForkConfig {
-                url: "https://api.testnet.abs.xyz".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::Sophon => ForkConfig {
-                url: "https://rpc.sophon.xyz".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::SophonTestnet => ForkConfig {
-                url: "https://rpc.testnet.sophon.xyz".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::Cronos => ForkConfig {
-                url: "https://mainnet.zkevm.cronos.org".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::CronosTestnet => ForkConfig {
-                url: "https://testnet.zkevm.cronos.org".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::Lens => ForkConfig {
-                url: "https://rpc.lens.xyz".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::LensTestnet => ForkConfig {
-                url: "https://rpc.testnet.lens.xyz".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::Openzk => ForkConfig {
-                url: "https://rpc.openzk.net".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::OpenzkTestnet => ForkConfig {
-                url: "https://openzk-testnet.rpc.caldera.xyz/http"
-                    .parse()
-                    .unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::WonderchainTestnet => ForkConfig {
-                url: "https://rpc.testnet.wonderchain.org".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-            BuiltinNetwork::Zkcandy => ForkConfig {
-                url: "https://rpc.zkcandy.io".parse().unwrap(),
-                estimate_gas_price_scale_factor: 1.5,
-                estimate_gas_scale_factor: 1.3,
-            },
-        }
-    }
-}
-
-impl ForkUrl {
-    /// Converts the ForkUrl to a ForkConfig.
-    pub fn to_config(&self) -> ForkConfig {
-        match self {
-            ForkUrl::Builtin(net) => net.to_fork_config(),
-            ForkUrl::Custom(url) => ForkConfig::unknown(url.clone()),
-        }
-    }
-}
-
-impl FromStr for ForkUrl {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(net) = BuiltinNetwork::from_str(s, true) {
-            return Ok(ForkUrl::Builtin(net));
-        }
-        Url::parse(s)
-            .map(ForkUrl::Custom)
-            .map_err(|e| format!("`{}` is neither a known network nor a valid URL: {}", s, e))
-    }
+#[derive(Debug, Parser, Clone)]
+pub struct ReplayBlocksArgs {
+    /// Network to replay blocks from, see `replay_tx --fork-url`.
+    #[arg(
+        long,
+        alias = "network",
+        value_enum,
+        value_delimiter = ',',
+        required = true,
+        help = "Which network to fork (builtins) or HTTP(S) URL(s)"
+    )]
+    pub fork_url: Vec<ForkUrl>,
+    /// First block to replay, the network is forked at the block preceding it.
+    #[arg(long, value_name = "BLOCK", value_parser = clap::value_parser!(u32).range(1..))]
+    pub from: u32,
+    /// Last block to replay (inclusive).
+    #[arg(long, value_name = "BLOCK")]
+    pub to: u32,
+    /// Write the divergence report as JSON to the given file.
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
 }
 
 impl Cli {
@@ -685,6 +745,11 @@ impl Cli {
             .with_vm_log_detail(self.show_vm_details)
             .with_show_storage_logs(self.show_storage_logs)
             .with_show_gas_details(self.show_gas_details)
+            .with_gas_report(self.gas_report)
+            .with_gas_profile(self.gas_profile)
+            .with_gas_profile_dir(self.gas_profile_dir)
+            .with_struct_logs(self.struct_logs)
+            .with_struct_logs_limit(self.struct_logs_limit)
             .with_gas_limit_scale(self.limit_scale_factor)
             .with_price_scale(self.price_scale_factor)
             .with_verbosity_level(self.verbosity)
@@ -699,6 +764,9 @@ impl Cli {
             .with_log_file_path(self.log_file_path.clone())
             .with_account_generator(self.account_generator())
             .with_auto_impersonate(self.auto_impersonate)
+            .with_auto_fund(self.auto_fund)
+            .with_fork_rpc(self.fork_rpc_config())
+            .with_fork_endpoints(self.fork_endpoints.clone().unwrap_or_default())
             .with_genesis_balance(genesis_balance)
             .with_cache_dir(self.cache_dir.clone())
             .with_cache_config(self.cache.map(|cache_type| {
@@ -723,6 +791,8 @@ impl Cli {
             } else {
                 None
             })
+            .with_testnet_paymaster(self.testnet_paymaster)
+            .with_predeploys(Some(self.predeploys.unwrap_or(self.evm_interpreter)))
             .with_boojum(self.boojum_group.into())
             .with_health_check_endpoint(if self.health_check_endpoint {
                 Some(true)
@@ -733,11 +803,13 @@ impl Cli {
             .with_no_mining(self.no_mining)
             .with_allow_origin(self.allow_origin)
             .with_no_cors(self.no_cors)
+            .with_call_console_logs(self.call_console_logs)
             .with_transaction_order(self.order)
             .with_state(self.state)
             .with_state_interval(self.state_interval)
             .with_dump_state(self.dump_state)
             .with_preserve_historical_states(self.preserve_historical_states)
+            .with_dump_signer_accounts(self.dump_signer_accounts)
             .with_load_state(self.load_state)
             .with_l1_config(self.l1_group.and_then(|group| {
                 group.spawn_l1.map(|port| L1Config::Spawn { port }).or(group
@@ -765,6 +837,21 @@ impl Cli {
             });
         }
 
+        if config.predeploys && !config.use_evm_interpreter {
+            return Err(zksync_error::anvil_zksync::env::InvalidArguments {
+                details: "predeploys require the EVM interpreter (`--evm-interpreter`)".into(),
+                arguments: debug_self_repr,
+            });
+        }
+
+        if config.testnet_paymaster && !config.use_evm_interpreter {
+            return Err(zksync_error::anvil_zksync::env::InvalidArguments {
+                details: "testnet paymaster requires the EVM interpreter (`--evm-interpreter`)"
+                    .into(),
+                arguments: debug_self_repr,
+            });
+        }
+
         Ok(config)
     }
 
@@ -806,6 +893,16 @@ impl Cli {
                 "show_gas_details",
                 self.show_gas_details.map(|v| v.to_string()),
             )
+            .insert_with("gas_report", self.gas_report, |v| v.then_some(v))
+            .insert_with("gas_profile", self.gas_profile, |v| v.then_some(v))
+            .insert_with("gas_profile_dir", self.gas_profile_dir, |v| {
+                v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+            })
+            .insert_with("struct_logs", self.struct_logs, |v| v.then_some(v))
+            .insert(
+                "struct_logs_limit",
+                self.struct_logs_limit.map(serde_json::Number::from),
+            )
             .insert(
                 "l1_gas_price",
                 self.l1_gas_price.map(serde_json::Number::from),
@@ -842,6 +939,10 @@ impl Cli {
                 self.protocol_version.map(|v| v.to_string()),
             )
             .insert_with("evm_interpreter", self.evm_interpreter, |v| v.then_some(v))
+            .insert_with("testnet_paymaster", self.testnet_paymaster, |v| {
+                v.then_some(v)
+            })
+            .insert("predeploys", self.predeploys)
             .insert("log", self.log.map(|v| v.to_string()))
             .insert_with("log_file_path", self.log_file_path, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
@@ -852,6 +953,26 @@ impl Cli {
             .insert_with("cache_dir", self.cache_dir, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
             })
+            .insert(
+                "fork_retries",
+                self.fork_retries.map(serde_json::Number::from),
+            )
+            .insert(
+                "fork_retry_backoff",
+                self.fork_retry_backoff.map(serde_json::Number::from),
+            )
+            .insert(
+                "fork_request_timeout",
+                self.fork_request_timeout.map(|v| format!("{:?}", v)),
+            )
+            .insert(
+                "fork_requests_per_second",
+                self.fork_requests_per_second
+                    .map(|v| serde_json::Number::from(v.get())),
+            )
+            .insert_with("fork_endpoints", self.fork_endpoints, |v| {
+                v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+            })
             .insert_with("accounts", self.accounts, |v| {
                 (v.to_string() != DEFAULT_ACCOUNTS).then_some(serde_json::Number::from(v))
             })
@@ -875,6 +996,9 @@ impl Cli {
                 self.preserve_historical_states,
                 |v| v.then_some(v),
             )
+            .insert_with("dump_signer_accounts", self.dump_signer_accounts, |v| {
+                v.then_some(v)
+            })
             .insert_with("load_state", self.load_state, |v| {
                 v.map(|_| TELEMETRY_SENSITIVE_VALUE)
             })
@@ -893,18 +1017,36 @@ impl Cli {
             .insert_with("auto_impersonate", self.auto_impersonate, |v| {
                 v.then_some(v)
             })
+            .insert_with("auto_fund", self.auto_fund, |v| v.then_some(v))
             .insert("block_time", self.block_time.map(|v| format!("{:?}", v)))
             .insert_with("no_mining", self.no_mining, |v| v.then_some(v))
             .insert_with("allow_origin", self.allow_origin, |v| {
                 (v != DEFAULT_ALLOW_ORIGIN).then_some(TELEMETRY_SENSITIVE_VALUE)
             })
             .insert_with("no_cors", self.no_cors, |v| v.then_some(v))
+            .insert_with("call_console_logs", self.call_console_logs, |v| {
+                v.then_some(v)
+            })
             .insert_with("order", self.order, |v| {
                 (v.to_string() != DEFAULT_TX_ORDER).then_some(v.to_string())
             })
             .take()
     }
 
+    fn fork_rpc_config(&self) -> ForkRpcConfig {
+        let default = ForkRpcConfig::default();
+        ForkRpcConfig {
+            retries: self.fork_retries.unwrap_or(default.retries),
+            retry_backoff: self
+                .fork_retry_backoff
+                .map(Duration::from_millis)
+                .unwrap_or(default.retry_backoff),
+            request_timeout: self.fork_request_timeout.unwrap_or(default.request_timeout),
+            requests_per_second: self.fork_requests_per_second,
+            ..default
+        }
+    }
+
     fn account_generator(&self) -> AccountGenerator {
         let mut gen = AccountGenerator::new(self.accounts as usize)
             .phrase(DEFAULT_MNEMONIC)
@@ -1119,6 +1261,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rejects_zero_fork_follow_interval() {
+        let fork_follow = |interval: &str| {
+            Cli::try_parse_from([
+                "anvil-zksync",
+                "fork",
+                "--fork-url",
+                "mainnet",
+                "--fork-follow",
+                "--fork-follow-interval",
+                interval,
+            ])
+        };
+        assert!(fork_follow("0").is_err());
+        assert!(fork_follow("1").is_ok());
+    }
+
     #[tokio::test]
     async fn test_dump_state() -> anyhow::Result<()> {
         let temp_dir = tempfile::Builder::new()
@@ -1,5 +1,5 @@
 use crate::bytecode_override::override_bytecodes;
-use crate::cli::{BuiltinNetwork, Cli, Command, ForkUrl, PeriodicStateDumper};
+use crate::cli::{fork_urls_to_config, BuiltinNetwork, Cli, Command, ForkUrl, PeriodicStateDumper};
 use crate::utils::update_with_fork_details;
 use alloy::primitives::Bytes;
 use anvil_zksync_api_server::NodeServerBuilder;
@@ -15,10 +15,13 @@ use anvil_zksync_config::constants::{
 use anvil_zksync_config::types::SystemContractsOptions;
 use anvil_zksync_config::{ForkPrintInfo, L1Config};
 use anvil_zksync_core::filters::EthFilters;
-use anvil_zksync_core::node::fork::ForkClient;
+use anvil_zksync_core::formatter::gas_report::GasReportTable;
+use anvil_zksync_core::formatter::replay_report::BlockReplayReportTable;
+use anvil_zksync_core::node::fork::{ForkAt, ForkClient};
 use anvil_zksync_core::node::{
-    BlockSealer, BlockSealerMode, ImpersonationManager, InMemoryNode, InMemoryNodeInner,
-    NodeExecutor, StorageKeyLayout, TestNodeFeeInputProvider, TxBatch, TxPool,
+    BlockSealer, BlockSealerMode, ForkFollower, ForkFollowerState, ImpersonationManager,
+    InMemoryNode, InMemoryNodeInner, NodeExecutor, StorageKeyLayout, TestNodeFeeInputProvider,
+    TxBatch, TxPool,
 };
 use anvil_zksync_core::observability::Observability;
 use anvil_zksync_core::system_contracts::SystemContractsBuilder;
@@ -44,7 +47,8 @@ use zksync_error::{ICustomError, IError as _};
 use zksync_telemetry::{get_telemetry, init_telemetry, TelemetryProps};
 use zksync_types::fee_model::{FeeModelConfigV2, FeeParams};
 use zksync_types::{
-    L2BlockNumber, Nonce, CONTRACT_DEPLOYER_ADDRESS, EVM_PREDEPLOYS_MANAGER_ADDRESS, H160, U256,
+    L1BatchNumber, L2BlockNumber, Nonce, CONTRACT_DEPLOYER_ADDRESS, EVM_PREDEPLOYS_MANAGER_ADDRESS,
+    H160, U256,
 };
 
 mod bytecode_override;
@@ -140,7 +144,9 @@ async fn start_program() -> Result<(), AnvilZksyncError> {
             } else {
                 // Initialize the client to get the fee params
                 let client = ForkClient::at_block_number(
-                    ForkUrl::Builtin(BuiltinNetwork::Era).to_config(),
+                    ForkUrl::Builtin(BuiltinNetwork::Era)
+                        .to_config(&config.fork_endpoints, &config.fork_rpc)
+                        .map_err(to_domain)?,
                     None,
                 )
                 .await
@@ -183,36 +189,98 @@ async fn start_program() -> Result<(), AnvilZksyncError> {
             }
         }
         Command::Fork(fork) => {
-            let (fork_client, earlier_txs) = if let Some(tx_hash) = fork.fork_transaction_hash {
+            let (fork_client, earlier_txs) = if let Some(path) = &fork.fork_replay {
+                // Fixture holds everything needed to fork, the network is never contacted
+                ForkClient::from_fixture(path).map_err(to_domain)?
+            } else if let Some(path) = &fork.fork_state {
+                // State file holds everything needed to fork, the network is never contacted
+                (ForkClient::from_file(path).map_err(to_domain)?, Vec::new())
+            } else if let Some(tx_hash) = fork.fork_transaction_hash {
                 // If transaction hash is provided, we fork at the parent of block containing tx
-                ForkClient::at_before_tx(fork.fork_url.to_config(), tx_hash)
-                    .await
-                    .map_err(to_domain)?
+                ForkClient::at_before_tx(
+                    fork_urls_to_config(&fork.fork_url, &config.fork_endpoints, &config.fork_rpc)
+                        .map_err(to_domain)?,
+                    tx_hash,
+                )
+                .await
+                .map_err(to_domain)?
             } else {
-                // Otherwise, we fork at the provided block
+                // Otherwise, we fork at the provided block, timestamp or L1 batch
+                let fork_at = if let Some(timestamp) = fork.fork_timestamp {
+                    ForkAt::Timestamp(timestamp)
+                } else if let Some(batch_number) = fork.fork_l1_batch {
+                    ForkAt::L1Batch(L1BatchNumber(batch_number))
+                } else {
+                    fork.fork_block_number
+                        .map(|bn| L2BlockNumber(bn as u32))
+                        .into()
+                };
                 (
-                    ForkClient::at_block_number(
-                        fork.fork_url.to_config(),
-                        fork.fork_block_number.map(|bn| L2BlockNumber(bn as u32)),
+                    ForkClient::at(
+                        fork_urls_to_config(
+                            &fork.fork_url,
+                            &config.fork_endpoints,
+                            &config.fork_rpc,
+                        )
+                        .map_err(to_domain)?,
+                        fork_at,
                     )
                     .await
                     .map_err(to_domain)?,
                     Vec::new(),
                 )
             };
+            let fork_client = if let Some(path) = &fork.fork_record {
+                fork_client
+                    .with_recording(path, &earlier_txs)
+                    .map_err(to_domain)?
+            } else {
+                fork_client
+            };
 
             update_with_fork_details(&mut config, &fork_client.details).await;
             (Some(fork_client), earlier_txs)
         }
         Command::ReplayTx(replay_tx) => {
-            let (fork_client, earlier_txs) =
-                ForkClient::at_before_tx(replay_tx.fork_url.to_config(), replay_tx.tx)
-                    .await
-                    .map_err(to_domain)?;
+            let (fork_client, earlier_txs) = ForkClient::at_before_tx(
+                fork_urls_to_config(
+                    &replay_tx.fork_url,
+                    &config.fork_endpoints,
+                    &config.fork_rpc,
+                )
+                .map_err(to_domain)?,
+                replay_tx.tx,
+            )
+            .await
+            .map_err(to_domain)?;
 
             update_with_fork_details(&mut config, &fork_client.details).await;
             (Some(fork_client), earlier_txs)
         }
+        Command::ReplayBlocks(replay_blocks) => {
+            if replay_blocks.to < replay_blocks.from {
+                return Err(to_domain(generic_error!(
+                    "--to ({}) must not precede --from ({})",
+                    replay_blocks.to,
+                    replay_blocks.from
+                )));
+            }
+            // Fork right before the first replayed block
+            let fork_client = ForkClient::at_block_number(
+                fork_urls_to_config(
+                    &replay_blocks.fork_url,
+                    &config.fork_endpoints,
+                    &config.fork_rpc,
+                )
+                .map_err(to_domain)?,
+                Some(L2BlockNumber(replay_blocks.from - 1)),
+            )
+            .await
+            .map_err(to_domain)?;
+
+            update_with_fork_details(&mut config, &fork_client.details).await;
+            (Some(fork_client), Vec::new())
+        }
     };
 
     // Ensure that system_contracts_path is only used with Local.
@@ -367,6 +435,12 @@ async fn start_program() -> Result<(), AnvilZksyncError> {
     let (block_sealer, block_sealer_state) =
         BlockSealer::new(sealing_mode, pool.clone(), node_handle.clone());
     node_service_tasks.push(Box::pin(block_sealer.run()));
+    let fork_follow_interval = match command {
+        Command::Fork(fork) if fork.fork_follow => {
+            Some(Duration::from_secs(fork.fork_follow_interval))
+        }
+        _ => None,
+    };
 
     let node: InMemoryNode = InMemoryNode::new(
         node_inner,
@@ -379,9 +453,13 @@ async fn start_program() -> Result<(), AnvilZksyncError> {
         impersonation,
         pool,
         block_sealer_state,
+        ForkFollowerState::new(fork_follow_interval.is_some()),
         system_contracts,
         storage_key_layout,
     );
+    if let Some(interval) = fork_follow_interval {
+        node_service_tasks.push(Box::pin(ForkFollower::new(node.clone(), interval).run()));
+    }
 
     // We start the node executor now so it can receive and handle commands
     // during replay. Otherwise, replay would send commands and hang.
@@ -454,6 +532,31 @@ async fn start_program() -> Result<(), AnvilZksyncError> {
             .unwrap();
     }
 
+    if config.predeploys && !is_fork_mode {
+        node.install_predeploys().await.map_err(to_domain)?;
+    }
+
+    // Testnet paymasters are only predeployed on a fresh local chain
+    if config.testnet_paymaster {
+        if is_fork_mode {
+            sh_warn!("Testnet paymasters are not predeployed in fork mode");
+        } else {
+            let paymasters = node.deploy_testnet_paymasters().await.map_err(to_domain)?;
+            sh_println!(
+                r#"
+Testnet Paymasters
+========================
+Paymaster:             {:#x}
+ERC20 Paymaster:       {:#x}
+ERC20 Token:           {:#x}
+"#,
+                paymasters.paymaster,
+                paymasters.erc20_paymaster,
+                paymasters.erc20_token,
+            );
+        }
+    }
+
     if !transactions_to_replay.is_empty() {
         sh_println!("Executing transactions from the block.");
         let total_txs = transactions_to_replay.len() as u64;
@@ -484,6 +587,39 @@ async fn start_program() -> Result<(), AnvilZksyncError> {
         return Ok(());
     }
 
+    if let Command::ReplayBlocks(replay_blocks) = command {
+        sh_println!(
+            "Replaying blocks #{}..=#{}.",
+            replay_blocks.from,
+            replay_blocks.to
+        );
+        let report = node
+            .replay_remote_blocks(
+                L2BlockNumber(replay_blocks.from)..=L2BlockNumber(replay_blocks.to),
+            )
+            .await
+            .map_err(to_domain)?;
+        sh_println!("{}", BlockReplayReportTable(&report));
+        if let Some(path) = &replay_blocks.report {
+            let file = File::create(path)
+                .with_context(|| format!("failed to create report file {}", path.display()))
+                .map_err(to_domain)?;
+            serde_json::to_writer_pretty(file, &report)
+                .context("failed to write replay report")
+                .map_err(to_domain)?;
+        }
+
+        // Same as replaying a transaction, we don't start the server
+        let mismatches = report.mismatch_count();
+        if mismatches > 0 {
+            return Err(to_domain(generic_error!(
+                "Found {mismatches} mismatch(es) between the local and the remote results."
+            )));
+        }
+        sh_println!("Replayed blocks match the remote chain.");
+        return Ok(());
+    }
+
     // TODO: Consider moving to `InMemoryNodeInner::init`
     let rich_addresses = itertools::chain!(
         config
@@ -523,6 +659,9 @@ async fn start_program() -> Result<(), AnvilZksyncError> {
     if !config.no_cors {
         server_builder.enable_cors();
     }
+    if config.call_console_logs {
+        server_builder.enable_call_console_logs();
+    }
     let mut server_handles = Vec::with_capacity(config.host.len());
     for host in &config.host {
         let mut addr = SocketAddr::new(*host, config.port);
@@ -613,6 +752,11 @@ async fn start_program() -> Result<(), AnvilZksyncError> {
         }
     }
 
+    if config.gas_report {
+        let report = node.get_gas_report().await.map_err(to_domain)?;
+        sh_println!("\nGas report:\n{}", GasReportTable(&report));
+    }
+
     Ok(())
 }
 
@@ -1,5 +1,5 @@
 use crate::cli::{Command, ForkUrl};
-use anvil_zksync_config::types::Genesis;
+use anvil_zksync_config::types::{ForkEndpointsFile, Genesis};
 use anvil_zksync_config::TestNodeConfig;
 use anvil_zksync_core::node::fork::ForkDetails;
 use std::fs;
@@ -12,6 +12,13 @@ pub fn parse_genesis_file(path: &str) -> Result<Genesis, String> {
     serde_json::from_str(&file_content).map_err(|err| format!("Failed to parse JSON: {err}"))
 }
 
+/// Parses the fork endpoints file from the given path.
+pub fn parse_fork_endpoints_file(path: &str) -> Result<ForkEndpointsFile, String> {
+    let file_content =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read file: {err}"))?;
+    serde_json::from_str(&file_content).map_err(|err| format!("Failed to parse JSON: {err}"))
+}
+
 /// Updates the configuration from fork details.
 pub async fn update_with_fork_details(config: &mut TestNodeConfig, fd: &ForkDetails) {
     let l1_gas_price = config.l1_gas_price.or(Some(fd.l1_gas_price));
@@ -37,9 +44,15 @@ pub async fn update_with_fork_details(config: &mut TestNodeConfig, fd: &ForkDeta
 pub const TELEMETRY_SENSITIVE_VALUE: &str = "***";
 
 pub fn get_cli_command_telemetry_props(command: Option<Command>) -> Option<TelemetryProps> {
-    let get_sensitive_fork_url = |fork_url| match fork_url {
-        ForkUrl::Custom(_) => Some(TELEMETRY_SENSITIVE_VALUE.to_string()),
-        _ => Some(format!("{:?}", fork_url)),
+    let get_sensitive_fork_url = |fork_urls: Vec<ForkUrl>| {
+        let fork_urls = fork_urls
+            .iter()
+            .map(|fork_url| match fork_url {
+                ForkUrl::Custom(_) | ForkUrl::Named(_) => TELEMETRY_SENSITIVE_VALUE.to_string(),
+                _ => format!("{:?}", fork_url),
+            })
+            .collect::<Vec<_>>();
+        Some(fork_urls.join(","))
     };
 
     let (command_name, command_args) = match command {
@@ -54,6 +67,27 @@ pub fn get_cli_command_telemetry_props(command: Option<Command>) -> Option<Telem
                 .insert_with("fork_transaction_hash", args.fork_transaction_hash, |v| {
                     v.map(|_| TELEMETRY_SENSITIVE_VALUE)
                 })
+                .insert(
+                    "fork_timestamp",
+                    args.fork_timestamp.map(serde_json::Number::from),
+                )
+                .insert(
+                    "fork_l1_batch",
+                    args.fork_l1_batch.map(serde_json::Number::from),
+                )
+                .insert_with("fork_record", args.fork_record, |v| {
+                    v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+                })
+                .insert_with("fork_replay", args.fork_replay, |v| {
+                    v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+                })
+                .insert_with("fork_state", args.fork_state, |v| {
+                    v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+                })
+                .insert_with("fork_follow", args.fork_follow, |v| v.then_some(v))
+                .insert_with("fork_follow_interval", args.fork_follow_interval, |v| {
+                    args.fork_follow.then(|| serde_json::Number::from(v))
+                })
                 .take();
             (Some("fork"), Some(command_args))
         }
@@ -64,6 +98,17 @@ pub fn get_cli_command_telemetry_props(command: Option<Command>) -> Option<Telem
                 .take();
             (Some("replay_tx"), Some(command_args))
         }
+        Some(Command::ReplayBlocks(args)) => {
+            let command_args = TelemetryProps::new()
+                .insert_with("fork_url", args.fork_url, get_sensitive_fork_url)
+                .insert("from", Some(serde_json::Number::from(args.from)))
+                .insert("to", Some(serde_json::Number::from(args.to)))
+                .insert_with("report", args.report, |v| {
+                    v.map(|_| TELEMETRY_SENSITIVE_VALUE)
+                })
+                .take();
+            (Some("replay_blocks"), Some(command_args))
+        }
         None => (None, None),
     };
 
@@ -57,6 +57,16 @@ pub struct TestNodeConfig {
     pub show_vm_details: ShowVMDetails,
     /// Level of detail for gas usage logs
     pub show_gas_details: ShowGasDetails,
+    /// Print gas usage aggregated by contract and function on shutdown
+    pub gas_report: bool,
+    /// Record per-transaction gas profiles, implied by `gas_profile_dir`
+    pub gas_profile: bool,
+    /// Directory to write per-transaction gas profiles to
+    pub gas_profile_dir: Option<PathBuf>,
+    /// Record instruction-level struct logs for every executed transaction
+    pub struct_logs: bool,
+    /// Maximum number of struct logs recorded per executed transaction, `0` means unlimited
+    pub struct_logs_limit: usize,
     /// Numeric verbosity derived from repeated `-v` flags (e.g. -v = 1, -vv = 2, etc.).
     pub verbosity: u8,
     /// Don’t print anything on startup if true
@@ -74,6 +84,11 @@ pub struct TestNodeConfig {
     pub bytecode_compression: bool,
     /// Enables EVM interpreter mode
     pub use_evm_interpreter: bool,
+    /// Predeploy testnet paymasters and a dev ERC20 token at genesis. Requires `use_evm_interpreter`
+    pub testnet_paymaster: bool,
+    /// Install helper contracts (Multicall3, deterministic deployment proxy) at their canonical
+    /// addresses. Requires `use_evm_interpreter`
+    pub predeploys: bool,
     /// Enables BoojumOS mode (experimental)
     pub boojum: BoojumConfig,
     /// Optional chain ID for the node
@@ -96,6 +111,10 @@ pub struct TestNodeConfig {
     pub cache_dir: String,
     /// Cache configuration for the test node
     pub cache_config: CacheConfig,
+    /// Retry, timeout and rate limiting settings for requests to the forked network
+    pub fork_rpc: ForkRpcConfig,
+    /// Named endpoints of forkable networks with their own client settings
+    pub fork_endpoints: ForkEndpointsFile,
     /// Signer accounts that will be initialized with `genesis_balance` in the genesis block.
     pub genesis_accounts: Vec<PrivateKeySigner>,
     /// Native token balance of every genesis account in the genesis block
@@ -110,6 +129,8 @@ pub struct TestNodeConfig {
     pub genesis_timestamp: Option<u64>,
     /// Enable auto impersonation of accounts on startup
     pub enable_auto_impersonate: bool,
+    /// Top up transaction initiators that cannot cover their fees and value at seal time
+    pub auto_fund: bool,
     /// Whether the node operates in offline mode
     pub offline: bool,
     /// The host the server will listen on
@@ -127,6 +148,8 @@ pub struct TestNodeConfig {
     pub allow_origin: String,
     /// Disable CORS if true
     pub no_cors: bool,
+    /// Always return `console.log` output alongside `eth_call` result
+    pub call_console_logs: bool,
     /// How transactions are sorted in the mempool
     pub transaction_order: TransactionOrder,
     /// Path to load/dump the state from
@@ -137,6 +160,8 @@ pub struct TestNodeConfig {
     pub state_interval: Option<u64>,
     /// Preserve historical states
     pub preserve_historical_states: bool,
+    /// Include private keys of accounts managed by the node in state dumps
+    pub dump_signer_accounts: bool,
     /// State to load
     pub load_state: Option<PathBuf>,
     /// L1 configuration, disabled if `None`
@@ -190,6 +215,11 @@ impl Default for TestNodeConfig {
             show_storage_logs: Default::default(),
             show_vm_details: Default::default(),
             show_gas_details: Default::default(),
+            gas_report: false,
+            gas_profile: false,
+            gas_profile_dir: None,
+            struct_logs: false,
+            struct_logs_limit: DEFAULT_STRUCT_LOGS_LIMIT,
             verbosity: 0,
             silent: false,
             system_contracts_options: Default::default(),
@@ -198,6 +228,8 @@ impl Default for TestNodeConfig {
             override_bytecodes_dir: None,
             bytecode_compression: false,
             use_evm_interpreter: false,
+            testnet_paymaster: false,
+            predeploys: false,
             boojum: Default::default(),
             chain_id: None,
 
@@ -215,12 +247,15 @@ impl Default for TestNodeConfig {
             // Cache configuration default
             cache_dir: String::from(DEFAULT_DISK_CACHE_DIR),
             cache_config: Default::default(),
+            fork_rpc: Default::default(),
+            fork_endpoints: Default::default(),
 
             // Account generator
             account_generator: None,
             genesis_accounts: genesis_accounts.clone(),
             signer_accounts: genesis_accounts,
             enable_auto_impersonate: false,
+            auto_fund: false,
             // 100ETH default balance
             genesis_balance: U256::from(100u128 * 10u128.pow(18)),
             genesis_timestamp: Some(NON_FORK_FIRST_BLOCK_TIMESTAMP),
@@ -241,12 +276,14 @@ impl Default for TestNodeConfig {
             // Server configuration
             allow_origin: "*".to_string(),
             no_cors: false,
+            call_console_logs: false,
 
             // state configuration
             state: None,
             dump_state: None,
             state_interval: None,
             preserve_historical_states: false,
+            dump_signer_accounts: false,
             load_state: None,
             l1_config: None,
             auto_execute_l1: false,
@@ -654,6 +691,22 @@ Address: {address}
         self
     }
 
+    /// Enable or disable the testnet paymaster predeploys
+    #[must_use]
+    pub fn with_testnet_paymaster(mut self, enable: bool) -> Self {
+        self.testnet_paymaster = enable;
+        self
+    }
+
+    /// Enable or disable the canonical helper contract predeploys
+    #[must_use]
+    pub fn with_predeploys(mut self, predeploys: Option<bool>) -> Self {
+        if let Some(predeploys) = predeploys {
+            self.predeploys = predeploys;
+        }
+        self
+    }
+
     /// Enable or disable Boojum
     #[must_use]
     pub fn with_boojum(mut self, boojum: BoojumConfig) -> Self {
@@ -761,6 +814,20 @@ Address: {address}
         self
     }
 
+    /// Set the fork RPC client configuration
+    #[must_use]
+    pub fn with_fork_rpc(mut self, fork_rpc: ForkRpcConfig) -> Self {
+        self.fork_rpc = fork_rpc;
+        self
+    }
+
+    /// Set the named fork endpoints
+    #[must_use]
+    pub fn with_fork_endpoints(mut self, fork_endpoints: ForkEndpointsFile) -> Self {
+        self.fork_endpoints = fork_endpoints;
+        self
+    }
+
     /// Get the cache configuration
     pub fn get_cache_config(&self) -> &CacheConfig {
         &self.cache_config
@@ -852,6 +919,48 @@ Address: {address}
         self.show_gas_details
     }
 
+    /// Enable or disable printing gas report on shutdown
+    #[must_use]
+    pub fn with_gas_report(mut self, gas_report: bool) -> Self {
+        self.gas_report = gas_report;
+        self
+    }
+
+    /// Enable or disable recording per-transaction gas profiles
+    #[must_use]
+    pub fn with_gas_profile(mut self, gas_profile: bool) -> Self {
+        self.gas_profile = gas_profile;
+        self
+    }
+
+    /// Set the directory to write per-transaction gas profiles to
+    #[must_use]
+    pub fn with_gas_profile_dir(mut self, gas_profile_dir: Option<PathBuf>) -> Self {
+        self.gas_profile_dir = gas_profile_dir;
+        self
+    }
+
+    /// Returns whether gas profiles are recorded for executed transactions
+    pub fn is_gas_profile_enabled(&self) -> bool {
+        self.gas_profile || self.gas_profile_dir.is_some()
+    }
+
+    /// Enable or disable recording struct logs for executed transactions
+    #[must_use]
+    pub fn with_struct_logs(mut self, struct_logs: bool) -> Self {
+        self.struct_logs = struct_logs;
+        self
+    }
+
+    /// Set the maximum number of struct logs recorded per executed transaction
+    #[must_use]
+    pub fn with_struct_logs_limit(mut self, limit: Option<usize>) -> Self {
+        if let Some(limit) = limit {
+            self.struct_logs_limit = limit;
+        }
+        self
+    }
+
     /// Set the gas limit scale factor
     #[must_use]
     pub fn with_gas_limit_scale(mut self, scale: Option<f32>) -> Self {
@@ -952,6 +1061,13 @@ Address: {address}
         self
     }
 
+    /// Sets whether to enable auto-funding of transaction initiators
+    #[must_use]
+    pub fn with_auto_fund(mut self, auto_fund: bool) -> Self {
+        self.auto_fund = auto_fund;
+        self
+    }
+
     /// Set the offline mode
     #[must_use]
     pub fn with_offline(mut self, offline: Option<bool>) -> Self {
@@ -1025,6 +1141,13 @@ Address: {address}
         self
     }
 
+    /// Enable or disable returning `console.log` output alongside `eth_call` result
+    #[must_use]
+    pub fn with_call_console_logs(mut self, call_console_logs: bool) -> Self {
+        self.call_console_logs = call_console_logs;
+        self
+    }
+
     /// Set the state
     #[must_use]
     pub fn with_state(mut self, state: Option<PathBuf>) -> Self {
@@ -1053,6 +1176,13 @@ Address: {address}
         self
     }
 
+    /// Set whether private keys of managed accounts are included in state dumps
+    #[must_use]
+    pub fn with_dump_signer_accounts(mut self, dump_signer_accounts: bool) -> Self {
+        self.dump_signer_accounts = dump_signer_accounts;
+        self
+    }
+
     /// Set the state to load
     #[must_use]
     pub fn with_load_state(mut self, load_state: Option<PathBuf>) -> Self {
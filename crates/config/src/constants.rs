@@ -1,4 +1,5 @@
 use alloy::primitives::hex;
+use std::time::Duration;
 use zksync_types::H160;
 
 /// Default L1 gas price for transactions
@@ -23,6 +24,14 @@ pub const DEFAULT_LOG_FILE_PATH: &str = "anvil-zksync.log";
 pub const DEFAULT_MNEMONIC: &str = "test test test test test test test test test test test junk";
 /// Timestamp of the first block (if not running in fork mode).
 pub const NON_FORK_FIRST_BLOCK_TIMESTAMP: u64 = 1_000;
+/// Default number of retries for a failed request to the forked network
+pub const DEFAULT_FORK_RETRIES: u32 = 5;
+/// Default delay before the first retry of a failed request to the forked network
+pub const DEFAULT_FORK_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+/// Default timeout for a single request to the forked network
+pub const DEFAULT_FORK_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Default maximum number of struct logs recorded per executed transaction
+pub const DEFAULT_STRUCT_LOGS_LIMIT: usize = 100_000;
 /// Default account balance for the dev accounts
 // TODO: Test-only constant, move to a test utils crate
 pub const DEFAULT_ACCOUNT_BALANCE: u128 = 1_000 * 10u128.pow(18);
@@ -2,4 +2,6 @@ mod config;
 pub mod constants;
 pub mod types;
 
-pub use config::{BaseTokenConfig, ForkPrintInfo, L1Config, TestNodeConfig};
+pub use config::{
+    BaseTokenConfig, ForkPrintInfo, L1Config, TestNodeConfig, DEFAULT_PROTOCOL_VERSION,
+};
@@ -55,21 +55,20 @@ impl AccountGenerator {
     }
 
     pub fn gen(&self) -> Vec<PrivateKeySigner> {
-        let builder = MnemonicBuilder::<English>::default().phrase(self.phrase.as_str());
+        (0..self.amount as u32)
+            .map(|idx| self.gen_at(idx))
+            .collect()
+    }
 
+    /// Derives a single account at the given index of the derivation path.
+    pub fn gen_at(&self, index: u32) -> PrivateKeySigner {
         let derivation_path = self.derivation_path.as_deref().unwrap_or(DERIVATION_PATH);
-
-        (0..self.amount)
-            .map(|idx| {
-                let builder = builder
-                    .clone()
-                    .derivation_path(format!("{derivation_path}{idx}"))
-                    .unwrap();
-                builder
-                    .build()
-                    .unwrap()
-                    .with_chain_id(Some(self.chain_id.into()))
-            })
-            .collect()
+        MnemonicBuilder::<English>::default()
+            .phrase(self.phrase.as_str())
+            .derivation_path(format!("{derivation_path}{index}"))
+            .unwrap()
+            .build()
+            .unwrap()
+            .with_chain_id(Some(self.chain_id.into()))
     }
 }
@@ -0,0 +1,89 @@
+use super::ForkRpcConfig;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::time::Duration;
+use url::Url;
+
+/// Named endpoints of forkable networks, loaded from the JSON file passed with `--fork-endpoints`.
+/// Names can be used with `--fork-url` and take precedence over the builtin networks.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ForkEndpointsFile {
+    /// Endpoints keyed by the name they are referred to with.
+    #[serde(default)]
+    pub networks: BTreeMap<String, ForkNetworkEndpoint>,
+}
+
+impl ForkEndpointsFile {
+    /// Looks up an endpoint by its name.
+    pub fn get(&self, name: &str) -> Option<&ForkNetworkEndpoint> {
+        self.networks.get(name)
+    }
+
+    /// Looks up an endpoint by its URL, so that its settings also apply when the URL is used
+    /// directly (e.g. in `anvil_reset`).
+    pub fn find_by_url(&self, url: &Url) -> Option<&ForkNetworkEndpoint> {
+        self.networks.values().find(|endpoint| &endpoint.url == url)
+    }
+}
+
+/// Single entry of [`ForkEndpointsFile`]. Settings that are not set fall back to the fork client
+/// options passed on the command line.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ForkNetworkEndpoint {
+    /// HTTP(S) URL of the endpoint.
+    pub url: Url,
+    /// Extra headers sent with every request (e.g. API keys).
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Credentials for HTTP basic authentication.
+    pub basic_auth: Option<ForkBasicAuth>,
+    /// Timeout in seconds for a single request.
+    pub timeout: Option<u64>,
+    /// Maximum number of requests in flight at the same time.
+    pub max_concurrent_requests: Option<NonZeroUsize>,
+    /// Maximum number of requests sent per second.
+    pub requests_per_second: Option<NonZeroUsize>,
+}
+
+impl ForkNetworkEndpoint {
+    /// Applies the endpoint's settings on top of `base`.
+    pub fn rpc_config(&self, base: &ForkRpcConfig) -> ForkRpcConfig {
+        let mut rpc = base.clone();
+        rpc.headers.extend(self.headers.clone());
+        if let Some(basic_auth) = &self.basic_auth {
+            rpc.basic_auth = Some(basic_auth.clone());
+        }
+        if let Some(timeout) = self.timeout {
+            rpc.request_timeout = Duration::from_secs(timeout);
+        }
+        if let Some(max_concurrent_requests) = self.max_concurrent_requests {
+            rpc.max_concurrent_requests = Some(max_concurrent_requests);
+        }
+        if let Some(requests_per_second) = self.requests_per_second {
+            rpc.requests_per_second = Some(requests_per_second);
+        }
+        rpc
+    }
+}
+
+/// Credentials for HTTP basic authentication.
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ForkBasicAuth {
+    pub username: String,
+    pub password: String,
+}
+
+// Keeps the password out of logged configuration
+impl fmt::Debug for ForkBasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForkBasicAuth")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
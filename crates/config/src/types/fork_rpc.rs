@@ -0,0 +1,57 @@
+use super::ForkBasicAuth;
+use crate::constants::{
+    DEFAULT_FORK_REQUEST_TIMEOUT, DEFAULT_FORK_RETRIES, DEFAULT_FORK_RETRY_BACKOFF,
+};
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+/// Upper bound for the delay between two attempts of the same fork request.
+const MAX_FORK_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Configuration of the client used to talk to the forked network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForkRpcConfig {
+    /// How many times a failed request is retried before giving up.
+    pub retries: u32,
+    /// Delay before the first retry, doubled after every subsequent attempt.
+    pub retry_backoff: Duration,
+    /// Maximum time a single request is allowed to take.
+    pub request_timeout: Duration,
+    /// Maximum number of requests sent to the forked network per second (unlimited if not set).
+    pub requests_per_second: Option<NonZeroUsize>,
+    /// Maximum number of requests in flight to a single endpoint (unlimited if not set).
+    pub max_concurrent_requests: Option<NonZeroUsize>,
+    /// Extra headers sent with every request.
+    pub headers: BTreeMap<String, String>,
+    /// Credentials for HTTP basic authentication.
+    pub basic_auth: Option<ForkBasicAuth>,
+}
+
+impl Default for ForkRpcConfig {
+    fn default() -> Self {
+        Self {
+            retries: DEFAULT_FORK_RETRIES,
+            retry_backoff: DEFAULT_FORK_RETRY_BACKOFF,
+            request_timeout: DEFAULT_FORK_REQUEST_TIMEOUT,
+            requests_per_second: None,
+            max_concurrent_requests: None,
+            headers: BTreeMap::new(),
+            basic_auth: None,
+        }
+    }
+}
+
+impl ForkRpcConfig {
+    /// Returns how long to wait before retrying a request that failed `attempt` times (starting
+    /// from 1).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.retry_backoff
+            .checked_mul(factor)
+            .unwrap_or(MAX_FORK_RETRY_BACKOFF)
+            .min(MAX_FORK_RETRY_BACKOFF)
+    }
+}
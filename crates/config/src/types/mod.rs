@@ -1,10 +1,14 @@
 mod account_generator;
 mod boojum;
+mod fork_endpoints;
+mod fork_rpc;
 mod genesis;
 
 pub use account_generator::AccountGenerator;
 pub use boojum::BoojumConfig;
 use clap::ValueEnum;
+pub use fork_endpoints::{ForkBasicAuth, ForkEndpointsFile, ForkNetworkEndpoint};
+pub use fork_rpc::ForkRpcConfig;
 pub use genesis::Genesis;
 use serde::Deserialize;
 
@@ -41,10 +41,7 @@ impl Default for ConsoleLogHandler {
 
 impl ConsoleLogHandler {
     pub fn handle_calls_recursive(&self, calls: &Vec<Call>) {
-        let mut messages: Vec<String> = vec![];
-        for call in calls {
-            self.handle_call_recursive(call, &mut messages);
-        }
+        let messages = self.collect_calls_recursive(calls);
 
         if !messages.is_empty() {
             sh_println!("===Logs===");
@@ -53,6 +50,15 @@ impl ConsoleLogHandler {
             sh_println!("{}", message.bold());
         }
     }
+    /// Collects all `console.log` messages emitted by the given calls (and their subcalls) in
+    /// execution order without printing them.
+    pub fn collect_calls_recursive(&self, calls: &[Call]) -> Vec<String> {
+        let mut messages: Vec<String> = vec![];
+        for call in calls {
+            self.handle_call_recursive(call, &mut messages);
+        }
+        messages
+    }
     pub fn handle_call_recursive(&self, current_call: &Call, messages: &mut Vec<String>) {
         if let Some(message) = self.handle_call(current_call) {
             messages.push(message);
@@ -77,12 +83,7 @@ impl ConsoleLogHandler {
                     tokens.map_or("Failed to parse inputs for log.".to_owned(), |tokens| {
                         tokens
                             .iter()
-                            .map(|t| {
-                                PrettyDecodedValue(
-                                    &decode_value(t.clone()).expect("Address decoding error"),
-                                )
-                                .to_string()
-                            })
+                            .map(|t| PrettyDecodedValue(&decode_value(t.clone())).to_string())
                             .collect::<Vec<_>>()
                             .join(" ")
                     })
@@ -509,4 +510,33 @@ mod tests {
     fn test_get_log_functions_compiles() {
         assert_eq!(378, get_log_functions().len());
     }
+
+    #[test]
+    fn test_collect_calls_recursive() {
+        use alloy::dyn_abi::DynSolValue;
+
+        let handler = ConsoleLogHandler::default();
+        let log_string = get_log_functions()
+            .into_iter()
+            .find(|func| func.signature() == "log(string)")
+            .unwrap();
+        let log_call = |message: &str| Call {
+            to: H160::from_str(CONSOLE_ADDRESS).unwrap(),
+            input: log_string
+                .abi_encode_input(&[DynSolValue::String(message.to_owned())])
+                .unwrap(),
+            ..Default::default()
+        };
+        let outer = Call {
+            to: H160::repeat_byte(0x1),
+            calls: vec![log_call("first"), log_call("second")],
+            ..Default::default()
+        };
+
+        let messages = handler.collect_calls_recursive(&[outer, log_call("third")]);
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].contains("first"));
+        assert!(messages[1].contains("second"));
+        assert!(messages[2].contains("third"));
+    }
 }
@@ -1,2 +1,3 @@
+pub mod console_log;
 pub mod ds;
 pub mod hh;
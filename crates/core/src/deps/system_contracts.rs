@@ -29,6 +29,19 @@ pub const TIMESTAMP_ASSERTER_ADDRESS: Address = H160([
     0x00, 0x80, 0x80, 0x12,
 ]);
 
+/// Canonical Multicall3 address on ZKsync chains.
+pub const MULTICALL3_ADDRESS: Address = H160([
+    0xf9, 0xcd, 0xa6, 0x24, 0xfb, 0xc7, 0xe0, 0x59, 0x35, 0x5c, 0xe9, 0x8a, 0x31, 0x69, 0x3d, 0x29,
+    0x9f, 0xac, 0xd9, 0x63,
+]);
+
+/// Address of the deterministic deployment proxy (https://github.com/Arachnid/deterministic-deployment-proxy),
+/// the same as on Ethereum and other EVM chains.
+pub const DETERMINISTIC_DEPLOYMENT_PROXY_ADDRESS: Address = H160([
+    0x4e, 0x59, 0xb4, 0x48, 0x47, 0xb3, 0x79, 0x57, 0x85, 0x88, 0x92, 0x0c, 0xa7, 0x8f, 0xbf, 0x26,
+    0xc0, 0xb4, 0x95, 0x6c,
+]);
+
 static BUILTIN_CONTRACT_ARCHIVES: [(ProtocolVersionId, &[u8]); 3] = [
     (
         ProtocolVersionId::Version26,
@@ -76,31 +89,59 @@ static BUILTIN_CONTRACT_ARTIFACTS: Lazy<HashMap<ProtocolVersionId, HashMap<Strin
         result
     });
 
+/// Extracts bytecode from either a Forge (`bytecode.object`) or a Hardhat (`bytecode`) artifact.
 pub fn bytecode_from_slice(artifact_name: &str, contents: &[u8]) -> Vec<u8> {
+    bytecode_field_from_slice(artifact_name, contents, "bytecode")
+}
+
+/// Same as [`bytecode_from_slice`] but extracts runtime bytecode (`deployedBytecode`) instead.
+pub fn deployed_bytecode_from_slice(artifact_name: &str, contents: &[u8]) -> Vec<u8> {
+    bytecode_field_from_slice(artifact_name, contents, "deployedBytecode")
+}
+
+fn bytecode_field_from_slice(artifact_name: &str, contents: &[u8], field: &str) -> Vec<u8> {
     let artifact: Value = serde_json::from_slice(contents).expect(artifact_name);
-    let bytecode = artifact["bytecode"]
-        .as_object()
-        .unwrap_or_else(|| panic!("Bytecode not found in {:?}", artifact_name))
-        .get("object")
-        .unwrap_or_else(|| panic!("Bytecode object not found in {:?}", artifact_name))
-        .as_str()
-        .unwrap_or_else(|| panic!("Bytecode object is not a string in {:?}", artifact_name));
-
-    hex::decode(bytecode)
+    let bytecode = &artifact[field];
+    let bytecode = match bytecode.as_object() {
+        Some(bytecode) => bytecode
+            .get("object")
+            .unwrap_or_else(|| panic!("Bytecode object not found in {:?}", artifact_name)),
+        None => bytecode,
+    }
+    .as_str()
+    .unwrap_or_else(|| panic!("Bytecode object is not a string in {:?}", artifact_name));
+
+    hex::decode(bytecode.trim_start_matches("0x"))
         .unwrap_or_else(|err| panic!("Can't decode bytecode in {:?}: {}", artifact_name, err))
 }
 
-pub fn load_builtin_contract(protocol_version: ProtocolVersionId, artifact_name: &str) -> Vec<u8> {
+fn load_builtin_artifact(
+    protocol_version: ProtocolVersionId,
+    artifact_name: &str,
+) -> &'static [u8] {
     let artifact_path = format!("{artifact_name}.json");
+    BUILTIN_CONTRACT_ARTIFACTS
+        .get(&protocol_version)
+        .unwrap_or_else(|| panic!("protocol version '{protocol_version}' is not supported"))
+        .get(&artifact_path)
+        .unwrap_or_else(|| panic!("failed to find built-in contract artifact at '{artifact_path}'"))
+}
+
+pub fn load_builtin_contract(protocol_version: ProtocolVersionId, artifact_name: &str) -> Vec<u8> {
     bytecode_from_slice(
         artifact_name,
-        BUILTIN_CONTRACT_ARTIFACTS
-            .get(&protocol_version)
-            .unwrap_or_else(|| panic!("protocol version '{protocol_version}' is not supported"))
-            .get(&artifact_path)
-            .unwrap_or_else(|| {
-                panic!("failed to find built-in contract artifact at '{artifact_path}'")
-            }),
+        load_builtin_artifact(protocol_version, artifact_name),
+    )
+}
+
+/// Same as [`load_builtin_contract`] but returns runtime bytecode of an EVM contract.
+pub fn load_builtin_evm_contract(
+    protocol_version: ProtocolVersionId,
+    artifact_name: &str,
+) -> Vec<u8> {
+    deployed_bytecode_from_slice(
+        artifact_name,
+        load_builtin_artifact(protocol_version, artifact_name),
     )
 }
 
@@ -214,6 +255,30 @@ pub static NON_KERNEL_CONTRACT_LOCATIONS: [(&str, Address, ProtocolVersionId); 8
     ("L2WrappedBaseToken", L2_WRAPPED_BASE_TOKEN_IMPL, V26),
 ];
 
+/// Helper contracts installed by `--predeploys` at their canonical addresses. These are EVM
+/// contracts, hence they require the EVM emulator that was added in v27.
+pub static PREDEPLOYED_CONTRACT_LOCATIONS: [(&str, Address, ProtocolVersionId); 2] = [
+    ("Multicall3", MULTICALL3_ADDRESS, V27),
+    (
+        "DeterministicDeploymentProxy",
+        DETERMINISTIC_DEPLOYMENT_PROXY_ADDRESS,
+        V27,
+    ),
+];
+
+/// Returns the predeployed helper contracts for the given protocol version. Panics if an artifact
+/// is missing from the built-in archive.
+pub fn get_predeployed_contracts(protocol_version: ProtocolVersionId) -> Vec<DeployedContract> {
+    PREDEPLOYED_CONTRACT_LOCATIONS
+        .iter()
+        .filter(|(_, _, min_version)| &protocol_version >= min_version)
+        .map(|(artifact_name, address, _)| DeployedContract {
+            account_id: AccountTreeId::new(*address),
+            bytecode: load_builtin_evm_contract(protocol_version, artifact_name),
+        })
+        .collect()
+}
+
 pub fn get_deployed_contracts(
     options: SystemContractsOptions,
     protocol_version: ProtocolVersionId,
@@ -293,4 +358,16 @@ mod tests {
             count_protocol_contracts(ProtocolVersionId::Version28)
         );
     }
+
+    #[test]
+    fn load_predeployed_contracts() {
+        assert!(get_predeployed_contracts(ProtocolVersionId::Version26).is_empty());
+        for protocol_version in [ProtocolVersionId::Version27, ProtocolVersionId::Version28] {
+            let contracts = get_predeployed_contracts(protocol_version);
+            assert_eq!(contracts.len(), PREDEPLOYED_CONTRACT_LOCATIONS.len());
+            assert!(contracts
+                .iter()
+                .all(|contract| !contract.bytecode.is_empty()));
+        }
+    }
 }
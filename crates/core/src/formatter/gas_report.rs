@@ -0,0 +1,59 @@
+use anvil_zksync_types::api::GasReport;
+use std::fmt;
+
+/// Representation of a single function row of [`GasReport`], prepared for formatting using
+/// `Tabled`.
+#[derive(tabled::Tabled)]
+struct FunctionGasReportRepr {
+    contract: String,
+    function: String,
+    calls: u64,
+    min: u64,
+    avg: u64,
+    median: u64,
+    max: u64,
+    #[tabled(rename = "avg pubdata")]
+    avg_pubdata: u64,
+}
+
+/// Renders [`GasReport`] as a table similar to `forge test --gas-report`.
+pub struct GasReportTable<'a>(pub &'a GasReport);
+
+impl fmt::Display for GasReportTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .0
+            .contracts
+            .iter()
+            .flat_map(|contract| {
+                let contract_name = match &contract.name {
+                    Some(name) => format!("{name} ({:?})", contract.address),
+                    None => format!("{:?}", contract.address),
+                };
+                contract
+                    .functions
+                    .iter()
+                    .map(move |function| FunctionGasReportRepr {
+                        contract: contract_name.clone(),
+                        function: match (&function.signature, &function.selector) {
+                            (Some(signature), _) => signature.clone(),
+                            (None, Some(selector)) => format!("0x{}", hex::encode(&selector.0)),
+                            (None, None) => "fallback".to_string(),
+                        },
+                        calls: function.calls,
+                        min: function.min,
+                        avg: function.avg,
+                        median: function.median,
+                        max: function.max,
+                        avg_pubdata: function.avg_pubdata,
+                    })
+            })
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return write!(f, "No contract calls were recorded.");
+        }
+        let mut table = tabled::Table::new(rows);
+        table.with(tabled::settings::Style::modern());
+        write!(f, "{table}")
+    }
+}
@@ -1,3 +1,4 @@
+use crate::node::ForkFetchStats;
 use crate::{bootloader_debug::BootloaderDebug, utils::to_human_size};
 use anvil_zksync_common::sh_println;
 use colored::Colorize;
@@ -430,6 +431,40 @@ impl Formatter {
             }
         });
     }
+
+    /// Prints statistics on data fetched from the forked network while executing a transaction.
+    pub(crate) fn print_fork_fetch_stats(&mut self, stats: &ForkFetchStats) {
+        self.section("[Fork Data Fetched]", true, |section| {
+            let stats = [
+                ("Prefetch Rounds", stats.prefetch_rounds.to_string()),
+                (
+                    "Prefetched Storage Slots",
+                    stats.prefetched_slots.to_string(),
+                ),
+                (
+                    "Prefetched Bytecodes",
+                    stats.prefetched_bytecodes.to_string(),
+                ),
+                ("On-demand Storage Slots", stats.fetched_slots.to_string()),
+                ("On-demand Bytecodes", stats.fetched_bytecodes.to_string()),
+                ("Merkle Tree Lookups", stats.tree_lookups.to_string()),
+                (
+                    "Initial Writes",
+                    if stats.heuristic_initial_writes {
+                        "Estimated (no Merkle tree)".to_string()
+                    } else {
+                        "Exact".to_string()
+                    },
+                ),
+                ("Time Spent Fetching", format!("{:?}", stats.fetch_time)),
+            ];
+
+            let last = stats.len() - 1;
+            for (i, (key, value)) in stats.iter().enumerate() {
+                section.item(i == last, key, value);
+            }
+        });
+    }
 }
 // Builds the branched prefix for the structured logs.
 fn build_prefix(sibling_stack: &[bool], is_last_sibling: bool) -> String {
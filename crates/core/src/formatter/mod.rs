@@ -3,8 +3,10 @@
 
 pub mod address;
 pub mod errors;
+pub mod gas_report;
 pub mod log;
 pub mod pubdata_bytes;
+pub mod replay_report;
 pub mod transaction;
 pub mod util;
 
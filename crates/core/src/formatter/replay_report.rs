@@ -0,0 +1,60 @@
+use anvil_zksync_types::api::{BlockReplayReport, ReplayMismatchKind};
+use std::fmt;
+
+/// Representation of a single mismatch of [`BlockReplayReport`], prepared for formatting using
+/// `Tabled`.
+#[derive(tabled::Tabled)]
+struct ReplayMismatchRepr {
+    block: u64,
+    kind: String,
+    #[tabled(rename = "tx / slot")]
+    location: String,
+    remote: String,
+    local: String,
+}
+
+/// Renders mismatches found by `replay_blocks` as a table.
+pub struct BlockReplayReportTable<'a>(pub &'a BlockReplayReport);
+
+impl fmt::Display for BlockReplayReportTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .0
+            .blocks
+            .iter()
+            .flat_map(|block| {
+                block
+                    .mismatches
+                    .iter()
+                    .map(move |mismatch| ReplayMismatchRepr {
+                        block: block.remote_block_number.as_u64(),
+                        kind: kind_name(mismatch.kind).to_string(),
+                        location: match (&mismatch.tx_hash, &mismatch.address, &mismatch.key) {
+                            (Some(tx_hash), _, _) => format!("{tx_hash:#x}"),
+                            (None, Some(address), Some(key)) => format!("{address:#x} @ {key:#x}"),
+                            _ => "-".to_string(),
+                        },
+                        remote: mismatch.remote.to_string(),
+                        local: mismatch.local.to_string(),
+                    })
+            })
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return write!(f, "No mismatches were found.");
+        }
+        let mut table = tabled::Table::new(rows);
+        table.with(tabled::settings::Style::modern());
+        write!(f, "{table}")
+    }
+}
+
+fn kind_name(kind: ReplayMismatchKind) -> &'static str {
+    match kind {
+        ReplayMismatchKind::Inclusion => "inclusion",
+        ReplayMismatchKind::Status => "status",
+        ReplayMismatchKind::GasUsed => "gas used",
+        ReplayMismatchKind::Logs => "logs",
+        ReplayMismatchKind::ReturnData => "return data",
+        ReplayMismatchKind::Storage => "storage",
+    }
+}
@@ -57,6 +57,8 @@ pub struct TransactionSummary {
     gas: GasDetails,
     /// Changes in balances.
     balance_diffs: Option<Vec<BalanceDiff>>,
+    /// Amount the initiator was topped up by in auto-fund mode
+    auto_funded: Option<U256>,
 }
 
 impl TransactionSummary {
@@ -94,8 +96,15 @@ impl TransactionSummary {
                 refunded,
             },
             balance_diffs,
+            auto_funded: None,
         }
     }
+
+    /// Records the amount the initiator's balance was topped up by before execution.
+    pub fn with_auto_funded(mut self, auto_funded: Option<U256>) -> Self {
+        self.auto_funded = auto_funded;
+        self
+    }
 }
 
 impl Display for TransactionSummary {
@@ -108,6 +117,7 @@ impl Display for TransactionSummary {
             context: TransactionContext { l2_gas_price },
             gas,
             balance_diffs,
+            auto_funded,
         } = self;
 
         // Calculate gas costs in ETH
@@ -136,6 +146,9 @@ Refunded: {refunded_in_eth}
             paid_in_eth = format_eth(paid),
             refunded_in_eth = format_eth(refunded),
         )?;
+        if let Some(auto_funded) = auto_funded {
+            writeln!(f, "Auto-funded: {}", format_eth(*auto_funded))?;
+        }
 
         if let Some(balance_diffs) = balance_diffs {
             if !balance_diffs.is_empty() {
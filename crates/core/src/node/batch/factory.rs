@@ -4,7 +4,9 @@ use crate::bootloader_debug::{BootloaderDebug, BootloaderDebugTracer};
 use crate::deps::InMemoryStorage;
 use crate::node::boojumos::BoojumOsVM;
 use crate::node::traces::call_error::CallErrorTracer;
+use crate::node::traces::struct_log::{StructLogTracer, StructLogsResult};
 use anvil_zksync_config::types::BoojumConfig;
+use anvil_zksync_types::api::StructLogConfig;
 use anyhow::Context as _;
 use once_cell::sync::OnceCell;
 use std::sync::RwLock;
@@ -94,6 +96,9 @@ pub struct MainBatchExecutorFactory<Tr> {
     divergence_handler: Option<DivergenceHandler>,
     legacy_bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
     boojum: BoojumConfig,
+    /// If set, every executed transaction is traced with [`StructLogTracer`] using the given
+    /// config and its logs are written to the result. Only supported by the legacy VM.
+    struct_logs: Option<(StructLogConfig, StructLogsResult)>,
     _tracer: PhantomData<Tr>,
 }
 
@@ -102,6 +107,7 @@ impl<Tr: BatchTracer> MainBatchExecutorFactory<Tr> {
         enforced_bytecode_compression: bool,
         legacy_bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
         boojum: BoojumConfig,
+        struct_logs: Option<(StructLogConfig, StructLogsResult)>,
     ) -> Self {
         Self {
             enforced_bytecode_compression,
@@ -110,6 +116,7 @@ impl<Tr: BatchTracer> MainBatchExecutorFactory<Tr> {
             divergence_handler: None,
             legacy_bootloader_debug_result,
             boojum,
+            struct_logs,
             _tracer: PhantomData,
         }
     }
@@ -137,6 +144,7 @@ impl<Tr: BatchTracer> MainBatchExecutorFactory<Tr> {
             divergence_handler: self.divergence_handler.clone(),
             commands: commands_receiver,
             legacy_bootloader_debug_result: self.legacy_bootloader_debug_result.clone(),
+            struct_logs: self.struct_logs.clone(),
             _storage: PhantomData,
             _tracer: PhantomData::<Tr>,
         };
@@ -252,6 +260,7 @@ impl<S: ReadStorage, Tr: BatchTracer> BatchVm<S, Tr> {
         tx: Transaction,
         with_compression: bool,
         legacy_bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
+        struct_logs: Option<(StructLogConfig, StructLogsResult)>,
     ) -> BatchTransactionExecutionResult {
         let legacy_tracer_result = Arc::new(OnceCell::default());
         let legacy_error_flags_result = Arc::new(OnceCell::new());
@@ -264,6 +273,10 @@ impl<S: ReadStorage, Tr: BatchTracer> BatchVm<S, Tr> {
             .push(BootloaderDebugTracer::new(legacy_bootloader_debug_result).into_tracer_pointer());
         legacy_tracer
             .push(CallErrorTracer::new(legacy_error_flags_result.clone()).into_tracer_pointer());
+        if let Some((config, struct_logs_result)) = struct_logs {
+            legacy_tracer
+                .push(StructLogTracer::new(config, struct_logs_result).into_tracer_pointer());
+        }
         let mut legacy_tracer = legacy_tracer.into();
         let mut fast_traces = vec![];
 
@@ -337,6 +350,7 @@ struct CommandReceiver<S, Tr> {
     divergence_handler: Option<DivergenceHandler>,
     commands: mpsc::Receiver<Command>,
     legacy_bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
+    struct_logs: Option<(StructLogConfig, StructLogsResult)>,
     _storage: PhantomData<S>,
     _tracer: PhantomData<Tr>,
 }
@@ -494,6 +508,7 @@ impl<S: ReadStorage + 'static, Tr: BatchTracer> CommandReceiver<S, Tr> {
             tx.clone(),
             true,
             self.legacy_bootloader_debug_result.clone(),
+            self.struct_logs.clone(),
         );
         if res.compression_result.is_ok() {
             return Ok(BatchTransactionExecutionResult {
@@ -512,6 +527,7 @@ impl<S: ReadStorage + 'static, Tr: BatchTracer> CommandReceiver<S, Tr> {
             tx.clone(),
             false,
             self.legacy_bootloader_debug_result.clone(),
+            self.struct_logs.clone(),
         );
         res.compression_result
             .context("compression failed when it wasn't applied")?;
@@ -533,6 +549,7 @@ impl<S: ReadStorage + 'static, Tr: BatchTracer> CommandReceiver<S, Tr> {
             tx.clone(),
             true,
             self.legacy_bootloader_debug_result.clone(),
+            self.struct_logs.clone(),
         );
         if res.compression_result.is_ok() {
             Ok(BatchTransactionExecutionResult {
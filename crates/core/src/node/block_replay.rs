@@ -0,0 +1,298 @@
+//! Differential replay of remote blocks: every remote block's transactions are re-executed locally
+//! on top of a fork created right before the first block, and the local results are compared
+//! against what the remote chain reports.
+
+use super::InMemoryNode;
+use anvil_zksync_common::sh_warn;
+use anvil_zksync_types::api::{
+    BlockReplayReport, BlockReplayResult, ReplayMismatch, ReplayMismatchKind,
+};
+use anyhow::Context;
+use futures::{StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+use zksync_types::utils::storage_key_for_eth_balance;
+use zksync_types::{
+    api, u256_to_h256, Address, L1BatchNumber, L2BlockNumber, StorageKey, H256,
+    SYSTEM_CONTEXT_ADDRESS,
+};
+
+/// Maximum number of remote storage reads that are in flight at the same time.
+const MAX_CONCURRENT_STORAGE_READS: usize = 16;
+
+impl InMemoryNode {
+    /// Replays remote blocks from `blocks` one by one, sealing a new local block for each of them.
+    /// The node is expected to be forked at the block preceding the range.
+    pub async fn replay_remote_blocks(
+        &self,
+        blocks: RangeInclusive<L2BlockNumber>,
+    ) -> anyhow::Result<BlockReplayReport> {
+        let mut report = BlockReplayReport {
+            from_block: blocks.start().0.into(),
+            to_block: blocks.end().0.into(),
+            blocks: Vec::new(),
+            skipped_checks: Vec::new(),
+        };
+        for remote_block in blocks.start().0..=blocks.end().0 {
+            let result = self
+                .replay_remote_block(L2BlockNumber(remote_block), &mut report.skipped_checks)
+                .await?;
+            tracing::info!(
+                remote_block,
+                local_block = %result.local_block_number,
+                mismatches = result.mismatches.len(),
+                "replayed remote block"
+            );
+            report.blocks.push(result);
+        }
+        Ok(report)
+    }
+
+    async fn replay_remote_block(
+        &self,
+        remote_block: L2BlockNumber,
+        skipped_checks: &mut Vec<ReplayMismatchKind>,
+    ) -> anyhow::Result<BlockReplayResult> {
+        let block = self
+            .fork
+            .get_block_by_number(api::BlockNumber::Number(remote_block.0.into()))
+            .await?
+            .with_context(|| format!("forked chain does not have block #{remote_block}"))?;
+        let txs = self.fork.get_raw_block_transactions(remote_block).await?;
+        let tx_hashes = txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+        let tx_count = tx_hashes.len();
+
+        // Seal the local block under the same conditions as the remote one
+        if block.timestamp.as_u64() > self.time.current_timestamp() {
+            self.node_handle
+                .enforce_next_timestamp_sync(block.timestamp.as_u64())
+                .await?;
+        }
+        if let Some(base_fee) = block.base_fee_per_gas {
+            self.node_handle
+                .enforce_next_base_fee_per_gas_sync(base_fee)
+                .await?;
+        }
+        let (local_block, halted_tx_hashes) = self.seal_replayed_txs(txs).await?;
+
+        let mut mismatches = Vec::new();
+        for tx_hash in tx_hashes {
+            self.compare_tx(tx_hash, &halted_tx_hashes, skipped_checks, &mut mismatches)
+                .await?;
+        }
+        let local = self
+            .blockchain
+            .get_block_by_number(local_block)
+            .await
+            .context("freshly sealed block could not be found in storage")?;
+        // Fees are paid to different operators
+        let ignored_keys = [block.author, local.author]
+            .iter()
+            .map(storage_key_for_eth_balance)
+            .collect::<HashSet<_>>();
+        if let Some(local_batch) = local.l1_batch_number {
+            mismatches.extend(
+                self.compare_storage(
+                    remote_block,
+                    L1BatchNumber(local_batch.as_u32()),
+                    &ignored_keys,
+                )
+                .await?,
+            );
+        }
+
+        Ok(BlockReplayResult {
+            remote_block_number: remote_block.0.into(),
+            local_block_number: local_block.0.into(),
+            tx_count: (tx_count as u64).into(),
+            mismatches,
+        })
+    }
+
+    async fn compare_tx(
+        &self,
+        tx_hash: H256,
+        halted_tx_hashes: &[H256],
+        skipped_checks: &mut Vec<ReplayMismatchKind>,
+        mismatches: &mut Vec<ReplayMismatch>,
+    ) -> anyhow::Result<()> {
+        let mut push = |kind, remote: serde_json::Value, local: serde_json::Value| {
+            mismatches.push(ReplayMismatch {
+                kind,
+                tx_hash: Some(tx_hash),
+                address: None,
+                key: None,
+                remote,
+                local,
+            })
+        };
+        if halted_tx_hashes.contains(&tx_hash) {
+            push(
+                ReplayMismatchKind::Inclusion,
+                to_json(&true),
+                to_json(&false),
+            );
+            return Ok(());
+        }
+        let remote = self
+            .fork
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .with_context(|| format!("forked chain has no receipt for transaction {tx_hash:?}"))?;
+        let local = self
+            .blockchain
+            .get_tx_receipt(&tx_hash)
+            .await
+            .with_context(|| format!("replayed transaction {tx_hash:?} has no receipt"))?;
+
+        if remote.status != local.status {
+            push(
+                ReplayMismatchKind::Status,
+                to_json(&remote.status),
+                to_json(&local.status),
+            );
+        }
+        if remote.gas_used != local.gas_used {
+            push(
+                ReplayMismatchKind::GasUsed,
+                to_json(&remote.gas_used),
+                to_json(&local.gas_used),
+            );
+        }
+        let (remote_logs, local_logs) = (log_summaries(&remote.logs), log_summaries(&local.logs));
+        if remote_logs != local_logs {
+            push(
+                ReplayMismatchKind::Logs,
+                to_json(&remote_logs),
+                to_json(&local_logs),
+            );
+        }
+
+        if skipped_checks.contains(&ReplayMismatchKind::ReturnData) {
+            return Ok(());
+        }
+        let remote_output = match self.fork.get_transaction_return_data(tx_hash).await {
+            Ok(Some(output)) => output,
+            // Only this transaction could not be traced, others may still be compared
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                // Tracing is not supported by every node, there is no point in asking again
+                sh_warn!("Return data will not be compared: {err:#}");
+                skipped_checks.push(ReplayMismatchKind::ReturnData);
+                return Ok(());
+            }
+        };
+        let local_output = self
+            .blockchain
+            .get_tx_debug_info(&tx_hash, true)
+            .await
+            .map(|call| call.output)
+            .unwrap_or_default();
+        if remote_output != local_output {
+            push(
+                ReplayMismatchKind::ReturnData,
+                to_json(&remote_output),
+                to_json(&local_output),
+            );
+        }
+        Ok(())
+    }
+
+    /// Compares the final value of every slot written by the local batch with the remote value at
+    /// the end of the remote block. Slots that only the remote block wrote are not detected.
+    async fn compare_storage(
+        &self,
+        remote_block: L2BlockNumber,
+        local_batch: L1BatchNumber,
+        ignored_keys: &HashSet<StorageKey>,
+    ) -> anyhow::Result<Vec<ReplayMismatch>> {
+        let state_diffs = self
+            .blockchain
+            .get_batch_state_diffs(local_batch)
+            .await
+            .with_context(|| format!("replayed batch {local_batch} has no state diffs"))?
+            .into_iter()
+            // System context holds block hashes, timestamps etc. that differ by construction
+            .filter(|diff| diff.address != SYSTEM_CONTEXT_ADDRESS)
+            .filter(|diff| {
+                let key = StorageKey::new(diff.address.into(), u256_to_h256(diff.key));
+                !ignored_keys.contains(&key)
+            });
+        let block = Some(api::BlockIdVariant::BlockNumber(api::BlockNumber::Number(
+            remote_block.0.into(),
+        )));
+        let remote_values = futures::stream::iter(state_diffs.map(|diff| async move {
+            let remote = self
+                .fork
+                .get_storage_at(diff.address, diff.key, block)
+                .await?;
+            anyhow::Ok((diff, remote))
+        }))
+        .buffered(MAX_CONCURRENT_STORAGE_READS)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        Ok(remote_values
+            .into_iter()
+            .filter(|(diff, remote)| u256_to_h256(diff.final_value) != *remote)
+            .map(|(diff, remote)| ReplayMismatch {
+                kind: ReplayMismatchKind::Storage,
+                tx_hash: None,
+                address: Some(diff.address),
+                key: Some(u256_to_h256(diff.key)),
+                remote: to_json(&remote),
+                local: to_json(&u256_to_h256(diff.final_value)),
+            })
+            .collect())
+    }
+}
+
+/// Part of a log that is expected to be identical between the remote and the local execution.
+#[derive(Debug, PartialEq, Serialize)]
+struct LogSummary<'a> {
+    address: Address,
+    topics: &'a [H256],
+    data: &'a zksync_types::web3::Bytes,
+}
+
+fn log_summaries(logs: &[api::Log]) -> Vec<LogSummary<'_>> {
+    logs.iter()
+        .map(|log| LogSummary {
+            address: log.address,
+            topics: &log.topics,
+            data: &log.data,
+        })
+        .collect()
+}
+
+fn to_json(value: &impl Serialize) -> serde_json::Value {
+    serde_json::to_value(value).expect("failed to serialize replay result")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_types::U256;
+
+    #[test]
+    fn test_logs_are_compared_by_content() {
+        let log = |address: Address, index: u64| api::Log {
+            address,
+            topics: vec![H256::repeat_byte(0x1)],
+            data: vec![0xde, 0xad].into(),
+            block_hash: Some(H256::from_low_u64_be(index)),
+            log_index: Some(U256::from(index)),
+            ..Default::default()
+        };
+        // Block-specific fields do not matter
+        assert_eq!(
+            log_summaries(&[log(Address::repeat_byte(0xa), 1)]),
+            log_summaries(&[log(Address::repeat_byte(0xa), 2)])
+        );
+        assert_ne!(
+            log_summaries(&[log(Address::repeat_byte(0xa), 1)]),
+            log_summaries(&[log(Address::repeat_byte(0xb), 1)])
+        );
+    }
+}
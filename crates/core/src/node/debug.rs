@@ -1,12 +1,15 @@
+use crate::node::inner::ForkStorage;
+use crate::node::traces::struct_log::{StructLogTracer, StructLogsResult};
 use crate::node::{InMemoryNode, MAX_TX_SIZE};
 use crate::utils::create_debug_output;
+use anvil_zksync_types::api::{StructLogConfig, StructLogTrace, TxStructLogTrace};
 use once_cell::sync::OnceCell;
 use std::sync::Arc;
 use zksync_multivm::interface::storage::StorageView;
-use zksync_multivm::interface::{VmFactory, VmInterface};
+use zksync_multivm::interface::{VmExecutionResultAndLogs, VmFactory, VmInterface};
 use zksync_multivm::tracers::CallTracer;
 use zksync_multivm::vm_latest::constants::ETH_CALL_GAS_LIMIT;
-use zksync_multivm::vm_latest::{HistoryDisabled, ToTracerPointer, Vm};
+use zksync_multivm::vm_latest::{HistoryDisabled, ToTracerPointer, TracerPointer, Vm};
 use zksync_types::l2::L2Tx;
 use zksync_types::transaction_request::CallRequest;
 use zksync_types::{api, PackedEthSignature, Transaction, H256};
@@ -50,6 +53,51 @@ impl InMemoryNode {
         options: Option<api::TracerConfig>,
     ) -> Result<api::CallTracerResult, Web3Error> {
         let only_top = options.is_some_and(|o| o.tracer_config.only_top_call);
+        let call_tracer_result = Arc::new(OnceCell::default());
+        let tracer = CallTracer::new(call_tracer_result.clone()).into_tracer_pointer();
+        let (tx, tx_result) = self.inspect_call(request, block, vec![tracer]).await?;
+        let call_traces = if only_top {
+            vec![]
+        } else {
+            Arc::try_unwrap(call_tracer_result)
+                .unwrap()
+                .take()
+                .unwrap_or_default()
+        };
+
+        let debug = create_debug_output(&tx, &tx_result, call_traces)?;
+
+        Ok(api::CallTracerResult::CallTrace(debug))
+    }
+
+    /// Same as [`Self::trace_call_impl`] but traces the call with the struct-log tracer.
+    pub async fn trace_call_struct_logs_impl(
+        &self,
+        request: CallRequest,
+        block: Option<api::BlockId>,
+        config: StructLogConfig,
+    ) -> Result<StructLogTrace, Web3Error> {
+        let struct_logs_result = StructLogsResult::default();
+        let tracer = StructLogTracer::new(config, struct_logs_result.clone()).into_tracer_pointer();
+        let (tx, tx_result) = self.inspect_call(request, block, vec![tracer]).await?;
+        let debug = create_debug_output(&tx, &tx_result, vec![])?;
+        let struct_logs = std::mem::take(&mut *struct_logs_result.write().unwrap());
+
+        Ok(StructLogTrace {
+            gas: tx_result.statistics.gas_used,
+            failed: tx_result.result.is_failed(),
+            return_value: debug.output,
+            struct_logs,
+        })
+    }
+
+    /// Executes call request on top of the latest block with the provided tracers.
+    async fn inspect_call(
+        &self,
+        request: CallRequest,
+        block: Option<api::BlockId>,
+        tracers: Vec<TracerPointer<StorageView<ForkStorage>, HistoryDisabled>>,
+    ) -> Result<(Transaction, VmExecutionResultAndLogs), Web3Error> {
         let inner = self.inner.read().await;
         let system_contracts = self.system_contracts.contracts_for_l2_call();
         if block.is_some() && !matches!(block, Some(api::BlockId::Number(api::BlockNumber::Latest)))
@@ -73,7 +121,9 @@ impl InMemoryNode {
         // update the enforced_base_fee within l1_batch_env to match the logic in zksync_core
         l1_batch_env.enforced_base_fee = Some(l2_tx.common_data.fee.max_fee_per_gas.as_u64());
         let system_env = inner.create_system_env(system_contracts.clone(), execution_mode);
-        let storage = StorageView::new(inner.read_storage()).to_rc_ptr();
+        // Traces may run concurrently with other executions, keep their fork errors separate
+        let fork_storage = inner.fork_storage.with_error_scope();
+        let storage = StorageView::new(fork_storage.clone()).to_rc_ptr();
         let mut vm: Vm<_, HistoryDisabled> = Vm::new(l1_batch_env, system_env, storage);
 
         // We must inject *some* signature (otherwise bootloader code fails to generate hash).
@@ -90,28 +140,18 @@ impl InMemoryNode {
             l2_tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
         }
 
-        let tx: Transaction = l2_tx.clone().into();
-        vm.push_transaction(tx);
-
-        let call_tracer_result = Arc::new(OnceCell::default());
-        let tracer = CallTracer::new(call_tracer_result.clone()).into_tracer_pointer();
+        let tx: Transaction = l2_tx.into();
+        vm.push_transaction(tx.clone());
 
         let tx_result = vm.inspect(
-            &mut tracer.into(),
+            &mut tracers.into(),
             zksync_multivm::interface::InspectExecutionMode::OneTx,
         );
-        let call_traces = if only_top {
-            vec![]
-        } else {
-            Arc::try_unwrap(call_tracer_result)
-                .unwrap()
-                .take()
-                .unwrap_or_default()
-        };
-
-        let debug = create_debug_output(&l2_tx.into(), &tx_result, call_traces)?;
+        if let Some(error) = fork_storage.take_fork_error() {
+            return Err(Web3Error::InternalError(error.into()));
+        }
 
-        Ok(api::CallTracerResult::CallTrace(debug))
+        Ok((tx, tx_result))
     }
 
     pub async fn trace_transaction_impl(
@@ -126,6 +166,59 @@ impl InMemoryNode {
             .await
             .map(api::CallTracerResult::CallTrace))
     }
+
+    /// Same as [`Self::trace_transaction_impl`] but returns the struct-log trace recorded during
+    /// execution. Struct logs are only recorded if the node was started with `--struct-logs`.
+    pub async fn trace_transaction_struct_logs_impl(
+        &self,
+        tx_hash: H256,
+        config: StructLogConfig,
+    ) -> anyhow::Result<Option<StructLogTrace>> {
+        self.ensure_struct_logs_enabled().await?;
+        Ok(self
+            .blockchain
+            .get_tx_struct_log_trace(&tx_hash, config)
+            .await)
+    }
+
+    /// Same as [`Self::trace_block_impl`] but returns struct-log traces recorded during execution.
+    pub async fn trace_block_struct_logs_impl(
+        &self,
+        block_id: api::BlockId,
+        config: StructLogConfig,
+    ) -> anyhow::Result<Vec<TxStructLogTrace>> {
+        self.ensure_struct_logs_enabled().await?;
+        let tx_hashes = self
+            .blockchain
+            .get_block_tx_hashes_by_id(block_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Block (id={block_id}) not found"))?;
+
+        let mut traces = Vec::with_capacity(tx_hashes.len());
+        for tx_hash in tx_hashes {
+            let result = self
+                .blockchain
+                .get_tx_struct_log_trace(&tx_hash, config)
+                .await
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unexpectedly transaction (hash={tx_hash}) belongs to a block but could not be found"
+                    )
+                })?;
+            traces.push(TxStructLogTrace { tx_hash, result });
+        }
+
+        Ok(traces)
+    }
+
+    async fn ensure_struct_logs_enabled(&self) -> anyhow::Result<()> {
+        if !self.inner.read().await.config.struct_logs {
+            anyhow::bail!(
+                "struct logs are not recorded for executed transactions, restart the node with `--struct-logs`"
+            );
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -133,6 +226,7 @@ mod tests {
     use alloy::dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt};
     use alloy::json_abi::{Function, Param, StateMutability};
     use alloy::primitives::{Address as AlloyAddress, U256 as AlloyU256};
+    use anvil_zksync_common::cache::CacheConfig;
     use anvil_zksync_config::constants::DEFAULT_ACCOUNT_BALANCE;
     use zksync_types::{
         transaction_request::CallRequestBuilder, utils::deployed_address_create, Address,
@@ -263,6 +357,92 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_trace_call_struct_logs() {
+        let node = InMemoryNode::test(None);
+
+        let (primary_deployed_address, _) = deploy_test_contracts(&node).await;
+
+        let func = Function {
+            name: "calculate".to_string(),
+            inputs: vec![Param {
+                name: "value".to_string(),
+                ty: "uint256".to_string(),
+                components: vec![],
+                internal_type: None,
+            }],
+            outputs: vec![Param {
+                name: "".to_string(),
+                ty: "uint256".to_string(),
+                components: vec![],
+                internal_type: None,
+            }],
+            state_mutability: StateMutability::NonPayable,
+        };
+        let calldata = func
+            .abi_encode_input(&[DynSolValue::Uint(AlloyU256::from(42), 256)])
+            .expect("failed to encode function input");
+        let request = CallRequestBuilder::default()
+            .to(Some(primary_deployed_address))
+            .data(calldata.into())
+            .gas(80_000_000.into())
+            .build();
+
+        let trace = node
+            .trace_call_struct_logs_impl(request, None, StructLogConfig::default())
+            .await
+            .expect("trace call");
+
+        assert!(!trace.failed);
+        let output = func
+            .abi_decode_output(trace.return_value.0.as_slice(), true)
+            .expect("failed to decode output");
+        assert_eq!(output[0], DynSolValue::Uint(AlloyU256::from(84), 256));
+
+        assert!(!trace.struct_logs.is_empty());
+        assert_eq!(trace.struct_logs[0].depth, 1);
+        // primary contract calls into secondary
+        assert!(trace.struct_logs.iter().any(|log| log.depth > 1));
+        assert!(trace
+            .struct_logs
+            .iter()
+            .all(|log| log.stack.is_some() && log.memory.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_trace_transaction_struct_logs_disabled() {
+        let node = InMemoryNode::test(None);
+        let err = node
+            .trace_transaction_struct_logs_impl(H256::repeat_byte(0x1), StructLogConfig::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("--struct-logs"));
+    }
+
+    #[tokio::test]
+    async fn test_trace_block_struct_logs_limit() {
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig {
+                cache_config: CacheConfig::None,
+                struct_logs: true,
+                struct_logs_limit: 10,
+                ..Default::default()
+            },
+        );
+        deploy_test_contracts(&node).await;
+
+        let traces = node
+            .trace_block_struct_logs_impl(
+                api::BlockId::Number(api::BlockNumber::Latest),
+                StructLogConfig::default(),
+            )
+            .await
+            .expect("trace block");
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].result.struct_logs.len(), 10);
+    }
+
     #[tokio::test]
     async fn test_trace_only_top() {
         let node = InMemoryNode::test(None);
@@ -358,15 +538,13 @@ mod tests {
                 .insert_tx_result(
                     H256::repeat_byte(0x1),
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: api::TransactionReceipt {
                             logs: vec![LogBuilder::new()
                                 .set_address(H160::repeat_byte(0xa1))
                                 .build()],
                             ..Default::default()
                         },
-                        debug: testing::default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
@@ -389,15 +567,13 @@ mod tests {
             .insert_tx_result(
                 H256::repeat_byte(0x1),
                 TransactionResult {
-                    info: testing::default_tx_execution_info(),
-                    new_bytecodes: vec![],
                     receipt: api::TransactionReceipt {
                         logs: vec![LogBuilder::new()
                             .set_address(H160::repeat_byte(0xa1))
                             .build()],
                         ..Default::default()
                     },
-                    debug: testing::default_tx_debug_info(),
+                    ..testing::default_tx_result()
                 },
             )
             .await;
@@ -459,10 +635,8 @@ mod tests {
                 .insert_tx_result(
                     tx_hash,
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: api::TransactionReceipt::default(),
-                        debug: testing::default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
@@ -493,10 +667,8 @@ mod tests {
                 .insert_tx_result(
                     tx_hash,
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: api::TransactionReceipt::default(),
-                        debug: testing::default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
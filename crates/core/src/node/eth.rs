@@ -1,6 +1,7 @@
 use crate::formatter::errors::view::ExecutionErrorReport;
 use crate::node::error::{ToHaltError, ToRevertReason};
 use anvil_zksync_common::{sh_err, sh_println, sh_warn};
+use anvil_zksync_types::api::{SimulatePayload, SimulatedBlock};
 use anyhow::Context as _;
 use std::collections::HashSet;
 use zksync_error::anvil_zksync::node::AnvilNodeResult;
@@ -14,8 +15,8 @@ use zksync_types::{
     api::{Block, BlockIdVariant, BlockNumber, TransactionVariant},
     get_code_key, get_is_account_key,
     l2::L2Tx,
-    transaction_request::TransactionRequest,
-    PackedEthSignature, MAX_L1_TRANSACTION_GAS_LIMIT,
+    transaction_request::{CallRequest, Eip712Meta, TransactionRequest},
+    PackedEthSignature, EIP_712_TX_TYPE, MAX_L1_TRANSACTION_GAS_LIMIT,
 };
 use zksync_types::{h256_to_u256, Transaction};
 use zksync_types::{
@@ -29,7 +30,7 @@ use zksync_web3_decl::{
 
 use crate::{
     filters::{FilterType, LogFilter},
-    node::{InMemoryNode, MAX_TX_SIZE, PROTOCOL_VERSION},
+    node::{inner::simulate_blocks, InMemoryNode, MAX_TX_SIZE, PROTOCOL_VERSION},
     utils::TransparentError,
 };
 
@@ -68,7 +69,7 @@ impl InMemoryNode {
             .await
             .context("Invalid data due to invalid name")?;
 
-        match call_result {
+        match call_result.result {
             ExecutionResult::Success { output } => Ok(output.into()),
             ExecutionResult::Revert { output } => {
                 let message = output.to_user_friendly_string();
@@ -129,9 +130,23 @@ impl InMemoryNode {
         &self,
         mut tx: zksync_types::transaction_request::CallRequest,
     ) -> Result<H256, Web3Error> {
-        let (chain_id, l2_gas_price) = {
+        // Transactions from accounts managed by the node are signed for real
+        if let Some(from) = tx.from {
+            if !self.impersonation.is_impersonating(&from)
+                && self.inner.read().await.is_signer_account(&from)
+            {
+                let tx_bytes = self.sign_transaction_impl(tx).await?;
+                return self.send_raw_transaction_impl(tx_bytes).await;
+            }
+        }
+
+        let (chain_id, l2_gas_price, gas_per_pubdata) = {
             let reader = self.inner.read().await;
-            (self.chain_id().await, reader.fee_input_provider.gas_price())
+            (
+                self.chain_id().await,
+                reader.fee_input_provider.gas_price(),
+                reader.fee_input_provider.gas_per_pubdata(),
+            )
         };
 
         // Users might expect a "sensible default"
@@ -139,8 +154,12 @@ impl InMemoryNode {
             tx.gas = Some(U256::from(MAX_L1_TRANSACTION_GAS_LIMIT));
         }
 
-        // EIP-1559 gas fields should be processed separately
-        if tx.gas_price.is_some() {
+        // EIP-712 and EIP-1559 gas fields should be processed separately
+        let is_eip712 =
+            tx.eip712_meta.is_some() || tx.transaction_type == Some(EIP_712_TX_TYPE.into());
+        if is_eip712 {
+            fill_eip712_defaults(&mut tx, l2_gas_price, gas_per_pubdata);
+        } else if tx.gas_price.is_some() {
             if tx.max_fee_per_gas.is_some() || tx.max_priority_fee_per_gas.is_some() {
                 let err = "Transaction contains unsupported fields: max_fee_per_gas or max_priority_fee_per_gas";
                 sh_err!("{err}");
@@ -193,6 +212,47 @@ impl InMemoryNode {
         self.pool.add_tx(l2_tx.into());
         Ok(hash)
     }
+
+    pub async fn simulate_v1_impl(
+        &self,
+        payload: SimulatePayload,
+        block: Option<BlockIdVariant>,
+    ) -> anyhow::Result<Vec<SimulatedBlock>> {
+        // TODO: Support simulating on top of historical state
+        if !matches!(
+            block,
+            None | Some(BlockIdVariant::BlockNumber(
+                BlockNumber::Latest | BlockNumber::Pending
+            ))
+        ) {
+            anyhow::bail!("eth_simulateV1 only supports simulating on top of the latest block");
+        }
+        let inner = self.inner.read().await;
+        simulate_blocks(&inner, &self.system_contracts, payload).await
+    }
+}
+
+/// Populates fields of a ZKsync EIP-712 (type `0x71`) transaction request that were not provided by
+/// the user with the current fee model values.
+pub(super) fn fill_eip712_defaults(tx: &mut CallRequest, l2_gas_price: u64, gas_per_pubdata: u64) {
+    tx.transaction_type = Some(EIP_712_TX_TYPE.into());
+    // EIP-712 transactions carry their fee cap in `gas_price`
+    let max_fee_per_gas = tx
+        .max_fee_per_gas
+        .or(tx.gas_price)
+        .unwrap_or(U256::from(l2_gas_price));
+    tx.max_fee_per_gas = Some(max_fee_per_gas);
+    tx.gas_price = Some(max_fee_per_gas);
+    tx.max_priority_fee_per_gas = Some(tx.max_priority_fee_per_gas.unwrap_or(U256::zero()));
+    let eip712_meta = tx.eip712_meta.get_or_insert_with(|| Eip712Meta {
+        gas_per_pubdata: U256::zero(),
+        factory_deps: vec![],
+        custom_signature: None,
+        paymaster_params: None,
+    });
+    if eip712_meta.gas_per_pubdata.is_zero() {
+        eip712_meta.gas_per_pubdata = U256::from(gas_per_pubdata);
+    }
 }
 
 impl InMemoryNode {
@@ -504,15 +564,25 @@ impl InMemoryNode {
         SyncState::NotSyncing
     }
 
+    /// Returns accounts managed by the node in a stable order: signer accounts in the order they
+    /// were added followed by the remaining rich accounts sorted by address.
     pub async fn accounts_impl(&self) -> anyhow::Result<Vec<H160>> {
-        Ok(self
-            .inner
-            .read()
-            .await
+        let inner = self.inner.read().await;
+        let mut accounts = inner
+            .config
+            .signer_accounts
+            .iter()
+            .map(|signer| H160::from_slice(signer.address().as_ref()))
+            .collect::<Vec<_>>();
+        let mut rich_accounts = inner
             .rich_accounts
-            .clone()
-            .into_iter()
-            .collect())
+            .iter()
+            .filter(|address| !accounts.contains(address))
+            .copied()
+            .collect::<Vec<_>>();
+        rich_accounts.sort_unstable();
+        accounts.extend(rich_accounts);
+        Ok(accounts)
     }
 
     pub async fn fee_history_impl(
@@ -568,19 +638,23 @@ mod tests {
     use crate::node::TransactionResult;
     use crate::{
         node::InMemoryNode,
-        testing::{
-            self, default_tx_debug_info, ForkBlockConfig, LogBuilder, MockServer,
-            TransactionResponseBuilder,
-        },
+        testing::{self, ForkBlockConfig, LogBuilder, MockServer, TransactionResponseBuilder},
     };
     use anvil_zksync_config::constants::{
         DEFAULT_ACCOUNT_BALANCE, DEFAULT_L2_GAS_PRICE, NON_FORK_FIRST_BLOCK_TIMESTAMP,
     };
+    use anvil_zksync_config::types::ForkRpcConfig;
+    use anvil_zksync_types::api::{SimulateBlock, SimulateBlockOverrides};
+    use httptest::matchers::any;
+    use httptest::responders::status_code;
+    use httptest::Expectation;
     use maplit::hashmap;
+    use std::time::Duration;
     use url::Url;
     use zksync_multivm::utils::get_max_batch_gas_limit;
     use zksync_types::block::L2BlockHasher;
     use zksync_types::l2::TransactionType;
+    use zksync_types::transaction_request::CallRequestBuilder;
     use zksync_types::vm::VmVersion;
     use zksync_types::{
         api,
@@ -1708,15 +1782,13 @@ mod tests {
                 .insert_tx_result(
                     H256::repeat_byte(0x1),
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: TransactionReceipt {
                             logs: vec![LogBuilder::new()
                                 .set_address(H160::repeat_byte(0xa1))
                                 .build()],
                             ..Default::default()
                         },
-                        debug: default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
@@ -1724,8 +1796,6 @@ mod tests {
                 .insert_tx_result(
                     H256::repeat_byte(0x2),
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: TransactionReceipt {
                             logs: vec![
                                 LogBuilder::new()
@@ -1737,7 +1807,7 @@ mod tests {
                             ],
                             ..Default::default()
                         },
-                        debug: default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
@@ -1772,15 +1842,13 @@ mod tests {
                 .insert_tx_result(
                     H256::repeat_byte(0x1),
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: TransactionReceipt {
                             logs: vec![LogBuilder::new()
                                 .set_address(H160::repeat_byte(0xa1))
                                 .build()],
                             ..Default::default()
                         },
-                        debug: default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
@@ -1803,15 +1871,13 @@ mod tests {
                 .insert_tx_result(
                     H256::repeat_byte(0x1),
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: TransactionReceipt {
                             logs: vec![LogBuilder::new()
                                 .set_address(H160::repeat_byte(0xa1))
                                 .build()],
                             ..Default::default()
                         },
-                        debug: testing::default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
@@ -1819,8 +1885,6 @@ mod tests {
                 .insert_tx_result(
                     H256::repeat_byte(0x2),
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: TransactionReceipt {
                             logs: vec![
                                 LogBuilder::new()
@@ -1832,7 +1896,7 @@ mod tests {
                             ],
                             ..Default::default()
                         },
-                        debug: testing::default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
@@ -2075,4 +2139,170 @@ mod tests {
 
         assert_eq!(expected_version, actual_version);
     }
+
+    #[tokio::test]
+    async fn test_simulate_v1_fills_gaps_and_keeps_state_intact() {
+        let node = InMemoryNode::test(None);
+        let from = H160::repeat_byte(0x1);
+        let to = H160::repeat_byte(0x2);
+        node.set_rich_account(from, U256::from(DEFAULT_ACCOUNT_BALANCE))
+            .await;
+        let current_block = node.blockchain.current_block_number().await;
+
+        let transfer = CallRequestBuilder::default()
+            .from(from)
+            .to(Some(to))
+            .value(U256::from(100))
+            .build();
+        let payload = SimulatePayload {
+            block_state_calls: vec![
+                SimulateBlock {
+                    block_overrides: None,
+                    state_overrides: None,
+                    calls: vec![transfer.clone()],
+                },
+                SimulateBlock {
+                    block_overrides: Some(SimulateBlockOverrides {
+                        number: Some(U64::from(current_block.0 + 3)),
+                        time: None,
+                        base_fee_per_gas: None,
+                    }),
+                    state_overrides: None,
+                    calls: vec![transfer],
+                },
+            ],
+            trace_transfers: false,
+            validation: false,
+            return_full_transactions: true,
+        };
+        let blocks = node
+            .simulate_v1_impl(payload, None)
+            .await
+            .expect("failed simulating blocks");
+
+        assert_eq!(blocks.len(), 3);
+        for (idx, block) in blocks.iter().enumerate() {
+            assert_eq!(
+                block.inner.number,
+                U64::from(current_block.0 + 1 + idx as u32)
+            );
+        }
+        assert_eq!(blocks[1].inner.parent_hash, blocks[0].inner.hash);
+        assert_eq!(blocks[2].inner.parent_hash, blocks[1].inner.hash);
+        assert!(blocks[1].calls.is_empty());
+        assert!(blocks[1].inner.transactions.is_empty());
+        for block in [&blocks[0], &blocks[2]] {
+            assert_eq!(block.calls.len(), 1);
+            assert_eq!(block.calls[0].status, U64::one());
+            assert!(block.calls[0].error.is_none());
+            assert!(matches!(
+                block.inner.transactions[0],
+                TransactionVariant::Full(_)
+            ));
+        }
+
+        // Nothing is persisted
+        assert_eq!(node.blockchain.current_block_number().await, current_block);
+        assert_eq!(node.get_balance_impl(to, None).await.unwrap(), U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_simulate_v1_rejects_validation() {
+        let node = InMemoryNode::test(None);
+        let payload = SimulatePayload {
+            block_state_calls: vec![],
+            trace_transfers: false,
+            validation: true,
+            return_full_transactions: false,
+        };
+
+        assert!(node.simulate_v1_impl(payload, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_honours_eip712_fields() {
+        let node = InMemoryNode::test(None);
+        let from = H160::repeat_byte(0x1);
+        node.impersonate_account(from).unwrap();
+        let paymaster_params = zksync_types::transaction_request::PaymasterParams {
+            paymaster: H160::repeat_byte(0x2),
+            paymaster_input: vec![0x8c, 0x5a, 0x34, 0x45],
+        };
+
+        let hash = node
+            .send_transaction_impl(CallRequest {
+                from: Some(from),
+                to: Some(H160::repeat_byte(0x3)),
+                eip712_meta: Some(Eip712Meta {
+                    gas_per_pubdata: U256::from(1234),
+                    factory_deps: vec![],
+                    custom_signature: None,
+                    paymaster_params: Some(paymaster_params.clone()),
+                }),
+                ..Default::default()
+            })
+            .await
+            .expect("failed to send transaction");
+
+        let tx = node
+            .pool
+            .drop_transaction(hash)
+            .expect("no pending transaction");
+        let zksync_types::ExecuteTransactionCommon::L2(common_data) = tx.common_data else {
+            panic!("unexpected transaction type");
+        };
+        assert_eq!(
+            common_data.transaction_type,
+            TransactionType::EIP712Transaction
+        );
+        assert_eq!(common_data.fee.gas_per_pubdata_limit, U256::from(1234));
+        assert_eq!(common_data.paymaster_params, paymaster_params);
+        assert_eq!(
+            common_data.fee.max_fee_per_gas,
+            U256::from(node.inner.read().await.fee_input_provider.gas_price())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas_fails_if_fork_is_unavailable() {
+        let mock_server = MockServer::run_with_config(ForkBlockConfig {
+            number: 10,
+            transaction_count: 0,
+            hash: H256::repeat_byte(0xab),
+        });
+        let node = InMemoryNode::test(Some(
+            ForkClient::at_block_number(
+                ForkConfig::unknown(mock_server.url()).with_rpc(ForkRpcConfig {
+                    retries: 1,
+                    retry_backoff: Duration::from_millis(1),
+                    ..Default::default()
+                }),
+                None,
+            )
+            .await
+            .unwrap(),
+        ));
+        // Every request made after the fork has been initialized fails
+        mock_server.inner.expect(
+            Expectation::matching(any())
+                .times(1..)
+                .respond_with(status_code(503)),
+        );
+
+        let result = node
+            .estimate_gas_impl(
+                CallRequest {
+                    from: Some(H160::repeat_byte(0x1)),
+                    to: Some(H160::repeat_byte(0x2)),
+                    value: Some(U256::from(1)),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await;
+        assert!(
+            result.is_err(),
+            "gas must not be estimated against a failing fork: {result:?}"
+        );
+    }
 }
@@ -146,6 +146,14 @@ impl TestNodeFeeInputProvider {
         base_fee
     }
 
+    pub fn gas_per_pubdata(&self) -> u64 {
+        let (_, gas_per_pubdata) = derive_base_fee_and_gas_per_pubdata(
+            self.get_batch_fee_input_scaled(),
+            VmVersion::latest(),
+        );
+        gas_per_pubdata
+    }
+
     pub fn fair_pubdata_price(&self) -> u64 {
         self.get_batch_fee_input_scaled().fair_pubdata_price()
     }
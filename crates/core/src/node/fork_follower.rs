@@ -0,0 +1,306 @@
+use super::InMemoryNode;
+use anvil_zksync_common::sh_warn;
+use anvil_zksync_types::api::{ForkDivergence, ForkFollowStatus};
+use anyhow::Context;
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::MissedTickBehavior;
+use zksync_types::{api, L2BlockNumber, H256, U64};
+
+/// Maximum number of divergences kept around for `anvil_getForkFollowStatus`.
+const MAX_DIVERGENCES: usize = 1000;
+
+/// Shortest polling interval, `tokio::time::interval` panics on a zero period.
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Keeps pulling new blocks from the forked chain and re-executes their transactions on top of the
+/// local state (interleaved with local transactions). Transactions whose outcome changes due to
+/// local modifications are reported as divergences.
+pub struct ForkFollower {
+    node: InMemoryNode,
+    /// How often the remote chain is polled for new blocks.
+    interval: Duration,
+}
+
+impl ForkFollower {
+    pub fn new(node: InMemoryNode, interval: Duration) -> Self {
+        Self {
+            node,
+            interval: interval.max(MIN_INTERVAL),
+        }
+    }
+
+    pub async fn run(self) -> anyhow::Result<()> {
+        let state = self.node.fork_follower.clone();
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if state.is_paused() {
+                tracing::debug!("fork following is paused, waiting to be resumed");
+                state.resumed.notified().await;
+                continue;
+            }
+            // Remote chain being temporarily unreachable should not bring the node down
+            if let Err(err) = self.follow().await {
+                sh_warn!("Failed to follow the forked chain: {err:#}");
+            }
+        }
+    }
+
+    /// Applies all remote blocks produced since the last poll.
+    async fn follow(&self) -> anyhow::Result<()> {
+        let state = &self.node.fork_follower;
+        let Some(details) = self.node.fork.details() else {
+            // Fork has been removed (e.g. by `anvil_reset`), nothing to follow
+            return Ok(());
+        };
+        let next_block = state.next_remote_block(details.block_hash, details.block_number);
+        let head = self
+            .node
+            .fork
+            .get_block_by_number(api::BlockNumber::Latest)
+            .await?
+            .context("forked chain did not return its latest block")?
+            .number
+            .as_u32();
+        for remote_block in next_block.0..=head {
+            if state.is_paused() {
+                break;
+            }
+            self.apply_remote_block(L2BlockNumber(remote_block)).await?;
+        }
+        Ok(())
+    }
+
+    /// Seals transactions of `remote_block` locally and reports the ones whose outcome differs from
+    /// the remote one.
+    async fn apply_remote_block(&self, remote_block: L2BlockNumber) -> anyhow::Result<()> {
+        let state = &self.node.fork_follower;
+        let txs = self
+            .node
+            .fork
+            .get_raw_block_transactions(remote_block)
+            .await?;
+        if txs.is_empty() {
+            tracing::debug!(%remote_block, "skipping empty remote block");
+            state.set_remote_block(remote_block);
+            return Ok(());
+        }
+        let tx_hashes = txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+        let (local_block, halted_tx_hashes) = self.node.seal_replayed_txs(txs).await?;
+        // The block is applied at this point, it must not be replayed again on the next poll
+        state.set_remote_block(remote_block);
+        tracing::info!(
+            %remote_block,
+            %local_block,
+            txs = tx_hashes.len(),
+            "applied remote block"
+        );
+
+        for tx_hash in tx_hashes {
+            // Divergences are informational, failing to check one should not stop following
+            if let Err(err) = self
+                .check_divergence(tx_hash, remote_block, local_block, &halted_tx_hashes)
+                .await
+            {
+                sh_warn!("Failed to check remote transaction {tx_hash:#x} for divergence: {err:#}");
+            }
+        }
+        Ok(())
+    }
+
+    async fn check_divergence(
+        &self,
+        tx_hash: H256,
+        remote_block: L2BlockNumber,
+        local_block: L2BlockNumber,
+        halted_tx_hashes: &[H256],
+    ) -> anyhow::Result<()> {
+        let Some(remote_details) = self.node.fork.get_transaction_details(tx_hash).await? else {
+            tracing::warn!(
+                %remote_block,
+                "outcome of remote transaction {tx_hash:#x} is unknown, skipping divergence check"
+            );
+            return Ok(());
+        };
+        let remote_success = remote_details.status != api::TransactionStatus::Failed;
+        let local_success = if halted_tx_hashes.contains(&tx_hash) {
+            None
+        } else {
+            self.node
+                .blockchain
+                .get_tx_receipt(&tx_hash)
+                .await
+                .map(|receipt| receipt.status == U64::one())
+        };
+        if local_success == Some(remote_success) {
+            return Ok(());
+        }
+        sh_warn!(
+            "Remote transaction {tx_hash:#x} from block #{remote_block} diverged locally: {} remotely, {} locally",
+            outcome(Some(remote_success)),
+            outcome(local_success)
+        );
+        self.node.fork_follower.push_divergence(ForkDivergence {
+            tx_hash,
+            remote_block_number: remote_block.0.into(),
+            local_block_number: local_block.0.into(),
+            remote_success,
+            local_success,
+        });
+        Ok(())
+    }
+}
+
+fn outcome(success: Option<bool>) -> &'static str {
+    match success {
+        Some(true) => "succeeded",
+        Some(false) => "reverted",
+        None => "halted",
+    }
+}
+
+/// Externally controllable state of [`ForkFollower`]. Disabled unless the node was started in
+/// fork-follow mode.
+#[derive(Clone, Debug, Default)]
+pub struct ForkFollowerState {
+    inner: Arc<RwLock<ForkFollowerInner>>,
+    /// Used to wake the follower up once it is resumed
+    resumed: Arc<Notify>,
+}
+
+#[derive(Debug, Default)]
+struct ForkFollowerInner {
+    enabled: bool,
+    paused: bool,
+    /// Hash of the fork block following started from, used to detect the fork being reset.
+    fork_block_hash: H256,
+    /// Last remote block that has been applied locally.
+    remote_block: L2BlockNumber,
+    divergences: VecDeque<ForkDivergence>,
+}
+
+impl ForkFollowerState {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(ForkFollowerInner {
+                enabled,
+                ..Default::default()
+            })),
+            resumed: Arc::new(Notify::new()),
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<ForkFollowerInner> {
+        self.inner.read().expect("ForkFollower lock is poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<ForkFollowerInner> {
+        self.inner.write().expect("ForkFollower lock is poisoned")
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.read().enabled
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.read().paused
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.write().paused = paused;
+        if !paused {
+            self.resumed.notify_one();
+        }
+    }
+
+    pub fn status(&self) -> ForkFollowStatus {
+        let inner = self.read();
+        ForkFollowStatus {
+            paused: inner.paused,
+            remote_block_number: inner.remote_block.0.into(),
+            divergences: inner.divergences.iter().cloned().collect(),
+        }
+    }
+
+    /// Returns the next remote block to apply. Starts over from the fork block if the fork has
+    /// changed since the last call.
+    fn next_remote_block(&self, fork_block_hash: H256, fork_block: L2BlockNumber) -> L2BlockNumber {
+        let mut inner = self.write();
+        if inner.fork_block_hash != fork_block_hash {
+            inner.fork_block_hash = fork_block_hash;
+            inner.remote_block = fork_block;
+            inner.divergences.clear();
+        }
+        inner.remote_block + 1
+    }
+
+    fn set_remote_block(&self, remote_block: L2BlockNumber) {
+        self.write().remote_block = remote_block;
+    }
+
+    fn push_divergence(&self, divergence: ForkDivergence) {
+        let mut inner = self.write();
+        if inner.divergences.len() == MAX_DIVERGENCES {
+            inner.divergences.pop_front();
+        }
+        inner.divergences.push_back(divergence);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn divergence(tx_hash: H256) -> ForkDivergence {
+        ForkDivergence {
+            tx_hash,
+            remote_block_number: 11.into(),
+            local_block_number: 12.into(),
+            remote_success: true,
+            local_success: Some(false),
+        }
+    }
+
+    #[test]
+    fn test_state_starts_over_when_fork_changes() {
+        let state = ForkFollowerState::new(true);
+        let fork_hash = H256::repeat_byte(0x1);
+        assert_eq!(
+            state.next_remote_block(fork_hash, L2BlockNumber(10)),
+            L2BlockNumber(11)
+        );
+        state.set_remote_block(L2BlockNumber(15));
+        state.push_divergence(divergence(H256::repeat_byte(0xa)));
+        // Same fork, following continues where it left off
+        assert_eq!(
+            state.next_remote_block(fork_hash, L2BlockNumber(10)),
+            L2BlockNumber(16)
+        );
+        assert_eq!(state.status().divergences.len(), 1);
+
+        // Fork was reset, following starts over from the new fork block
+        assert_eq!(
+            state.next_remote_block(H256::repeat_byte(0x2), L2BlockNumber(100)),
+            L2BlockNumber(101)
+        );
+        let status = state.status();
+        assert_eq!(status.remote_block_number, 100.into());
+        assert!(status.divergences.is_empty());
+    }
+
+    #[test]
+    fn test_divergences_are_capped() {
+        let state = ForkFollowerState::new(true);
+        for i in 0..=MAX_DIVERGENCES {
+            state.push_divergence(divergence(H256::from_low_u64_be(i as u64)));
+        }
+        let divergences = state.status().divergences;
+        assert_eq!(divergences.len(), MAX_DIVERGENCES);
+        // Oldest divergence was dropped
+        assert_eq!(divergences[0].tx_hash, H256::from_low_u64_be(1));
+    }
+}
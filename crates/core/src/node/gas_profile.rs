@@ -0,0 +1,308 @@
+//! Per-transaction gas profiles in folded-stack format (as consumed by `flamegraph.pl`, `inferno`
+//! or speedscope) along with a minimal built-in SVG flamegraph renderer.
+
+use crate::bootloader_debug::BootloaderDebug;
+use anvil_zksync_types::traces::{CallTraceArena, CallTraceNode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use zksync_multivm::interface::Call;
+use zksync_types::H256;
+
+/// Synthetic frame for gas spent on publishing pubdata.
+const PUBDATA_FRAME: &str = "[pubdata]";
+/// Synthetic frame for intrinsic (bootloader) overhead.
+const INTRINSIC_FRAME: &str = "[intrinsic]";
+/// Synthetic frame for gas spent on publishing/preparing factory dependencies.
+const BYTECODE_PREPARATION_FRAME: &str = "[bytecode preparation]";
+
+const SVG_WIDTH: f64 = 1200.0;
+const SVG_FRAME_HEIGHT: f64 = 16.0;
+const SVG_PADDING_TOP: f64 = 32.0;
+const SVG_FONT_WIDTH: f64 = 7.0;
+
+/// Gas profile of a single transaction represented as folded stacks: each entry is a `;`-separated
+/// call stack and the amount of gas spent exclusively in its innermost frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoldedStacks(pub Vec<(String, u64)>);
+
+impl FoldedStacks {
+    /// Builds the profile out of a decoded call trace arena. Gas spent on pubdata and, if
+    /// available, the bootloader overhead are reported as separate top-level frames.
+    pub fn from_arena(
+        arena: &CallTraceArena,
+        bootloader_debug: Option<&BootloaderDebug>,
+        spent_on_pubdata: u64,
+    ) -> Self {
+        let mut stacks = Vec::new();
+        if let Some(root) = arena.nodes().first() {
+            for child in &root.children {
+                collect_stacks(arena, *child, &mut Vec::new(), &mut stacks);
+            }
+        }
+        if let Some(debug) = bootloader_debug {
+            let total_gas_limit = debug
+                .total_gas_limit_from_user
+                .saturating_sub(debug.reserved_gas);
+            let intrinsic = total_gas_limit.saturating_sub(debug.gas_limit_after_intrinsic);
+            stacks.push((INTRINSIC_FRAME.to_string(), intrinsic.low_u64()));
+            stacks.push((
+                BYTECODE_PREPARATION_FRAME.to_string(),
+                debug.gas_spent_on_bytecode_preparation.low_u64(),
+            ));
+        }
+        stacks.push((PUBDATA_FRAME.to_string(), spent_on_pubdata));
+        stacks.retain(|(_, gas)| *gas > 0);
+        Self(stacks)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Renders the profile in the folded-stack text format (one `stack weight` pair per line).
+    pub fn to_folded(&self) -> String {
+        let mut out = String::new();
+        for (stack, gas) in &self.0 {
+            let _ = writeln!(out, "{stack} {gas}");
+        }
+        out
+    }
+
+    /// Writes the profile to `<dir>/<tx_hash>.folded` and `<dir>/<tx_hash>.svg`, creating `dir` if
+    /// it does not exist yet.
+    pub fn write_to_dir(&self, dir: &Path, tx_hash: H256) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let name = format!("{tx_hash:?}");
+        std::fs::write(dir.join(format!("{name}.folded")), self.to_folded())?;
+        std::fs::write(dir.join(format!("{name}.svg")), self.to_svg(&name))?;
+        Ok(())
+    }
+
+    /// Renders the profile as a self-contained SVG flamegraph.
+    pub fn to_svg(&self, title: &str) -> String {
+        let mut root = FlameNode::default();
+        for (stack, gas) in &self.0 {
+            root.insert(stack.split(';'), *gas);
+        }
+        let total = root.total.max(1) as f64;
+        let depth = root.depth();
+        let height = SVG_PADDING_TOP + (depth as f64 + 1.0) * SVG_FRAME_HEIGHT;
+
+        let mut svg = String::new();
+        let _ = write!(
+            svg,
+            r#"<?xml version="1.0" standalone="no"?>
+<svg version="1.1" width="{SVG_WIDTH}" height="{height}" viewBox="0 0 {SVG_WIDTH} {height}" xmlns="http://www.w3.org/2000/svg">
+<style>text {{ font-family: monospace; font-size: 12px; }} rect {{ stroke: white; stroke-width: 0.5; }}</style>
+<rect x="0" y="0" width="100%" height="100%" fill="#f8f8f8"/>
+<text x="{x}" y="20" text-anchor="middle">{title}</text>
+"#,
+            x = SVG_WIDTH / 2.0,
+            title = escape_xml(title),
+        );
+        let y = height - SVG_FRAME_HEIGHT;
+        write_frame(&mut svg, "all", &root, 0.0, y, total);
+        write_children(&mut svg, &root, 0.0, y - SVG_FRAME_HEIGHT, total);
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Ergs spent within a call frame. `gas_used` is measured from the caller's side and also covers
+/// the cost of making the call (e.g. decommitting the callee), so it is capped by the ergs that
+/// were passed to the frame; the remainder stays with the caller.
+fn frame_ergs(call: &Call) -> u64 {
+    call.gas_used.min(call.gas)
+}
+
+fn collect_stacks(
+    arena: &CallTraceArena,
+    idx: usize,
+    path: &mut Vec<String>,
+    stacks: &mut Vec<(String, u64)>,
+) {
+    let node = &arena.nodes()[idx];
+    path.push(frame_label(node));
+    let children_gas: u64 = node
+        .children
+        .iter()
+        .map(|child| frame_ergs(&arena.nodes()[*child].trace.call))
+        .sum();
+    let self_gas = frame_ergs(&node.trace.call).saturating_sub(children_gas);
+    stacks.push((path.join(";"), self_gas));
+    for child in &node.children {
+        collect_stacks(arena, *child, path, stacks);
+    }
+    path.pop();
+}
+
+/// Labels frame as `Contract::function` using decoded data where possible and falling back to
+/// raw address and selector otherwise.
+fn frame_label(node: &CallTraceNode) -> String {
+    let trace = &node.trace;
+    let contract = trace
+        .decoded
+        .label
+        .clone()
+        .unwrap_or_else(|| format!("{:?}", trace.address));
+    let function = match &trace.decoded.call_data {
+        Some(call_data) => call_data
+            .signature
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        None if trace.call.input.len() >= 4 => {
+            format!("0x{}", hex::encode(&trace.call.input[..4]))
+        }
+        None => "fallback".to_string(),
+    };
+    // `;` is used as a stack separator
+    format!("{contract}::{function}").replace(';', "_")
+}
+
+#[derive(Default)]
+struct FlameNode {
+    total: u64,
+    children: BTreeMap<String, FlameNode>,
+}
+
+impl FlameNode {
+    fn insert<'a>(&mut self, mut frames: impl Iterator<Item = &'a str>, gas: u64) {
+        self.total += gas;
+        if let Some(frame) = frames.next() {
+            self.children
+                .entry(frame.to_string())
+                .or_default()
+                .insert(frames, gas);
+        }
+    }
+
+    fn depth(&self) -> usize {
+        self.children
+            .values()
+            .map(|child| child.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+fn write_children(svg: &mut String, node: &FlameNode, mut x: f64, y: f64, total: f64) {
+    for (name, child) in &node.children {
+        write_frame(svg, name, child, x, y, total);
+        write_children(svg, child, x, y - SVG_FRAME_HEIGHT, total);
+        x += child.total as f64 / total * SVG_WIDTH;
+    }
+}
+
+fn write_frame(svg: &mut String, name: &str, node: &FlameNode, x: f64, y: f64, total: f64) {
+    let width = node.total as f64 / total * SVG_WIDTH;
+    if width < 0.1 {
+        return;
+    }
+    let percentage = node.total as f64 / total * 100.0;
+    let max_chars = (width / SVG_FONT_WIDTH) as usize;
+    let text = if name.chars().count() <= max_chars {
+        name.to_string()
+    } else if max_chars > 2 {
+        format!("{}..", name.chars().take(max_chars - 2).collect::<String>())
+    } else {
+        String::new()
+    };
+    let _ = writeln!(
+        svg,
+        r#"<g><title>{title} ({gas} gas, {percentage:.2}%)</title><rect x="{x:.2}" y="{y:.2}" width="{width:.2}" height="{h}" fill="{color}"/><text x="{tx:.2}" y="{ty:.2}">{text}</text></g>"#,
+        title = escape_xml(name),
+        gas = node.total,
+        h = SVG_FRAME_HEIGHT - 1.0,
+        color = frame_color(name),
+        tx = x + 3.0,
+        ty = y + SVG_FRAME_HEIGHT - 4.0,
+        text = escape_xml(&text),
+    );
+}
+
+/// Picks a stable "warm" color for the frame based on its name.
+fn frame_color(name: &str) -> String {
+    let hash = name
+        .bytes()
+        .fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
+    let r = 205 + (hash % 50);
+    let g = 80 + ((hash >> 8) % 140);
+    let b = (hash >> 16) % 60;
+    format!("rgb({r},{g},{b})")
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anvil_zksync_types::traces::{
+        CallTrace, DecodedCallData, DecodedCallTrace, ExecutionResult,
+    };
+    use zksync_types::Address;
+
+    fn node(label: &str, signature: &str, gas: u64, gas_used: u64) -> CallTraceNode {
+        CallTraceNode {
+            parent: None,
+            children: vec![],
+            idx: 0,
+            trace: CallTrace {
+                success: true,
+                caller: Address::zero(),
+                address: Address::repeat_byte(0x1),
+                execution_result: ExecutionResult::Success { output: vec![] },
+                decoded: DecodedCallTrace {
+                    label: Some(label.to_string()),
+                    call_data: Some(DecodedCallData {
+                        signature: signature.to_string(),
+                        args: vec![],
+                    }),
+                    ..Default::default()
+                },
+                call: Call {
+                    gas,
+                    gas_used,
+                    ..Default::default()
+                },
+            },
+            logs: vec![],
+            l2_l1_logs: vec![],
+            ordering: vec![],
+        }
+    }
+
+    #[test]
+    fn test_folded_stacks_from_arena() {
+        let mut arena = CallTraceArena::default();
+        let outer = arena.add_node(Some(0), node("Router", "swap(uint256)", 1000, 1100));
+        // Call overhead beyond the ergs passed to the callee is attributed to the caller
+        arena.add_node(
+            Some(outer),
+            node("Token", "transfer(address,uint256)", 300, 350),
+        );
+        arena.add_node(Some(outer), node("Token", "balanceOf(address)", 250, 200));
+
+        let profile = FoldedStacks::from_arena(&arena, None, 50);
+        assert_eq!(
+            profile.to_folded(),
+            "Router::swap 500\n\
+             Router::swap;Token::transfer 300\n\
+             Router::swap;Token::balanceOf 200\n\
+             [pubdata] 50\n"
+        );
+
+        let svg = profile.to_svg("0xabc");
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains("Token::transfer (300 gas"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
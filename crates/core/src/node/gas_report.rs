@@ -0,0 +1,240 @@
+use anvil_zksync_common::address_map;
+use anvil_zksync_console::console_log::CONSOLE_ADDRESS;
+use anvil_zksync_types::api::{ContractGasReport, FunctionGasReport, GasReport};
+use anvil_zksync_types::traces::CallTraceArena;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use zksync_types::web3::Bytes;
+use zksync_types::Address;
+
+/// Aggregates gas usage per (contract, function selector) across all executed transactions.
+///
+/// Clones always share the same underlying data and recording through one affects all other
+/// instances.
+#[derive(Clone, Debug, Default)]
+pub struct GasReporter {
+    state: Arc<RwLock<GasReporterState>>,
+}
+
+#[derive(Debug, Default)]
+struct GasReporterState {
+    contracts: BTreeMap<Address, ContractGasSamples>,
+}
+
+#[derive(Debug, Default)]
+struct ContractGasSamples {
+    name: Option<String>,
+    functions: BTreeMap<Option<[u8; 4]>, FunctionGasSamples>,
+}
+
+#[derive(Debug, Default)]
+struct FunctionGasSamples {
+    signature: Option<String>,
+    gas: Vec<u64>,
+    pubdata: Vec<u64>,
+}
+
+impl GasReporter {
+    /// Records all user contract calls from a decoded call trace arena of a single transaction.
+    ///
+    /// Calls made to system contracts, precompiles, `console.log` and the transaction's initiator
+    /// are ignored. Gas spent on pubdata is attributed to the first remaining call (i.e. the
+    /// transaction's entry call).
+    pub fn record(&self, arena: &CallTraceArena, initiator: Address, spent_on_pubdata: u64) {
+        let console_address = Address::from_str(CONSOLE_ADDRESS).unwrap();
+        let mut state = self.state.write().expect("GasReporter lock is poisoned");
+        let mut entry_call_seen = false;
+        for node in arena.nodes().iter().skip(1) {
+            let trace = &node.trace;
+            if trace.address.is_zero()
+                || trace.address == initiator
+                || trace.address == console_address
+                || address_map::is_system(&trace.address)
+                || address_map::is_precompile(&trace.address)
+            {
+                continue;
+            }
+            let pubdata = if entry_call_seen {
+                0
+            } else {
+                entry_call_seen = true;
+                spent_on_pubdata
+            };
+
+            let selector = trace
+                .call
+                .input
+                .get(..4)
+                .map(|s| <[u8; 4]>::try_from(s).unwrap());
+            let contract = state.contracts.entry(trace.address).or_default();
+            if contract.name.is_none() {
+                contract.name = trace.decoded.label.clone();
+            }
+            let function = contract.functions.entry(selector).or_default();
+            if function.signature.is_none() {
+                function.signature = trace
+                    .decoded
+                    .call_data
+                    .as_ref()
+                    .map(|call_data| call_data.signature.clone());
+            }
+            function.gas.push(trace.call.gas_used);
+            function.pubdata.push(pubdata);
+        }
+    }
+
+    /// Builds a report out of all recorded samples.
+    pub fn report(&self) -> GasReport {
+        let state = self.state.read().expect("GasReporter lock is poisoned");
+        let contracts = state
+            .contracts
+            .iter()
+            .map(|(address, contract)| ContractGasReport {
+                address: *address,
+                name: contract.name.clone(),
+                functions: contract
+                    .functions
+                    .iter()
+                    .map(|(selector, function)| {
+                        let mut gas = function.gas.clone();
+                        gas.sort_unstable();
+                        FunctionGasReport {
+                            selector: selector.map(|s| Bytes(s.to_vec())),
+                            signature: function.signature.clone(),
+                            calls: gas.len() as u64,
+                            min: gas.first().copied().unwrap_or_default(),
+                            avg: average(&gas),
+                            median: median(&gas),
+                            max: gas.last().copied().unwrap_or_default(),
+                            avg_pubdata: average(&function.pubdata),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        GasReport { contracts }
+    }
+
+    /// Removes all recorded samples.
+    pub fn clear(&self) {
+        self.state
+            .write()
+            .expect("GasReporter lock is poisoned")
+            .contracts
+            .clear();
+    }
+}
+
+fn average(samples: &[u64]) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+    (samples.iter().map(|v| *v as u128).sum::<u128>() / samples.len() as u128) as u64
+}
+
+/// Expects `sorted` to be sorted in ascending order.
+fn median(sorted: &[u64]) -> u64 {
+    let len = sorted.len();
+    match len {
+        0 => 0,
+        _ if len % 2 == 1 => sorted[len / 2],
+        _ => ((sorted[len / 2 - 1] as u128 + sorted[len / 2] as u128) / 2) as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anvil_zksync_types::traces::{CallTrace, CallTraceNode, DecodedCallData, ExecutionResult};
+    use zksync_multivm::interface::Call;
+
+    fn node(address: Address, input: Vec<u8>, gas_used: u64, signature: &str) -> CallTraceNode {
+        CallTraceNode {
+            parent: None,
+            children: vec![],
+            idx: 0,
+            trace: CallTrace {
+                success: true,
+                caller: Address::zero(),
+                address,
+                execution_result: ExecutionResult::Success { output: vec![] },
+                decoded: anvil_zksync_types::traces::DecodedCallTrace {
+                    label: Some("Counter".to_string()),
+                    call_data: Some(DecodedCallData {
+                        signature: signature.to_string(),
+                        args: vec![],
+                    }),
+                    ..Default::default()
+                },
+                call: Call {
+                    to: address,
+                    input,
+                    gas_used,
+                    ..Default::default()
+                },
+            },
+            logs: vec![],
+            l2_l1_logs: vec![],
+            ordering: vec![],
+        }
+    }
+
+    #[test]
+    fn test_report_aggregates_per_function() {
+        let reporter = GasReporter::default();
+        let initiator = Address::repeat_byte(0xaa);
+        let counter = Address::repeat_byte(0x11);
+
+        for (gas_used, pubdata) in [(100, 10), (300, 20), (200, 30)] {
+            let mut arena = CallTraceArena::default();
+            arena.add_node(
+                Some(0),
+                node(initiator, vec![0xe2, 0xf3, 0x18, 0xe3], 1_000, ""),
+            );
+            arena.add_node(
+                Some(1),
+                node(
+                    counter,
+                    vec![0xd0, 0x9d, 0xe0, 0x8a],
+                    gas_used,
+                    "increment()",
+                ),
+            );
+            arena.add_node(Some(2), node(counter, vec![], 7, "fallback()"));
+            reporter.record(&arena, initiator, pubdata);
+        }
+
+        let report = reporter.report();
+        assert_eq!(report.contracts.len(), 1);
+        let contract = &report.contracts[0];
+        assert_eq!(contract.address, counter);
+        assert_eq!(contract.name.as_deref(), Some("Counter"));
+        assert_eq!(contract.functions.len(), 2);
+
+        let fallback = &contract.functions[0];
+        assert_eq!(fallback.selector, None);
+        assert_eq!(fallback.calls, 3);
+        assert_eq!(fallback.avg_pubdata, 0);
+
+        let increment = &contract.functions[1];
+        assert_eq!(increment.signature.as_deref(), Some("increment()"));
+        assert_eq!(increment.calls, 3);
+        assert_eq!(increment.min, 100);
+        assert_eq!(increment.avg, 200);
+        assert_eq!(increment.median, 200);
+        assert_eq!(increment.max, 300);
+        assert_eq!(increment.avg_pubdata, 20);
+
+        reporter.clear();
+        assert!(reporter.report().contracts.is_empty());
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), 0);
+        assert_eq!(median(&[5]), 5);
+        assert_eq!(median(&[1, 3]), 2);
+        assert_eq!(median(&[1, 2, 10]), 2);
+    }
+}
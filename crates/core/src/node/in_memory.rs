@@ -6,6 +6,8 @@ use crate::delegate_vm;
 use crate::deps::InMemoryStorage;
 use crate::filters::EthFilters;
 use crate::node::fee_model::TestNodeFeeInputProvider;
+use crate::node::fork_follower::ForkFollowerState;
+use crate::node::gas_profile::FoldedStacks;
 use crate::node::impersonate::{ImpersonationManager, ImpersonationState};
 use crate::node::inner::blockchain::ReadBlockchain;
 use crate::node::inner::storage::ReadStorageDyn;
@@ -13,6 +15,7 @@ use crate::node::inner::time::ReadTime;
 use crate::node::sealer::BlockSealerState;
 use crate::node::state::VersionedState;
 use crate::node::state_override::apply_state_override;
+use crate::node::trace::flatten_call_trace;
 use crate::node::traces::call_error::CallErrorTracer;
 use crate::node::traces::decoder::CallTraceDecoderBuilder;
 use crate::node::{BlockSealer, BlockSealerMode, NodeExecutor, TxBatch, TxPool};
@@ -24,12 +27,17 @@ use anvil_zksync_common::shell::get_shell;
 use anvil_zksync_config::constants::{NON_FORK_FIRST_BLOCK_TIMESTAMP, TEST_NODE_NETWORK_ID};
 use anvil_zksync_config::types::Genesis;
 use anvil_zksync_config::TestNodeConfig;
+use anvil_zksync_console::console_log::ConsoleLogHandler;
 use anvil_zksync_traces::{
     build_call_trace_arena, decode_trace_arena, filter_call_trace_arena,
     identifier::SignaturesIdentifier, render_trace_arena_inner,
 };
 use anvil_zksync_types::{
-    traces::CallTraceArena, LogLevel, ShowGasDetails, ShowStorageLogs, ShowVMDetails,
+    api::{
+        LocalizedTransactionTrace, StructLog, StructLogConfig, StructLogTrace, TransactionTrace,
+    },
+    traces::CallTraceArena,
+    LogLevel, ShowGasDetails, ShowStorageLogs, ShowVMDetails,
 };
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
@@ -37,6 +45,7 @@ use flate2::Compression;
 use indexmap::IndexMap;
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::io::{Read, Write};
 use std::sync::Arc;
@@ -51,7 +60,8 @@ use zksync_multivm::interface::storage::{
 };
 use zksync_multivm::interface::VmFactory;
 use zksync_multivm::interface::{
-    ExecutionResult, InspectExecutionMode, L1BatchEnv, L2BlockEnv, TxExecutionMode, VmInterface,
+    InspectExecutionMode, L1BatchEnv, L2BlockEnv, TxExecutionMode, VmExecutionResultAndLogs,
+    VmInterface,
 };
 use zksync_multivm::tracers::CallTracer;
 use zksync_multivm::utils::{get_batch_base_fee, get_max_batch_gas_limit};
@@ -87,6 +97,13 @@ pub const MAX_PREVIOUS_STATES: u16 = 128;
 /// The zks protocol version.
 pub const PROTOCOL_VERSION: &str = "zks/1";
 
+tokio::task_local! {
+    /// `console.log` messages emitted by the `eth_call`s executed within the current scope. Only
+    /// collected when the caller explicitly scoped it (e.g. API server for requests that asked for
+    /// console logs to be returned).
+    pub static CALL_CONSOLE_LOGS: RefCell<Vec<String>>;
+}
+
 pub fn compute_hash<'a>(
     protocol_version: ProtocolVersionId,
     number: L2BlockNumber,
@@ -240,6 +257,24 @@ pub struct TransactionResult {
     pub new_bytecodes: Vec<(H256, Vec<u8>)>,
     pub receipt: TransactionReceipt,
     pub debug: DebugCall,
+    /// Messages emitted by the transaction through `console.log` calls, in execution order.
+    #[serde(default)]
+    pub console_logs: Vec<String>,
+    /// Gas profile of the transaction's call tree (see [`FoldedStacks`]). Only recorded when
+    /// gas profiling is enabled (`--gas-profile` or `--gas-profile-dir`).
+    #[serde(default)]
+    pub gas_profile: Option<FoldedStacks>,
+    /// Instruction-level trace of the transaction, only recorded when struct logs are enabled.
+    #[serde(default)]
+    pub struct_logs: Vec<StructLog>,
+    /// Amount the initiator's balance was topped up by before execution, only set in auto-fund
+    /// mode (see `TestNodeConfig::auto_fund`).
+    #[serde(default)]
+    pub auto_funded: Option<U256>,
+    /// Parity-style traces of the transaction's call tree. Unlike `debug`, they keep the kind of
+    /// every call (e.g. `delegatecall`). Empty for results loaded from older states.
+    #[serde(default)]
+    pub flat_traces: Vec<TransactionTrace>,
 }
 
 impl TransactionResult {
@@ -256,6 +291,51 @@ impl TransactionResult {
             ..self.debug.clone()
         }
     }
+
+    /// Returns the transaction's call tree flattened into Parity-style traces.
+    pub fn parity_traces(&self) -> Vec<LocalizedTransactionTrace> {
+        let traces = if self.flat_traces.is_empty() {
+            flatten_call_trace(&self.debug, None)
+        } else {
+            self.flat_traces.clone()
+        };
+        traces
+            .into_iter()
+            .map(|trace| LocalizedTransactionTrace {
+                trace,
+                block_hash: self.receipt.block_hash,
+                block_number: self.receipt.block_number.as_u64(),
+                transaction_hash: self.receipt.transaction_hash,
+                transaction_position: self.receipt.transaction_index.as_u64(),
+            })
+            .collect()
+    }
+
+    /// Returns the struct-log trace for the transaction with stack and storage stripped according
+    /// to `config`. Memory is never recorded for executed transactions.
+    pub fn struct_log_trace(&self, config: StructLogConfig) -> StructLogTrace {
+        let limit = if config.limit == 0 {
+            usize::MAX
+        } else {
+            config.limit
+        };
+        let struct_logs = self
+            .struct_logs
+            .iter()
+            .take(limit)
+            .map(|log| StructLog {
+                stack: log.stack.clone().filter(|_| !config.disable_stack),
+                storage: log.storage.clone().filter(|_| !config.disable_storage),
+                ..log.clone()
+            })
+            .collect();
+        StructLogTrace {
+            gas: self.receipt.gas_used.unwrap_or_default().as_u64(),
+            failed: self.receipt.status.is_zero(),
+            return_value: self.debug.output.clone(),
+            struct_logs,
+        }
+    }
 }
 
 /// Creates a restorable snapshot for the [InMemoryNodeInner]. The snapshot contains all the necessary
@@ -299,6 +379,7 @@ pub struct InMemoryNode {
     pub(crate) observability: Option<Observability>,
     pub(crate) pool: TxPool,
     pub(crate) sealer_state: BlockSealerState,
+    pub(crate) fork_follower: ForkFollowerState,
     pub(crate) system_contracts: SystemContracts,
     pub(crate) storage_key_layout: StorageKeyLayout,
 }
@@ -316,6 +397,7 @@ impl InMemoryNode {
         impersonation: ImpersonationManager,
         pool: TxPool,
         sealer_state: BlockSealerState,
+        fork_follower: ForkFollowerState,
         system_contracts: SystemContracts,
         storage_key_layout: StorageKeyLayout,
     ) -> Self {
@@ -331,6 +413,7 @@ impl InMemoryNode {
             observability,
             pool,
             sealer_state,
+            fork_follower,
             system_contracts,
             storage_key_layout,
         }
@@ -339,15 +422,29 @@ impl InMemoryNode {
     /// Replays transactions consequently in a new block. All transactions are expected to be
     /// executable and will become a part of the resulting block.
     pub async fn replay_txs(&self, txs: Vec<Transaction>) -> AnvilNodeResult<()> {
+        let (_, halted_tx_hashes) = self.seal_replayed_txs(txs).await?;
+        // If some transactions were not executed (i.e. were halted), replay has diverged from the
+        // original chain.
+        if !halted_tx_hashes.is_empty() {
+            return Err(generic_error!(
+                "Failed to replay transactions: {halted_tx_hashes:?}. Please report this."
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Replays transactions consequently in a new block. Returns the number of the new block
+    /// along with hashes of transactions that did not make it into the block (i.e. were halted).
+    pub(crate) async fn seal_replayed_txs(
+        &self,
+        txs: Vec<Transaction>,
+    ) -> AnvilNodeResult<(L2BlockNumber, Vec<H256>)> {
+        let expected_tx_hashes = txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
         let tx_batch = TxBatch {
             impersonating: false,
             txs,
         };
-        let expected_tx_hashes = tx_batch
-            .txs
-            .iter()
-            .map(|tx| tx.hash())
-            .collect::<HashSet<_>>();
         let block_number = self.node_handle.seal_block_sync(tx_batch).await?;
         // Fetch the block that was just sealed
         let block = self
@@ -366,19 +463,11 @@ impl InMemoryNode {
             })
             .collect::<HashSet<_>>();
 
-        // Calculate the difference between expected transaction hash set and the actual one.
-        // If the difference is not empty it means some transactions were not executed (i.e.
-        // were halted).
-        let diff_tx_hashes = expected_tx_hashes
-            .difference(&actual_tx_hashes)
-            .collect::<Vec<_>>();
-        if !diff_tx_hashes.is_empty() {
-            return Err(generic_error!(
-                "Failed to replay transactions: {diff_tx_hashes:?}. Please report this."
-            ));
-        }
-
-        Ok(())
+        let halted_tx_hashes = expected_tx_hashes
+            .into_iter()
+            .filter(|tx_hash| !actual_tx_hashes.contains(tx_hash))
+            .collect();
+        Ok((block_number, halted_tx_hashes))
     }
 
     /// Adds a lot of tokens to a given account with a specified balance.
@@ -392,7 +481,7 @@ impl InMemoryNode {
         mut l2_tx: L2Tx,
         base_contracts: BaseSystemContracts,
         state_override: Option<StateOverride>,
-    ) -> AnvilNodeResult<ExecutionResult> {
+    ) -> AnvilNodeResult<VmExecutionResultAndLogs> {
         let execution_mode = TxExecutionMode::EthCall;
 
         let inner = self.inner.read().await;
@@ -402,15 +491,17 @@ impl InMemoryNode {
         let (batch_env, _) = inner.create_l1_batch_env().await;
         let system_env = inner.create_system_env(base_contracts, execution_mode);
 
+        // Calls may run concurrently with other executions, keep their fork errors separate
+        let fork_storage = inner.fork_storage.with_error_scope();
+
         let storage_override = if let Some(state_override) = state_override {
-            apply_state_override(inner.read_storage(), state_override)
+            apply_state_override(&fork_storage, state_override)
         } else {
             // Do not spawn a new thread in the most frequent case.
-            StorageWithOverrides::new(inner.read_storage())
+            StorageWithOverrides::new(&fork_storage)
         };
 
         let storage = StorageView::new(storage_override).to_rc_ptr();
-
         let mut vm = if self.system_contracts.boojum.use_boojum {
             AnvilVM::BoojumOs(super::boojumos::BoojumOsVM::<_, HistoryDisabled>::new(
                 batch_env,
@@ -443,12 +534,21 @@ impl InMemoryNode {
             vm,
             inspect(&mut tracers.into(), InspectExecutionMode::OneTx)
         );
+        if let Some(error) = fork_storage.take_fork_error() {
+            return Err(error);
+        }
 
         let call_traces = Arc::try_unwrap(call_tracer_result)
             .unwrap()
             .take()
             .unwrap_or_default();
 
+        // Ignore the error as it only means that nobody asked for console logs
+        let _ = CALL_CONSOLE_LOGS.try_with(|logs| {
+            logs.borrow_mut()
+                .extend(ConsoleLogHandler::default().collect_calls_recursive(&call_traces))
+        });
+
         let verbosity = get_shell().verbosity;
         if !call_traces.is_empty() && verbosity >= 2 {
             let tx_result_for_arena = tx_result.clone();
@@ -485,7 +585,7 @@ impl InMemoryNode {
             sh_println!("\nTraces:\n{}", trace_output);
         }
 
-        Ok(tx_result.result)
+        Ok(tx_result)
     }
 
     // Forcefully stores the given bytecode at a given account.
@@ -679,6 +779,7 @@ impl InMemoryNode {
             impersonation,
             pool,
             block_sealer_state,
+            ForkFollowerState::default(),
             system_contracts,
             storage_key_layout,
         )
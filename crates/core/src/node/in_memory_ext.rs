@@ -1,7 +1,14 @@
+use super::fork::ForkAt;
+use super::fork_url::ForkUrl;
 use super::pool::TxBatch;
 use super::sealer::BlockSealerMode;
 use super::InMemoryNode;
-use anvil_zksync_types::api::{DetailedTransaction, ResetRequest};
+use alloy::signers::local::PrivateKeySigner;
+use anvil_zksync_config::constants::DEFAULT_MNEMONIC;
+use anvil_zksync_config::types::AccountGenerator;
+use anvil_zksync_types::api::{
+    DetailedTransaction, ForkFollowStatus, GasProfile, GasReport, NewAccount, ResetRequest,
+};
 use anyhow::{anyhow, Context};
 use std::str::FromStr;
 use std::time::Duration;
@@ -10,7 +17,9 @@ use zksync_error::anvil_zksync::node::AnvilNodeResult;
 use zksync_types::api::{Block, TransactionVariant};
 use zksync_types::bytecode::{BytecodeHash, BytecodeMarker};
 use zksync_types::u256_to_h256;
-use zksync_types::{AccountTreeId, Address, L2BlockNumber, StorageKey, H256, U256, U64};
+use zksync_types::{
+    AccountTreeId, Address, L1BatchNumber, L2BlockNumber, StorageKey, H160, H256, U256, U64,
+};
 
 type Result<T> = anyhow::Result<T>;
 
@@ -235,9 +244,32 @@ impl InMemoryNode {
                     .reset_fork_block_number_sync(L2BlockNumber(to.as_u32()))
                     .await?;
             } else if let Some(forking) = spec.forking {
-                let url = Url::from_str(&forking.json_rpc_url).context("malformed fork URL")?;
-                let block_number = forking.block_number.map(|n| L2BlockNumber(n.as_u32()));
-                self.node_handle.reset_fork_sync(url, block_number).await?;
+                // Accepts the same networks as `--fork-url`, names from the fork endpoints file
+                // included
+                let fork_url =
+                    ForkUrl::from_str(&forking.json_rpc_url).map_err(|err| anyhow!(err))?;
+                let fork_config = {
+                    let reader = self.inner.read().await;
+                    fork_url.to_config(&reader.config.fork_endpoints, &reader.config.fork_rpc)?
+                };
+                let fork_at = match (
+                    forking.block_number,
+                    forking.timestamp,
+                    forking.l1_batch_number,
+                ) {
+                    (None, None, None) => ForkAt::Latest,
+                    (Some(n), None, None) => ForkAt::BlockNumber(L2BlockNumber(n.as_u32())),
+                    (None, Some(timestamp), None) => ForkAt::Timestamp(timestamp.as_u64()),
+                    (None, None, Some(n)) => ForkAt::L1Batch(L1BatchNumber(n.as_u32())),
+                    _ => {
+                        return Err(anyhow!(
+                            "Only one of 'blockNumber', 'timestamp' and 'l1BatchNumber' attributes can be specified"
+                        ));
+                    }
+                };
+                self.node_handle
+                    .reset_fork_sync(fork_config, fork_at)
+                    .await?;
             } else {
                 self.node_handle.remove_fork_sync().await?;
             }
@@ -256,6 +288,14 @@ impl InMemoryNode {
         self.impersonation.set_auto_impersonation(enabled);
     }
 
+    pub async fn set_auto_fund(&self, enabled: bool) {
+        self.inner.write().await.config.auto_fund = enabled;
+        tracing::debug!(
+            "Auto-funding has been {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+
     pub fn impersonate_account(&self, address: Address) -> Result<bool> {
         if self.impersonation.impersonate(address) {
             tracing::debug!("Account {:?} has been impersonated", address);
@@ -279,6 +319,37 @@ impl InMemoryNode {
         }
     }
 
+    pub async fn add_account(&self, account: NewAccount) -> Result<Address> {
+        let mut writer = self.inner.write().await;
+        let signer = match account {
+            NewAccount::DerivationIndex(index) => writer
+                .config
+                .account_generator
+                .clone()
+                .unwrap_or_else(|| AccountGenerator::new(0).phrase(DEFAULT_MNEMONIC))
+                .gen_at(index),
+            NewAccount::PrivateKey(key) => PrivateKeySigner::from_bytes(&key.0.into())
+                .map_err(|err| anyhow!("invalid private key: {err}"))?,
+        };
+        let address = H160::from_slice(signer.address().as_ref());
+        if writer.add_signer_account(signer) {
+            tracing::debug!("Account {:?} has been added", address);
+        } else {
+            tracing::debug!("Account {:?} was already managed", address);
+        }
+        Ok(address)
+    }
+
+    pub async fn remove_account(&self, address: Address) -> Result<bool> {
+        if self.inner.write().await.remove_signer_account(&address) {
+            tracing::debug!("Account {:?} has been removed", address);
+            Ok(true)
+        } else {
+            tracing::debug!("Account {:?} was not managed, nothing to remove", address);
+            Ok(false)
+        }
+    }
+
     pub async fn set_code(&self, address: Address, code: String) -> Result<()> {
         let code_slice = code
             .strip_prefix("0x")
@@ -398,6 +469,29 @@ impl InMemoryNode {
         Ok(())
     }
 
+    pub fn set_fork_following(&self, enabled: bool) -> Result<()> {
+        if !self.fork_follower.is_enabled() {
+            return Err(anyhow!(
+                "Fork following is not enabled, start the node with `fork --fork-follow`"
+            ));
+        }
+        self.fork_follower.set_paused(!enabled);
+        tracing::debug!(
+            "Fork following has been {}",
+            if enabled { "resumed" } else { "paused" }
+        );
+        Ok(())
+    }
+
+    pub fn get_fork_follow_status(&self) -> Result<ForkFollowStatus> {
+        if !self.fork_follower.is_enabled() {
+            return Err(anyhow!(
+                "Fork following is not enabled, start the node with `fork --fork-follow`"
+            ));
+        }
+        Ok(self.fork_follower.status())
+    }
+
     pub async fn set_chain_id(&self, id: u32) -> Result<()> {
         let mut inner = self.inner.write().await;
 
@@ -405,16 +499,43 @@ impl InMemoryNode {
         inner.fork_storage.set_chain_id(id.into());
         Ok(())
     }
+
+    pub async fn get_console_logs(&self, hash: H256) -> Result<Option<Vec<String>>> {
+        Ok(self.blockchain.get_tx_console_logs(&hash).await)
+    }
+
+    pub async fn get_gas_report(&self) -> Result<GasReport> {
+        Ok(self.inner.read().await.gas_report())
+    }
+
+    pub async fn get_gas_profile(&self, hash: H256) -> Result<Option<GasProfile>> {
+        if !self.inner.read().await.config.is_gas_profile_enabled() {
+            anyhow::bail!(
+                "gas profiles are not recorded for executed transactions, restart the node with `--gas-profile`"
+            );
+        }
+        Ok(self
+            .blockchain
+            .get_tx_gas_profile(&hash)
+            .await
+            .map(|profile| GasProfile {
+                folded: profile.to_folded(),
+                svg: profile.to_svg(&format!("{hash:?}")),
+            }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::node::InMemoryNode;
+    use crate::node::inner::blockchain::ReadBlockchain;
+    use crate::node::{InMemoryNode, TransactionResult};
+    use crate::testing;
     use crate::testing::TransactionBuilder;
+    use anvil_zksync_common::cache::CacheConfig;
     use std::str::FromStr;
     use zksync_multivm::interface::storage::ReadStorage;
-    use zksync_types::{api, L1BatchNumber, Transaction};
+    use zksync_types::{api, L1BatchNumber, Nonce, Transaction};
     use zksync_types::{h256_to_u256, L2ChainId, H256};
 
     #[tokio::test]
@@ -566,6 +687,39 @@ mod tests {
         assert_ne!(node.blockchain.current_block_hash().await, H256::random());
     }
 
+    #[tokio::test]
+    async fn test_reset_to_named_fork_endpoint() {
+        let mock_server = testing::MockServer::run_with_config(testing::ForkBlockConfig {
+            number: 10,
+            hash: H256::repeat_byte(0xab),
+            transaction_count: 0,
+        });
+        let fork_endpoints = serde_json::from_value(serde_json::json!({
+            "networks": { "local": { "url": mock_server.url() } }
+        }))
+        .unwrap();
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig::default().with_fork_endpoints(fork_endpoints),
+        );
+
+        let reset_spec = ResetRequest {
+            to: None,
+            forking: Some(anvil_zksync_types::api::ResetRequestForking {
+                json_rpc_url: "local".to_string(),
+                block_number: None,
+                timestamp: None,
+                l1_batch_number: None,
+            }),
+        };
+        assert!(node.reset_network(Some(reset_spec)).await.unwrap());
+
+        assert_eq!(
+            node.blockchain.current_block_number().await,
+            L2BlockNumber(10)
+        );
+    }
+
     #[tokio::test]
     async fn test_impersonate_account() {
         let node = InMemoryNode::test(None);
@@ -621,6 +775,129 @@ mod tests {
         assert!(node.apply_txs([generate_tx()]).await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_auto_fund() {
+        let node = InMemoryNode::test(None);
+        let sender = Address::from_str("0xd8da6bf26964af9d7eed9e03e53415d37aa96045").unwrap();
+        node.impersonate_account(sender)
+            .expect("impersonate_account");
+
+        let tx = Transaction::from(TransactionBuilder::new().impersonate(sender));
+        assert!(node.apply_txs([tx]).await.is_err());
+
+        node.set_auto_fund(true).await;
+        let tx = Transaction::from(TransactionBuilder::new().impersonate(sender));
+        let tx_hash = tx.hash();
+        let receipts = node.apply_txs([tx]).await.expect("auto-funded tx failed");
+        assert_eq!(receipts[0].status, U64::from(1));
+
+        let auto_funded = node
+            .blockchain
+            .get_tx_auto_funded(&tx_hash)
+            .await
+            .expect("tx was not auto-funded");
+        assert!(!auto_funded.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_auto_fund_rejected_tx() {
+        let node = InMemoryNode::test(None);
+        let sender = Address::from_str("0xd8da6bf26964af9d7eed9e03e53415d37aa96045").unwrap();
+        node.impersonate_account(sender)
+            .expect("impersonate_account");
+        node.set_auto_fund(true).await;
+
+        // Nonce is too high, so the transaction fails validation and never makes it into a block
+        let tx = anvil_zksync_types::L2TxBuilder::new(
+            sender,
+            Nonce(5),
+            U256::from(4_000_000),
+            U256::from(50_000_000),
+            260.into(),
+        )
+        .with_to(Address::random())
+        .build_impersonated();
+        assert!(node.apply_txs([tx.into()]).await.is_err());
+
+        // Top-up must have been taken back
+        assert_eq!(
+            node.get_balance_impl(sender, None).await.unwrap(),
+            U256::zero()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_account() {
+        let node = InMemoryNode::test(None);
+
+        // accounts `0..10` are already derived from the default mnemonic
+        let address = node
+            .add_account(NewAccount::DerivationIndex(10))
+            .await
+            .expect("add_account");
+        assert!(node.accounts_impl().await.unwrap().contains(&address));
+        let signature = node.sign_impl(address, vec![0x1].into()).await;
+        assert!(signature.is_ok());
+
+        let private_key = H256::repeat_byte(0x11);
+        let from_key = node
+            .add_account(NewAccount::PrivateKey(private_key))
+            .await
+            .expect("add_account");
+        assert_eq!(
+            from_key,
+            zksync_types::K256PrivateKey::from_bytes(private_key)
+                .unwrap()
+                .address()
+        );
+
+        // managed accounts are only dumped on request and merged into existing ones on load
+        async fn dump_state(node: &InMemoryNode) -> crate::node::state::StateV1 {
+            let state = node.inner.read().await.dump_state(false).await.unwrap();
+            let crate::node::VersionedState::V1 { state, .. } = state else {
+                panic!("unexpected state version");
+            };
+            state
+        }
+        assert_eq!(dump_state(&node).await.signer_accounts, None);
+        node.inner.write().await.config.dump_signer_accounts = true;
+        let state = dump_state(&node).await;
+        assert!(state
+            .signer_accounts
+            .as_ref()
+            .unwrap()
+            .contains(&private_key));
+
+        let other_node = InMemoryNode::test(None);
+        let other_address = other_node
+            .add_account(NewAccount::DerivationIndex(11))
+            .await
+            .expect("add_account");
+        other_node
+            .inner
+            .write()
+            .await
+            .load_state(crate::node::VersionedState::v1(state))
+            .await
+            .expect("load_state");
+        let other_accounts = other_node.accounts_impl().await.unwrap();
+        assert!(other_accounts.contains(&from_key));
+        assert!(other_accounts.contains(&other_address));
+
+        assert!(node.remove_account(address).await.unwrap());
+        assert!(!node.remove_account(address).await.unwrap());
+        assert!(!node.accounts_impl().await.unwrap().contains(&address));
+        assert!(node.sign_impl(address, vec![0x1].into()).await.is_err());
+
+        // funded dev accounts disappear from `eth_accounts` too and the rest keep their order
+        let accounts = node.accounts_impl().await.unwrap();
+        let dev_account = accounts[0];
+        node.set_rich_account(dev_account, U256::exp10(18)).await;
+        assert_eq!(node.accounts_impl().await.unwrap(), accounts);
+        assert!(node.remove_account(dev_account).await.unwrap());
+        assert_eq!(node.accounts_impl().await.unwrap(), accounts[1..]);
+    }
+
     #[tokio::test]
     async fn test_set_code() {
         let address = Address::repeat_byte(0x1);
@@ -1016,4 +1293,62 @@ mod tests {
             node_inner.fork_storage.chain_id
         );
     }
+
+    #[tokio::test]
+    async fn test_get_console_logs() {
+        let node = InMemoryNode::test(None);
+        node.inner
+            .write()
+            .await
+            .insert_tx_result(
+                H256::repeat_byte(0x1),
+                TransactionResult {
+                    console_logs: vec!["\"hello\"".to_string(), "42".to_string()],
+                    ..testing::default_tx_result()
+                },
+            )
+            .await;
+
+        let logs = node
+            .get_console_logs(H256::repeat_byte(0x1))
+            .await
+            .expect("failed getting console logs");
+        assert_eq!(logs, Some(vec!["\"hello\"".to_string(), "42".to_string()]));
+
+        let missing = node
+            .get_console_logs(H256::repeat_byte(0x2))
+            .await
+            .expect("failed getting console logs");
+        assert_eq!(missing, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_gas_profile() {
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig {
+                cache_config: CacheConfig::None,
+                gas_profile: true,
+                ..Default::default()
+            },
+        );
+        let (tx_hash, _, _) = testing::apply_tx(&node).await;
+
+        assert!(node
+            .get_gas_profile(tx_hash)
+            .await
+            .expect("failed getting gas profile")
+            .is_some());
+        assert!(node
+            .get_gas_profile(H256::repeat_byte(0x1))
+            .await
+            .expect("failed getting gas profile")
+            .is_none());
+
+        let err = InMemoryNode::test(None)
+            .get_gas_profile(tx_hash)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("--gas-profile"));
+    }
 }
@@ -1,14 +1,20 @@
 use crate::filters::LogFilter;
+use crate::node::gas_profile::FoldedStacks;
 use crate::node::inner::fork::ForkDetails;
 use crate::node::time::{ReadTime, Time};
 use crate::node::{create_genesis, create_genesis_from_json, TransactionResult};
 use crate::utils::utc_datetime_from_epoch_ms;
+use anvil_zksync_common::address_map;
 use anvil_zksync_config::types::Genesis;
-use anvil_zksync_types::api::DetailedTransaction;
+use anvil_zksync_types::api::{
+    DetailedTransaction, LocalizedTransactionTrace, StructLogConfig, StructLogTrace,
+};
 use anyhow::Context;
 use async_trait::async_trait;
+use indexmap::IndexSet;
 use itertools::Itertools;
-use std::collections::HashMap;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::sync::Arc;
 use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -18,10 +24,11 @@ use zksync_multivm::interface::{FinishedL1Batch, L2Block, VmEvent};
 use zksync_multivm::vm_latest::utils::l2_blocks::load_last_l2_block;
 use zksync_types::block::{unpack_block_info, L1BatchHeader, L2BlockHasher};
 use zksync_types::l2::L2Tx;
+use zksync_types::web3::keccak256;
 use zksync_types::writes::StateDiffRecord;
 use zksync_types::{
     api, h256_to_u256, AccountTreeId, Address, ExecuteTransactionCommon, L1BatchNumber,
-    L2BlockNumber, ProtocolVersionId, StorageKey, H256, SYSTEM_CONTEXT_ADDRESS,
+    L2BlockNumber, Nonce, ProtocolVersionId, StorageKey, H256, SYSTEM_CONTEXT_ADDRESS,
     SYSTEM_CONTEXT_BLOCK_INFO_POSITION, U256, U64,
 };
 
@@ -127,6 +134,34 @@ pub trait ReadBlockchain: Send + Sync + Debug {
     /// fork instead.
     async fn get_tx_debug_info(&self, tx_hash: &H256, only_top: bool) -> Option<api::DebugCall>;
 
+    /// Retrieve `console.log` messages emitted by transaction by transaction's hash. Returns `None`
+    /// if no transaction was found. Note that the transaction might still be a part of the chain
+    /// but is available in the fork instead.
+    async fn get_tx_console_logs(&self, tx_hash: &H256) -> Option<Vec<String>>;
+
+    /// Retrieve transaction gas profile by transaction's hash. Returns `None` if no transaction was
+    /// found or it was executed without gas profiling. Note that the transaction might still be a
+    /// part of the chain but is available in the fork instead.
+    async fn get_tx_gas_profile(&self, tx_hash: &H256) -> Option<FoldedStacks>;
+
+    /// Retrieve the amount transaction's initiator was topped up by in auto-fund mode by
+    /// transaction's hash. Returns `None` if no transaction was found or it was not auto-funded.
+    async fn get_tx_auto_funded(&self, tx_hash: &H256) -> Option<U256>;
+
+    /// Retrieve transaction struct-log trace by transaction's hash. Returns `None` if no
+    /// transaction was found. Note that the transaction might still be a part of the chain but is
+    /// available in the fork instead.
+    async fn get_tx_struct_log_trace(
+        &self,
+        tx_hash: &H256,
+        config: StructLogConfig,
+    ) -> Option<StructLogTrace>;
+
+    /// Retrieve transaction's call tree flattened into Parity-style traces by transaction's hash.
+    /// Returns `None` if no transaction was found. Note that the transaction might still be a part
+    /// of the chain but is available in the fork instead.
+    async fn get_tx_parity_traces(&self, tx_hash: &H256) -> Option<Vec<LocalizedTransactionTrace>>;
+
     /// Retrieve transaction in API format by transaction's hash. Returns `None` if no transaction was
     /// found. Note that the transaction might still be a part of the chain but is available in the
     /// fork instead.
@@ -147,6 +182,26 @@ pub trait ReadBlockchain: Send + Sync + Debug {
     /// of the chain but is available in the fork instead.
     async fn get_zksync_tx(&self, tx_hash: &H256) -> Option<zksync_types::Transaction>;
 
+    /// Retrieve hashes of all transactions the address took part in (as initiator or a party of
+    /// any call made by the transaction) along with their block numbers. Hashes are ordered from
+    /// the oldest to the newest transaction. Does not include pre-fork transactions.
+    async fn get_address_tx_hashes(&self, address: &Address) -> Vec<(L2BlockNumber, H256)>;
+
+    /// Retrieve transaction's hash by its initiator and nonce. Returns `None` if no transaction was
+    /// found. Note that the transaction might still be a part of the chain but is available in the
+    /// fork instead.
+    async fn get_tx_hash_by_sender_and_nonce(&self, sender: &Address, nonce: Nonce)
+        -> Option<H256>;
+
+    /// Retrieve hash of the transaction that deployed the contract along with the contract's
+    /// creator. Returns `None` if no such transaction was found. Note that the contract might
+    /// still have been deployed by a transaction available in the fork instead.
+    async fn get_contract_creator(&self, address: &Address) -> Option<(H256, Address)>;
+
+    /// Retrieve contracts that emitted an ERC20-like `Transfer` event, ordered by their first
+    /// transfer. Does not include pre-fork transfers.
+    async fn get_token_transfer_emitters(&self) -> Vec<Address>;
+
     /// Retrieve all logs matching given filter. Does not return matching logs from pre-fork blocks.
     async fn get_filter_logs(&self, log_filter: &LogFilter) -> Vec<api::Log>;
 
@@ -378,6 +433,35 @@ impl ReadBlockchain for Blockchain {
         self.inspect_tx(tx_hash, |tx| tx.debug_info(only_top)).await
     }
 
+    async fn get_tx_console_logs(&self, tx_hash: &H256) -> Option<Vec<String>> {
+        self.inspect_tx(tx_hash, |tx| tx.console_logs.clone()).await
+    }
+
+    async fn get_tx_gas_profile(&self, tx_hash: &H256) -> Option<FoldedStacks> {
+        self.inspect_tx(tx_hash, |tx| tx.gas_profile.clone())
+            .await
+            .flatten()
+    }
+
+    async fn get_tx_auto_funded(&self, tx_hash: &H256) -> Option<U256> {
+        self.inspect_tx(tx_hash, |tx| tx.auto_funded)
+            .await
+            .flatten()
+    }
+
+    async fn get_tx_struct_log_trace(
+        &self,
+        tx_hash: &H256,
+        config: StructLogConfig,
+    ) -> Option<StructLogTrace> {
+        self.inspect_tx(tx_hash, |tx| tx.struct_log_trace(config))
+            .await
+    }
+
+    async fn get_tx_parity_traces(&self, tx_hash: &H256) -> Option<Vec<LocalizedTransactionTrace>> {
+        self.inspect_tx(tx_hash, |tx| tx.parity_traces()).await
+    }
+
     async fn get_tx_api(&self, tx_hash: &H256) -> anyhow::Result<Option<api::Transaction>> {
         self.inspect_tx(tx_hash, |TransactionResult { info, receipt, .. }| {
             let l2_tx: L2Tx =
@@ -473,6 +557,56 @@ impl ReadBlockchain for Blockchain {
             .await
     }
 
+    async fn get_address_tx_hashes(&self, address: &Address) -> Vec<(L2BlockNumber, H256)> {
+        self.inner
+            .read()
+            .await
+            .tx_indexes
+            .by_address
+            .get(address)
+            .map(|txs| {
+                txs.iter()
+                    .map(|((block_number, _), tx_hash)| (*block_number, *tx_hash))
+                    .collect_vec()
+            })
+            .unwrap_or_default()
+    }
+
+    async fn get_tx_hash_by_sender_and_nonce(
+        &self,
+        sender: &Address,
+        nonce: Nonce,
+    ) -> Option<H256> {
+        self.inner
+            .read()
+            .await
+            .tx_indexes
+            .by_sender_nonce
+            .get(&(*sender, nonce))
+            .copied()
+    }
+
+    async fn get_contract_creator(&self, address: &Address) -> Option<(H256, Address)> {
+        self.inner
+            .read()
+            .await
+            .tx_indexes
+            .contract_creators
+            .get(address)
+            .copied()
+    }
+
+    async fn get_token_transfer_emitters(&self) -> Vec<Address> {
+        self.inner
+            .read()
+            .await
+            .tx_indexes
+            .token_transfer_emitters
+            .iter()
+            .copied()
+            .collect()
+    }
+
     async fn get_filter_logs(&self, log_filter: &LogFilter) -> Vec<api::Log> {
         let latest_block_number = self.current_block_number().await;
         // FIXME: This should traverse blocks from `log_filter.from_block` to `log_filter.to_block`
@@ -537,6 +671,7 @@ impl Blockchain {
                 current_block: fork_details.block_number,
                 current_block_hash: fork_details.block_hash,
                 tx_results: Default::default(),
+                tx_indexes: Default::default(),
                 blocks: HashMap::from_iter([(
                     fork_details.block_hash,
                     fork_details.api_block.clone(),
@@ -565,6 +700,7 @@ impl Blockchain {
                 current_block: L2BlockNumber(0),
                 current_block_hash: block_hash,
                 tx_results: Default::default(),
+                tx_indexes: Default::default(),
                 blocks: HashMap::from_iter([(block_hash, genesis_block)]),
                 hashes: HashMap::from_iter([(L2BlockNumber(0), block_hash)]),
                 batches: HashMap::from_iter([(L1BatchNumber(0), genesis_batch_info)]),
@@ -604,6 +740,8 @@ pub(super) struct BlockchainState {
     pub(super) current_block_hash: H256,
     /// Map from transaction to details about the execution.
     pub(super) tx_results: HashMap<H256, TransactionResult>,
+    /// Secondary indexes over `tx_results`.
+    pub(super) tx_indexes: TxIndexes,
     /// Map from block hash to information about the block.
     pub(super) blocks: HashMap<H256, api::Block<api::TransactionVariant>>,
     /// Map from block number to a block hash.
@@ -622,6 +760,92 @@ struct StoredL1BatchInfo {
     aggregation_root: H256,
 }
 
+/// Secondary indexes over executed transactions. They are derived from transaction results and
+/// hence are never persisted on their own.
+#[derive(Debug, Clone, Default)]
+pub(super) struct TxIndexes {
+    /// Map from address to transactions it took part in, keyed by their position in the chain
+    /// (block number and index in the block).
+    by_address: HashMap<Address, BTreeMap<(L2BlockNumber, u64), H256>>,
+    /// Map from transaction's initiator and nonce to its hash.
+    by_sender_nonce: HashMap<(Address, Nonce), H256>,
+    /// Map from deployed contract to the hash of transaction that deployed it and its creator.
+    contract_creators: HashMap<Address, (H256, Address)>,
+    /// Contracts that emitted an ERC20-like `Transfer` event, in order of their first transfer.
+    token_transfer_emitters: IndexSet<Address>,
+}
+
+/// `keccak256("Transfer(address,address,uint256)")`
+static TRANSFER_EVENT_TOPIC: Lazy<H256> =
+    Lazy::new(|| H256(keccak256(b"Transfer(address,address,uint256)")));
+
+impl TxIndexes {
+    pub(super) fn new<'a>(tx_results: impl IntoIterator<Item = &'a TransactionResult>) -> Self {
+        let mut indexes = Self::default();
+        for tx_result in tx_results {
+            indexes.insert(tx_result);
+        }
+        indexes
+    }
+
+    pub(super) fn insert(&mut self, tx_result: &TransactionResult) {
+        let tx = &tx_result.info.tx;
+        let tx_hash = tx_result.receipt.transaction_hash;
+        let position = (
+            L2BlockNumber(tx_result.receipt.block_number.as_u32()),
+            tx_result.receipt.transaction_index.as_u64(),
+        );
+        if let Some(nonce) = tx.nonce() {
+            self.by_sender_nonce
+                .insert((tx.initiator_account(), nonce), tx_hash);
+        }
+
+        // ERC20 `Transfer` indexes `from` and `to` only, ERC721 also indexes the token id
+        for log in &tx_result.receipt.logs {
+            if log.topics.len() == 3
+                && log.topics[0] == *TRANSFER_EVENT_TOPIC
+                && log.data.0.len() == 32
+                && !address_map::is_system(&log.address)
+            {
+                self.token_transfer_emitters.insert(log.address);
+            }
+        }
+
+        let mut addresses = HashSet::from([tx.initiator_account()]);
+        self.insert_call(&tx_result.debug, tx_hash, &mut addresses);
+        for address in addresses {
+            self.by_address
+                .entry(address)
+                .or_default()
+                .insert(position, tx_hash);
+        }
+    }
+
+    fn insert_call(
+        &mut self,
+        call: &api::DebugCall,
+        tx_hash: H256,
+        addresses: &mut HashSet<Address>,
+    ) {
+        let is_user_address = |address: &Address| {
+            !address_map::is_system(address) && !address_map::is_precompile(address)
+        };
+        addresses.extend([call.from, call.to].into_iter().filter(is_user_address));
+        if matches!(call.r#type, api::DebugCallType::Create)
+            && call.error.is_none()
+            && call.revert_reason.is_none()
+            && is_user_address(&call.to)
+        {
+            self.contract_creators
+                .entry(call.to)
+                .or_insert((tx_hash, call.from));
+        }
+        for child in &call.calls {
+            self.insert_call(child, tx_hash, addresses);
+        }
+    }
+}
+
 impl BlockchainState {
     pub(super) fn get_block_hash_by_number(&self, number: L2BlockNumber) -> Option<H256> {
         self.hashes.get(&number).copied()
@@ -752,6 +976,9 @@ impl BlockchainState {
             aggregation_root,
         };
         self.batches.insert(self.current_batch, batch_info);
+        for tx_result in &tx_results {
+            self.tx_indexes.insert(tx_result);
+        }
         self.tx_results.extend(
             tx_results
                 .into_iter()
@@ -811,6 +1038,7 @@ impl BlockchainState {
                 hash = %transaction.receipt.transaction_hash,
                 "loading new transaction from supplied state"
             );
+            self.tx_indexes.insert(&transaction);
             self.tx_results
                 .insert(transaction.receipt.transaction_hash, transaction);
         }
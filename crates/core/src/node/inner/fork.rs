@@ -1,29 +1,51 @@
+use super::fork_file::FileForkSource;
+use super::fork_fixture::{FixtureHeader, FixtureRecorder, FixtureReplayer, ForkFixture};
 use anvil_zksync_common::{
     cache::{Cache, CacheConfig},
-    sh_err,
+    sh_err, sh_warn,
 };
 use anvil_zksync_config::constants::{
     DEFAULT_ESTIMATE_GAS_PRICE_SCALE_FACTOR, DEFAULT_ESTIMATE_GAS_SCALE_FACTOR,
     DEFAULT_FAIR_PUBDATA_PRICE,
 };
+use anvil_zksync_config::types::ForkRpcConfig;
 use anyhow::Context;
 use async_trait::async_trait;
-use futures::TryFutureExt;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use futures::future::BoxFuture;
 use itertools::Itertools;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 use std::future::Future;
-use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tower::limit::ConcurrencyLimitLayer;
 use tracing::Instrument;
 use url::Url;
+use zksync_error::anvil_zksync::node::AnvilNodeError;
 use zksync_types::fee_model::FeeParams;
 use zksync_types::url::SensitiveUrl;
 use zksync_types::web3::Index;
 use zksync_types::{
-    api, Address, L1BatchNumber, L2BlockNumber, L2ChainId, ProtocolVersionId, Transaction, H256,
-    U256,
+    api, h256_to_u256, Address, L1BatchNumber, L2BlockNumber, L2ChainId, ProtocolVersionId,
+    StorageKey, Transaction, H256, U256,
 };
-use zksync_web3_decl::client::{DynClient, L2};
+use zksync_web3_decl::client::{ClientBuilder, DynClient, L2};
 use zksync_web3_decl::error::Web3Error;
+use zksync_web3_decl::jsonrpsee::core::client::{BatchResponse, ClientT};
+use zksync_web3_decl::jsonrpsee::core::params::BatchRequestBuilder;
+use zksync_web3_decl::jsonrpsee::core::ClientError;
+use zksync_web3_decl::jsonrpsee::http_client::transport::Error as HttpTransportError;
+use zksync_web3_decl::jsonrpsee::http_client::HttpClientBuilder;
+use zksync_web3_decl::jsonrpsee::rpc_params;
+use zksync_web3_decl::jsonrpsee::types::error::METHOD_NOT_FOUND_CODE;
 use zksync_web3_decl::namespaces::{EthNamespaceClient, ZksNamespaceClient};
 
 /// Trait that provides necessary data when forking a remote chain.
@@ -56,6 +78,25 @@ pub trait ForkSource: fmt::Debug + Send + Sync {
     /// Returns the bytecode stored under this hash (if available).
     async fn get_bytecode_by_hash(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>>;
 
+    /// Fetches fork's storage values for all given keys at the forked block. Results are returned
+    /// in the same order as `keys`.
+    async fn get_storage_at_forked_batch(&self, keys: &[StorageKey]) -> anyhow::Result<Vec<H256>>;
+
+    /// Returns the bytecodes stored under the given hashes (if available). Results are returned in
+    /// the same order as `hashes`.
+    async fn get_bytecodes_by_hash_batch(
+        &self,
+        hashes: &[H256],
+    ) -> anyhow::Result<Vec<Option<Vec<u8>>>>;
+
+    /// Fetches enumeration indices of the given storage slots in the fork's Merkle tree (`0` for
+    /// slots that were never written to). Results are returned in the same order as `keys`.
+    /// Returns `None` if the fork does not serve its Merkle tree.
+    async fn get_enumeration_indices_batch(
+        &self,
+        keys: &[StorageKey],
+    ) -> anyhow::Result<Option<Vec<u64>>>;
+
     /// Fetches fork's transaction for a given hash.
     async fn get_transaction_by_hash(&self, hash: H256)
         -> anyhow::Result<Option<api::Transaction>>;
@@ -66,6 +107,19 @@ pub trait ForkSource: fmt::Debug + Send + Sync {
         hash: H256,
     ) -> anyhow::Result<Option<api::TransactionDetails>>;
 
+    /// Fetches fork's transaction receipt for a given hash.
+    async fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<api::TransactionReceipt>>;
+
+    /// Fetches data returned by fork's transaction with the given hash (from its top-level call
+    /// trace). Returns `None` if the transaction is not known or the fork cannot trace it.
+    async fn get_transaction_return_data(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<zksync_types::web3::Bytes>>;
+
     /// Fetches fork's transactions that belong to a block with the given number.
     async fn get_raw_block_transactions(
         &self,
@@ -175,7 +229,7 @@ impl Clone for Box<dyn ForkSource> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForkDetails {
     /// Chain ID of the fork.
     pub chain_id: L2ChainId,
@@ -202,10 +256,37 @@ pub struct ForkDetails {
     pub fee_params: FeeParams,
 }
 
+/// Point of the remote chain to fork at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForkAt {
+    /// Latest block of the remote chain.
+    Latest,
+    /// Block with the given number.
+    BlockNumber(L2BlockNumber),
+    /// Last block whose timestamp (in seconds) is at or before the given one.
+    Timestamp(u64),
+    /// Last block of the given L1 batch.
+    L1Batch(L1BatchNumber),
+}
+
+impl From<Option<L2BlockNumber>> for ForkAt {
+    fn from(block_number: Option<L2BlockNumber>) -> Self {
+        block_number.map_or(ForkAt::Latest, ForkAt::BlockNumber)
+    }
+}
+
+#[derive(Debug)]
 pub struct ForkConfig {
     pub url: Url,
     pub estimate_gas_price_scale_factor: f64,
     pub estimate_gas_scale_factor: f32,
+    /// Endpoints serving the same network as `url`. Requests are load-balanced across all of them.
+    pub additional_urls: Vec<Url>,
+    /// Retry, timeout and rate limiting settings for the clients talking to the endpoints.
+    pub rpc: ForkRpcConfig,
+    /// Settings overriding `rpc` for individual endpoints (e.g. ones requiring credentials).
+    /// Retries are always configured by `rpc`.
+    pub endpoint_rpc: HashMap<Url, ForkRpcConfig>,
 }
 
 impl ForkConfig {
@@ -222,29 +303,434 @@ impl ForkConfig {
             url,
             estimate_gas_price_scale_factor,
             estimate_gas_scale_factor,
+            additional_urls: Vec::new(),
+            rpc: ForkRpcConfig::default(),
+            endpoint_rpc: HashMap::new(),
+        }
+    }
+
+    /// Overrides scale factors applied to gas price and gas limit estimates.
+    pub fn with_scale_factors(
+        mut self,
+        estimate_gas_price_scale_factor: f64,
+        estimate_gas_scale_factor: f32,
+    ) -> Self {
+        self.estimate_gas_price_scale_factor = estimate_gas_price_scale_factor;
+        self.estimate_gas_scale_factor = estimate_gas_scale_factor;
+        self
+    }
+
+    /// Adds endpoints that serve the same network as the primary `url`.
+    pub fn with_additional_urls(mut self, urls: impl IntoIterator<Item = Url>) -> Self {
+        self.additional_urls.extend(urls);
+        self
+    }
+
+    /// Overrides fork client's retry, timeout and rate limiting settings.
+    pub fn with_rpc(mut self, rpc: ForkRpcConfig) -> Self {
+        self.rpc = rpc;
+        self
+    }
+
+    /// Overrides fork client's settings for specific endpoints.
+    pub fn with_endpoint_rpc(
+        mut self,
+        endpoint_rpc: impl IntoIterator<Item = (Url, ForkRpcConfig)>,
+    ) -> Self {
+        self.endpoint_rpc.extend(endpoint_rpc);
+        self
+    }
+
+    /// Builds clients for the primary and all additional endpoints.
+    fn build_endpoints(&self) -> anyhow::Result<ForkEndpoints> {
+        let endpoints = std::iter::once(&self.url)
+            .chain(&self.additional_urls)
+            .map(|url| self.build_endpoint(url.clone()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(ForkEndpoints::new(endpoints))
+    }
+
+    /// Builds an L2 client that sends the configured headers and enforces the configured request
+    /// timeout, concurrency and rate limits, and records the delays requested by the remote
+    /// endpoint.
+    fn build_endpoint(&self, url: Url) -> anyhow::Result<ForkEndpoint> {
+        let rpc = self.endpoint_rpc.get(&url).unwrap_or(&self.rpc);
+        let retry_after = RetryAfterHint::default();
+        let concurrency_limit = rpc
+            .max_concurrent_requests
+            .map_or(Semaphore::MAX_PERMITS, NonZeroUsize::get);
+        let http_client = HttpClientBuilder::default()
+            .request_timeout(rpc.request_timeout)
+            .set_headers(
+                build_headers(rpc).with_context(|| format!("invalid headers for fork={url}"))?,
+            )
+            .set_http_middleware(
+                tower::ServiceBuilder::new()
+                    .layer(ConcurrencyLimitLayer::new(concurrency_limit))
+                    .layer(RetryAfterLayer(retry_after.clone())),
+            )
+            .build(url.as_str())
+            .with_context(|| format!("failed to build HTTP client for fork={url}"))?;
+        let mut builder = ClientBuilder::new(http_client, SensitiveUrl::from(url.clone()));
+        if let Some(requests_per_second) = rpc.requests_per_second {
+            builder = builder.with_allowed_requests_per_second(requests_per_second);
         }
+        Ok(ForkEndpoint {
+            url,
+            client: Box::new(builder.build()),
+            retry_after,
+            health: Default::default(),
+        })
     }
 }
 
+/// Builds the headers sent with every request to an endpoint.
+fn build_headers(rpc: &ForkRpcConfig) -> anyhow::Result<http::HeaderMap> {
+    let mut headers = http::HeaderMap::new();
+    for (name, value) in &rpc.headers {
+        let name = http::HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("invalid header name `{name}`"))?;
+        let mut value = http::HeaderValue::from_str(value)
+            .with_context(|| format!("invalid value of header `{name}`"))?;
+        // Headers commonly carry API keys
+        value.set_sensitive(true);
+        headers.insert(name, value);
+    }
+    if let Some(auth) = &rpc.basic_auth {
+        let credentials = BASE64_STANDARD.encode(format!("{}:{}", auth.username, auth.password));
+        let mut value = http::HeaderValue::from_str(&format!("Basic {credentials}"))
+            .context("invalid basic authentication credentials")?;
+        value.set_sensitive(true);
+        headers.insert(http::header::AUTHORIZATION, value);
+    }
+    Ok(headers)
+}
+
+/// Number of consecutive failed requests after which an endpoint is taken out of rotation.
+const ENDPOINT_FAILOVER_THRESHOLD: u32 = 3;
+/// How long an endpoint stays out of rotation after it was failed over.
+const ENDPOINT_FAILOVER_COOLDOWN: Duration = Duration::from_secs(30);
+/// Maximum number of requests sent to the fork in a single JSON-RPC batch.
+const FORK_BATCH_SIZE: usize = 100;
+
+/// Single RPC endpoint of the forked network.
+#[derive(Debug)]
+struct ForkEndpoint {
+    url: Url,
+    client: Box<DynClient<L2>>,
+    retry_after: RetryAfterHint,
+    health: Mutex<EndpointHealth>,
+}
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    consecutive_failures: u32,
+    /// If set, the endpoint is not picked for requests until this moment.
+    failed_over_until: Option<Instant>,
+}
+
+impl ForkEndpoint {
+    fn health(&self) -> std::sync::MutexGuard<EndpointHealth> {
+        self.health
+            .lock()
+            .expect("endpoint health lock is poisoned")
+    }
+
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.health()
+            .failed_over_until
+            .is_none_or(|until| until <= now)
+    }
+
+    fn report_success(&self) {
+        *self.health() = EndpointHealth::default();
+    }
+
+    fn report_failure(&self) {
+        let mut health = self.health();
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= ENDPOINT_FAILOVER_THRESHOLD {
+            sh_warn!(
+                "Fork endpoint {} failed {} requests in a row, taking it out of rotation for {:?}",
+                self.url,
+                health.consecutive_failures,
+                ENDPOINT_FAILOVER_COOLDOWN
+            );
+            *health = EndpointHealth {
+                consecutive_failures: 0,
+                failed_over_until: Some(Instant::now() + ENDPOINT_FAILOVER_COOLDOWN),
+            };
+        }
+    }
+}
+
+/// Interchangeable endpoints serving the same forked network. Requests are spread across healthy
+/// endpoints in a round-robin fashion. The first endpoint is the primary one.
+#[derive(Debug, Clone)]
+struct ForkEndpoints {
+    endpoints: Arc<[ForkEndpoint]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl ForkEndpoints {
+    fn new(endpoints: Vec<ForkEndpoint>) -> Self {
+        assert!(!endpoints.is_empty(), "fork requires at least one endpoint");
+        Self {
+            endpoints: endpoints.into(),
+            next: Default::default(),
+        }
+    }
+
+    fn primary(&self) -> &ForkEndpoint {
+        &self.endpoints[0]
+    }
+
+    /// Picks the next healthy endpoint. If all endpoints were failed over, picks the one that is
+    /// closest to being put back into rotation.
+    fn pick(&self) -> &ForkEndpoint {
+        let now = Instant::now();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let len = self.endpoints.len();
+        (0..len)
+            .map(|offset| &self.endpoints[(start + offset) % len])
+            .find(|endpoint| endpoint.is_healthy(now))
+            .unwrap_or_else(|| {
+                self.endpoints
+                    .iter()
+                    .min_by_key(|endpoint| endpoint.health().failed_over_until)
+                    .expect("there is at least one endpoint")
+            })
+    }
+
+    /// Makes sure every secondary endpoint serves the same chain as the primary one and agrees on
+    /// the hash of the block we are forking from.
+    async fn check_consistency(
+        &self,
+        chain_id: L2ChainId,
+        block_number: L2BlockNumber,
+        block_hash: H256,
+    ) -> anyhow::Result<()> {
+        let primary_url = &self.primary().url;
+        for endpoint in self.endpoints.iter().skip(1) {
+            let url = &endpoint.url;
+            let other_chain_id = endpoint
+                .client
+                .chain_id()
+                .await
+                .with_context(|| format!("failed to get chain id from fork={url}"))?;
+            if other_chain_id.as_u64() != chain_id.as_u64() {
+                anyhow::bail!(
+                    "fork={url} reports chain id {} while fork={primary_url} reports {}",
+                    other_chain_id.as_u64(),
+                    chain_id.as_u64()
+                );
+            }
+            let other_block_hash = endpoint
+                .client
+                .get_block_details(block_number)
+                .await
+                .with_context(|| {
+                    format!("failed to get block #{block_number} details from fork={url}")
+                })?
+                .and_then(|details| details.base.root_hash);
+            if other_block_hash != Some(block_hash) {
+                anyhow::bail!(
+                    "fork={url} does not agree with fork={primary_url} on block #{block_number}: \
+                    expected hash {block_hash:?}, got {other_block_hash:?}"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Delay requested by the forked network through the `Retry-After` header of its most recent
+/// rejected response. Consumed by the next retry.
+#[derive(Debug, Clone, Default)]
+struct RetryAfterHint(Arc<Mutex<Option<Duration>>>);
+
+impl RetryAfterHint {
+    fn set(&self, delay: Duration) {
+        *self.0.lock().expect("Retry-After lock is poisoned") = Some(delay);
+    }
+
+    fn take(&self) -> Option<Duration> {
+        self.0.lock().expect("Retry-After lock is poisoned").take()
+    }
+}
+
+/// HTTP middleware that inspects responses for the `Retry-After` header.
+#[derive(Debug, Clone)]
+struct RetryAfterLayer(RetryAfterHint);
+
+impl<S> tower::Layer<S> for RetryAfterLayer {
+    type Service = RetryAfterService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RetryAfterService {
+            inner,
+            hint: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RetryAfterService<S> {
+    inner: S,
+    hint: RetryAfterHint,
+}
+
+impl<S, ReqBody, ResBody> tower::Service<http::Request<ReqBody>> for RetryAfterService<S>
+where
+    S: tower::Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    ResBody: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let hint = self.hint.clone();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            if let Some(delay) = parse_retry_after(&response) {
+                hint.set(delay);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Extracts the delay from the `Retry-After` header of a rate-limited or unavailable response.
+/// Both `delay-seconds` and `HTTP-date` forms are supported.
+fn parse_retry_after<B>(response: &http::Response<B>) -> Option<Duration> {
+    if !matches!(
+        response.status(),
+        http::StatusCode::TOO_MANY_REQUESTS | http::StatusCode::SERVICE_UNAVAILABLE
+    ) {
+        return None;
+    }
+    let value = response
+        .headers()
+        .get(http::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means the request can be retried right away
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Whether a failed request to the forked network is worth retrying: connection failures,
+/// timeouts, HTTP 408/429/5xx responses and JSON-RPC level rate limiting (including items of a
+/// batch request). Requests the endpoint rejected for good (e.g. HTTP 401/403) are not retried.
+fn is_retryable(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<ClientError>())
+        .any(|error| match error {
+            ClientError::Transport(error) => match error.downcast_ref::<HttpTransportError>() {
+                Some(HttpTransportError::Rejected { status_code }) => {
+                    matches!(*status_code, 408 | 429 | 500..=599)
+                }
+                Some(HttpTransportError::Http(_)) => true,
+                Some(_) => false,
+                // Not an HTTP transport, assume the connection failed
+                None => true,
+            },
+            ClientError::RequestTimeout => true,
+            ClientError::Call(error) => is_rate_limited(error.code()),
+            _ => false,
+        })
+}
+
+/// Whether a JSON-RPC error code signals that the endpoint is rate limiting requests.
+fn is_rate_limited(code: i32) -> bool {
+    matches!(code, 429 | -32005)
+}
+
+async fn latest_block_number(client: &DynClient<L2>, url: &Url) -> anyhow::Result<L2BlockNumber> {
+    let block_number = client
+        .get_block_number()
+        .await
+        .with_context(|| format!("failed to get block number from fork={url}"))?;
+    Ok(L2BlockNumber(block_number.as_u32()))
+}
+
+/// Binary searches the remote chain for the last block (up to `latest`) whose timestamp is at or
+/// before `timestamp`.
+async fn find_block_at_timestamp(
+    client: &DynClient<L2>,
+    latest: L2BlockNumber,
+    timestamp: u64,
+) -> anyhow::Result<L2BlockNumber> {
+    let block_timestamp = |block_number: u32| async move {
+        let details = client
+            .get_block_details(L2BlockNumber(block_number))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("could not find block #{block_number}"))?;
+        anyhow::Ok(details.base.timestamp)
+    };
+    if block_timestamp(0).await? > timestamp {
+        anyhow::bail!("timestamp {timestamp} precedes the genesis block");
+    }
+    // Block `low` is always at or before `timestamp`, blocks after `high` are always after it
+    let (mut low, mut high) = (0, latest.0);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if block_timestamp(mid).await? <= timestamp {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(L2BlockNumber(low))
+}
+
 /// Simple wrapper over `eth`/`zks`-capable client that propagates all [`ForkSource`] RPC requests to it.
 #[derive(Debug, Clone)]
 pub struct ForkClient {
     pub url: Url,
     pub details: ForkDetails,
-    l2_client: Box<DynClient<L2>>,
+    /// Remote endpoints of the forked network, `None` if the fork is served offline (from a
+    /// fixture or a local file).
+    endpoints: Option<ForkEndpoints>,
+    rpc: ForkRpcConfig,
+    endpoint_rpc: HashMap<Url, ForkRpcConfig>,
+    fixture: Option<ForkFixture>,
+    /// Local file serving all requests instead of the remote network (see [`ForkClient::from_file`]).
+    file: Option<FileForkSource>,
 }
 
 impl ForkClient {
     async fn new(
         config: ForkConfig,
-        l2_client: Box<DynClient<L2>>,
+        endpoints: ForkEndpoints,
         block_number: L2BlockNumber,
     ) -> anyhow::Result<Self> {
         let ForkConfig {
             url,
             estimate_gas_price_scale_factor,
             estimate_gas_scale_factor,
+            additional_urls: _,
+            rpc,
+            endpoint_rpc,
         } = config;
+        let l2_client = &endpoints.primary().client;
         let chain_id = l2_client
             .chain_id()
             .await
@@ -310,10 +796,17 @@ impl ForkClient {
             estimate_gas_scale_factor,
             fee_params,
         };
+        endpoints
+            .check_consistency(chain_id, block_number, root_hash)
+            .await?;
         let fork = ForkClient {
             url,
             details,
-            l2_client,
+            endpoints: Some(endpoints),
+            rpc,
+            endpoint_rpc,
+            fixture: None,
+            file: None,
         };
         Ok(fork)
     }
@@ -323,19 +816,48 @@ impl ForkClient {
         config: ForkConfig,
         block_number: Option<L2BlockNumber>,
     ) -> anyhow::Result<Self> {
-        let l2_client =
-            zksync_web3_decl::client::Client::http(SensitiveUrl::from(config.url.clone()))?.build();
-        let block_number = if let Some(block_number) = block_number {
-            block_number
-        } else {
-            let block_number = l2_client
-                .get_block_number()
-                .await
-                .with_context(|| format!("failed to get block number from fork={}", config.url))?;
-            L2BlockNumber(block_number.as_u32())
+        Self::at(config, block_number.into()).await
+    }
+
+    /// Initializes a fork based on config at the given point of the remote chain.
+    pub async fn at(config: ForkConfig, fork_at: ForkAt) -> anyhow::Result<Self> {
+        let endpoints = config.build_endpoints()?;
+        let l2_client = &endpoints.primary().client;
+        let block_number = match fork_at {
+            ForkAt::BlockNumber(block_number) => block_number,
+            ForkAt::Latest => latest_block_number(l2_client, &config.url).await?,
+            ForkAt::Timestamp(timestamp) => {
+                let latest = latest_block_number(l2_client, &config.url).await?;
+                find_block_at_timestamp(l2_client, latest, timestamp)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "failed to find block at timestamp {timestamp} at fork={}",
+                            config.url
+                        )
+                    })?
+            }
+            ForkAt::L1Batch(batch_number) => {
+                let (_, last_block) = l2_client
+                    .get_l2_block_range(batch_number)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "failed to get block range of L1 batch #{batch_number} from fork={}",
+                            config.url
+                        )
+                    })?
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "could not find L1 batch #{batch_number} at fork={}",
+                            config.url
+                        )
+                    })?;
+                L2BlockNumber(last_block.as_u32())
+            }
         };
 
-        Self::new(config, Box::new(l2_client), block_number).await
+        Self::new(config, endpoints, block_number).await
     }
 
     /// Initializes a fork based on config at a block BEFORE given transaction.
@@ -344,8 +866,8 @@ impl ForkClient {
         config: ForkConfig,
         tx_hash: H256,
     ) -> anyhow::Result<(Self, Vec<Transaction>)> {
-        let l2_client =
-            zksync_web3_decl::client::Client::http(SensitiveUrl::from(config.url.clone()))?.build();
+        let endpoints = config.build_endpoints()?;
+        let l2_client = &endpoints.primary().client;
         let tx_details = l2_client
             .get_transaction_by_hash(tx_hash)
             .await?
@@ -378,15 +900,75 @@ impl ForkClient {
 
         // We initialize fork from the parent of the block containing transaction.
         Ok((
-            Self::new(config, Box::new(l2_client), block_number - 1).await?,
+            Self::new(config, endpoints, block_number - 1).await?,
             earlier_txs,
         ))
     }
 }
 
 impl ForkClient {
+    /// Records the fork's startup state (including `earlier_txs`) along with every subsequent
+    /// request made to the fork into a fixture at `path`. The fixture can later be served with
+    /// [`ForkClient::from_fixture`].
+    pub fn with_recording(
+        mut self,
+        path: &Path,
+        earlier_txs: &[Transaction],
+    ) -> anyhow::Result<Self> {
+        let header = FixtureHeader {
+            url: self.url.clone(),
+            details: self.details.clone(),
+            earlier_txs: earlier_txs.to_vec(),
+        };
+        let recorder = FixtureRecorder::create(path, &header)?;
+        self.fixture = Some(ForkFixture::Record(Arc::new(recorder)));
+        Ok(self)
+    }
+
+    /// Initializes a fork purely from a fixture recorded with [`ForkClient::with_recording`]. The
+    /// forked network is never contacted; requests missing from the fixture fail.
+    pub fn from_fixture(path: &Path) -> anyhow::Result<(Self, Vec<Transaction>)> {
+        let replayer = FixtureReplayer::load(path)?;
+        let FixtureHeader {
+            url,
+            details,
+            earlier_txs,
+        } = replayer.header.clone();
+        let fork = ForkClient {
+            url,
+            details,
+            endpoints: None,
+            rpc: ForkRpcConfig::default(),
+            endpoint_rpc: HashMap::new(),
+            fixture: Some(ForkFixture::Replay(Arc::new(replayer))),
+            file: None,
+        };
+        Ok((fork, earlier_txs))
+    }
+
+    /// Initializes a fork from a local state dump (as produced by `anvil_dumpState`) or a state
+    /// snapshot file. Storage, historical blocks, fork details and fee parameters are all served
+    /// from the file; the remote network is never contacted.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let source = FileForkSource::load(path)?;
+        Ok(ForkClient {
+            url: source.fork_url().clone(),
+            details: source.fork_details().clone(),
+            endpoints: None,
+            rpc: ForkRpcConfig::default(),
+            endpoint_rpc: HashMap::new(),
+            fixture: None,
+            file: Some(source),
+        })
+    }
+
     pub async fn get_fee_params(&self) -> anyhow::Result<FeeParams> {
-        self.l2_client
+        let Some(endpoints) = &self.endpoints else {
+            return Ok(self.details.fee_params);
+        };
+        endpoints
+            .primary()
+            .client
             .get_fee_params()
             .await
             .with_context(|| format!("failed to get fee parameters from fork={}", self.url))
@@ -396,10 +978,11 @@ impl ForkClient {
 #[cfg(test)]
 impl ForkClient {
     pub fn mock(details: ForkDetails, storage: crate::deps::InMemoryStorage) -> Self {
-        use zksync_types::{u256_to_h256, AccountTreeId, StorageKey, H160};
+        use zksync_types::{u256_to_h256, AccountTreeId, H160};
 
         let storage = Arc::new(RwLock::new(storage));
         let storage_clone = storage.clone();
+        let storage_tree = storage.clone();
         let l2_client = Box::new(
             zksync_web3_decl::client::MockClient::builder(L2::default())
                 .method(
@@ -423,6 +1006,34 @@ impl ForkClient {
                         .get(&hash)
                         .cloned())
                 })
+                .method(
+                    "zks_getProof",
+                    move |address: Address, keys: Vec<H256>, _batch: L1BatchNumber| {
+                        let storage = storage_tree.read().unwrap();
+                        let storage_proof = keys
+                            .into_iter()
+                            .map(|key| {
+                                let value = storage
+                                    .state
+                                    .get(&StorageKey::new(AccountTreeId::new(address), key))
+                                    .copied();
+                                api::StorageProof {
+                                    key,
+                                    proof: Vec::new(),
+                                    value: value.unwrap_or_default(),
+                                    // Every slot present in storage was written to at some point;
+                                    // the order of writes is not tracked so all of them share
+                                    // the same index
+                                    index: value.map_or(0, |_| 1),
+                                }
+                            })
+                            .collect();
+                        Ok(Some(api::Proof {
+                            address,
+                            storage_proof,
+                        }))
+                    },
+                )
                 .method("zks_getBlockDetails", move |block_number: L2BlockNumber| {
                     Ok(Some(api::BlockDetails {
                         number: block_number,
@@ -453,10 +1064,20 @@ impl ForkClient {
                 })
                 .build(),
         );
+        let url = Url::parse("http://test-fork-in-memory-storage.local").unwrap();
         ForkClient {
-            url: Url::parse("http://test-fork-in-memory-storage.local").unwrap(),
+            url: url.clone(),
             details,
-            l2_client,
+            endpoints: Some(ForkEndpoints::new(vec![ForkEndpoint {
+                url,
+                client: l2_client,
+                retry_after: RetryAfterHint::default(),
+                health: Default::default(),
+            }])),
+            rpc: ForkRpcConfig::default(),
+            endpoint_rpc: HashMap::new(),
+            fixture: None,
+            file: None,
         }
     }
 }
@@ -497,6 +1118,32 @@ impl Fork {
         }
     }
 
+    /// Returns settings of the current fork's endpoints that override the fork-wide ones.
+    pub(super) fn endpoint_rpc(&self) -> HashMap<Url, ForkRpcConfig> {
+        self.read()
+            .client
+            .as_ref()
+            .map(|client| client.endpoint_rpc.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns all endpoints of the current fork apart from the primary one.
+    pub(super) fn additional_urls(&self) -> Vec<Url> {
+        self.read()
+            .client
+            .as_ref()
+            .and_then(|client| client.endpoints.as_ref())
+            .map(|endpoints| {
+                endpoints
+                    .endpoints
+                    .iter()
+                    .skip(1)
+                    .map(|endpoint| endpoint.url.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     fn read(&self) -> RwLockReadGuard<ForkState> {
         self.state.read().expect("Fork lock is poisoned")
     }
@@ -505,29 +1152,142 @@ impl Fork {
         self.state.write().expect("Fork lock is poisoned")
     }
 
-    async fn make_call<T, F: Future<Output = anyhow::Result<T>>>(
+    /// Returns the fixture attached to the fork client (if there is one).
+    fn fixture(&self) -> Option<ForkFixture> {
+        self.read()
+            .client
+            .as_ref()
+            .and_then(|client| client.fixture.clone())
+    }
+
+    /// Returns the local file the fork client is served from (if there is one).
+    fn file(&self) -> Option<FileForkSource> {
+        self.read()
+            .client
+            .as_ref()
+            .and_then(|client| client.file.clone())
+    }
+
+    /// Runs `call_body` against the fork client (if there is one), see [`Fork::call_with_retries`].
+    /// If the client has a fixture attached, the response to `method(params)` is either recorded
+    /// to or served from it.
+    async fn make_call<T, P, F>(
         &self,
         method: &str,
-        call_body: impl FnOnce(Box<DynClient<L2>>) -> F,
+        params: P,
+        call_body: impl Fn(Box<DynClient<L2>>) -> F,
+    ) -> Option<anyhow::Result<T>>
+    where
+        T: Serialize + DeserializeOwned,
+        P: Serialize,
+        F: Future<Output = anyhow::Result<T>>,
+    {
+        let fixture = self.fixture();
+        if let Some(ForkFixture::Replay(replayer)) = &fixture {
+            return Some(replayer.replay(method, &params));
+        }
+        let result = self.call_with_retries(method, call_body).await?;
+        if let (Ok(result), Some(ForkFixture::Record(recorder))) = (&result, &fixture) {
+            record_or_warn(recorder, method, &params, result);
+        }
+        Some(result)
+    }
+
+    /// Runs `call_body` against the fork client (if there is one). Transient failures are retried
+    /// with exponential backoff (or after the delay requested by the remote endpoint) until the
+    /// configured number of retries is exhausted, at which point
+    /// [`AnvilNodeError::ForkRequestFailed`] is returned.
+    async fn call_with_retries<T, F: Future<Output = anyhow::Result<T>>>(
+        &self,
+        method: &str,
+        call_body: impl Fn(Box<DynClient<L2>>) -> F,
     ) -> Option<anyhow::Result<T>> {
-        let (client, span) = if let Some(client) = self.read().client.as_ref() {
+        let (endpoints, rpc, span) = if let Some(client) = self.read().client.as_ref() {
+            let Some(endpoints) = client.endpoints.clone() else {
+                return Some(Err(anyhow::anyhow!(
+                    "fork={} is served offline, `{method}` cannot be sent to the remote network",
+                    client.url
+                )));
+            };
             let span = tracing::info_span!("fork_rpc_call", method, url = %client.url);
-            (client.l2_client.clone(), span)
+            (endpoints, client.rpc.clone(), span)
         } else {
             return None;
         };
         Some(
-            call_body(client)
-                .map_err(|error| {
-                    sh_err!("call failed: {}", error);
-                    error
-                })
-                .instrument(span)
-                .await,
+            async move {
+                let mut attempt = 0;
+                loop {
+                    let endpoint = endpoints.pick();
+                    let error = match call_body(endpoint.client.clone()).await {
+                        Ok(result) => {
+                            endpoint.report_success();
+                            return Ok(result);
+                        }
+                        Err(error) => error,
+                    };
+                    if !is_retryable(&error) {
+                        sh_err!("call failed: {}", error);
+                        return Err(error);
+                    }
+                    endpoint.report_failure();
+                    if attempt >= rpc.retries {
+                        sh_err!("call failed after {} attempt(s): {}", attempt + 1, error);
+                        return Err(anyhow::Error::new(AnvilNodeError::ForkRequestFailed {
+                            method: method.to_owned(),
+                            reason: format!("{error:#}"),
+                        }));
+                    }
+                    attempt += 1;
+                    let delay = endpoint
+                        .retry_after
+                        .take()
+                        .unwrap_or_else(|| rpc.backoff(attempt));
+                    sh_warn!(
+                        "call to {} failed, retrying in {:?} ({}/{}): {}",
+                        endpoint.url,
+                        delay,
+                        attempt,
+                        rpc.retries,
+                        error
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+            }
+            .instrument(span)
+            .await,
         )
     }
 }
 
+fn record_or_warn(
+    recorder: &FixtureRecorder,
+    method: &str,
+    params: &impl Serialize,
+    result: &impl Serialize,
+) {
+    if let Err(error) = recorder.record(method, params, result) {
+        tracing::warn!(method, "failed to record fork response: {error:#}");
+    }
+}
+
+/// Converts a JSON-RPC batch response into its results, failing if any of the batched requests
+/// failed.
+fn collect_batch_response<T: fmt::Debug + Clone>(
+    response: BatchResponse<'_, T>,
+) -> anyhow::Result<Vec<T>> {
+    response
+        .into_iter()
+        .map(|result| {
+            // Keep the JSON-RPC error around so that rate limited items are retried
+            result.map_err(|error| {
+                anyhow::Error::from(ClientError::Call(error.into_owned()))
+                    .context("batched request failed")
+            })
+        })
+        .collect()
+}
+
 #[async_trait]
 impl ForkSource for Fork {
     fn dyn_cloned(&self) -> Box<dyn ForkSource> {
@@ -551,14 +1311,21 @@ impl ForkSource for Fork {
         idx: U256,
         block: Option<api::BlockIdVariant>,
     ) -> anyhow::Result<H256> {
+        if let Some(file) = self.file() {
+            return file.get_storage_at(address, idx, block).await;
+        }
         // TODO: This is currently cached at the `ForkStorage` level but I am unsure if this is a
         //       good thing. Intuitively it feels like cache should be centralized in a single place.
-        self.make_call("get_storage_at", |client| async move {
-            client
-                .get_storage_at(address, idx, block)
-                .await
-                .with_context(|| format!("(address={address:?}, idx={idx:?})"))
-        })
+        self.make_call(
+            "get_storage_at",
+            (address, idx, block),
+            |client| async move {
+                client
+                    .get_storage_at(address, idx, block)
+                    .await
+                    .with_context(|| format!("(address={address:?}, idx={idx:?})"))
+            },
+        )
         .await
         .unwrap_or(Ok(H256::zero()))
     }
@@ -583,9 +1350,12 @@ impl ForkSource for Fork {
     }
 
     async fn get_bytecode_by_hash(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
+        if let Some(file) = self.file() {
+            return file.get_bytecode_by_hash(hash).await;
+        }
         // TODO: This is currently cached at the `ForkStorage` level but I am unsure if this is a
         //       good thing. Intuitively it feels like cache should be centralized in a single place.
-        self.make_call("get_bytecode_by_hash", |client| async move {
+        self.make_call("get_bytecode_by_hash", (hash,), |client| async move {
             client
                 .get_bytecode_by_hash(hash)
                 .await
@@ -595,17 +1365,188 @@ impl ForkSource for Fork {
         .unwrap_or(Ok(None))
     }
 
+    async fn get_storage_at_forked_batch(&self, keys: &[StorageKey]) -> anyhow::Result<Vec<H256>> {
+        if let Some(file) = self.file() {
+            return file.get_storage_at_forked_batch(keys).await;
+        }
+        let Some(block_number) = self
+            .read()
+            .client
+            .as_ref()
+            .map(|client| client.details.block_number)
+        else {
+            return Ok(vec![H256::zero(); keys.len()]);
+        };
+        let block =
+            api::BlockIdVariant::BlockNumber(api::BlockNumber::Number(block_number.0.into()));
+        let fixture = self.fixture();
+        let fixture = fixture.as_ref();
+        let chunks = keys.chunks(FORK_BATCH_SIZE).map(|chunk| async move {
+            // Fixtures store batched requests item by item (as `get_storage_at` would), so that
+            // replaying does not depend on how requests happened to be batched
+            let params = chunk
+                .iter()
+                .map(|key| (*key.address(), h256_to_u256(*key.key()), Some(block)))
+                .collect::<Vec<_>>();
+            if let Some(ForkFixture::Replay(replayer)) = fixture {
+                return params
+                    .iter()
+                    .map(|params| replayer.replay("get_storage_at", params))
+                    .collect();
+            }
+            let values = self
+                .call_with_retries("get_storage_at_batch", |client| async move {
+                    let mut batch = BatchRequestBuilder::new();
+                    for key in chunk {
+                        batch.insert(
+                            "eth_getStorageAt",
+                            rpc_params![key.address(), h256_to_u256(*key.key()), block],
+                        )?;
+                    }
+                    collect_batch_response(client.batch_request::<H256>(batch).await?)
+                        .with_context(|| format!("({} storage slots)", chunk.len()))
+                })
+                .await
+                .unwrap_or_else(|| Ok(vec![H256::zero(); chunk.len()]))?;
+            if let Some(ForkFixture::Record(recorder)) = fixture {
+                for (params, value) in params.iter().zip(&values) {
+                    record_or_warn(recorder, "get_storage_at", params, value);
+                }
+            }
+            Ok(values)
+        });
+        Ok(futures::future::try_join_all(chunks)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    async fn get_bytecodes_by_hash_batch(
+        &self,
+        hashes: &[H256],
+    ) -> anyhow::Result<Vec<Option<Vec<u8>>>> {
+        if let Some(file) = self.file() {
+            return file.get_bytecodes_by_hash_batch(hashes).await;
+        }
+        let fixture = self.fixture();
+        let fixture = fixture.as_ref();
+        let chunks = hashes.chunks(FORK_BATCH_SIZE).map(|chunk| async move {
+            if let Some(ForkFixture::Replay(replayer)) = fixture {
+                return chunk
+                    .iter()
+                    .map(|hash| replayer.replay("get_bytecode_by_hash", &(hash,)))
+                    .collect();
+            }
+            let bytecodes = self
+                .call_with_retries("get_bytecodes_by_hash_batch", |client| async move {
+                    let mut batch = BatchRequestBuilder::new();
+                    for hash in chunk {
+                        batch.insert("zks_getBytecodeByHash", rpc_params![hash])?;
+                    }
+                    collect_batch_response(client.batch_request::<Option<Vec<u8>>>(batch).await?)
+                        .with_context(|| format!("({} bytecodes)", chunk.len()))
+                })
+                .await
+                .unwrap_or_else(|| Ok(vec![None; chunk.len()]))?;
+            if let Some(ForkFixture::Record(recorder)) = fixture {
+                for (hash, bytecode) in chunk.iter().zip(&bytecodes) {
+                    record_or_warn(recorder, "get_bytecode_by_hash", &(hash,), bytecode);
+                }
+            }
+            Ok(bytecodes)
+        });
+        Ok(futures::future::try_join_all(chunks)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    async fn get_enumeration_indices_batch(
+        &self,
+        keys: &[StorageKey],
+    ) -> anyhow::Result<Option<Vec<u64>>> {
+        if let Some(file) = self.file() {
+            return file.get_enumeration_indices_batch(keys).await;
+        }
+        // The tree is only available for sealed batches, so the last batch preceding the fork
+        // block's batch is used. Slots first written (or zeroed) in earlier blocks of the fork
+        // block's batch are thus not detected.
+        let Some(batch_number) = self
+            .read()
+            .client
+            .as_ref()
+            .and_then(|client| client.details.batch_number.0.checked_sub(1))
+            .map(L1BatchNumber)
+        else {
+            return Ok(None);
+        };
+        let by_address = keys
+            .iter()
+            .enumerate()
+            .into_group_map_by(|(_, key)| *key.address());
+        let chunks = by_address.iter().flat_map(|(address, keys)| {
+            keys.chunks(FORK_BATCH_SIZE).map(move |chunk| async move {
+                let slots = chunk.iter().map(|(_, key)| *key.key()).collect::<Vec<_>>();
+                let params = (*address, slots.clone(), batch_number);
+                let proof = self
+                    .make_call("get_proof", params, |client| {
+                        let (address, slots) = (*address, slots.clone());
+                        async move {
+                            match client.get_proof(address, slots, batch_number).await {
+                                Ok(proof) => Ok(proof),
+                                // Endpoint does not serve the tree at all; any other failure may be
+                                // transient and is surfaced so that the lookup is retried later
+                                Err(ClientError::Call(error))
+                                    if error.code() == METHOD_NOT_FOUND_CODE =>
+                                {
+                                    tracing::debug!(%error, "fork did not return a proof");
+                                    Ok(None)
+                                }
+                                Err(error) => Err(anyhow::Error::from(error)
+                                    .context(format!("(address={address:?})"))),
+                            }
+                        }
+                    })
+                    .await
+                    .unwrap_or(Ok(None))?;
+                anyhow::Ok(proof.map(|proof| {
+                    chunk
+                        .iter()
+                        .map(|(idx, _)| *idx)
+                        .zip(proof.storage_proof.into_iter().map(|proof| proof.index))
+                        .collect::<Vec<_>>()
+                }))
+            })
+        });
+
+        let mut indices = vec![0; keys.len()];
+        for chunk in futures::future::try_join_all(chunks).await? {
+            let Some(chunk) = chunk else {
+                return Ok(None);
+            };
+            for (idx, index) in chunk {
+                indices[idx] = index;
+            }
+        }
+        Ok(Some(indices))
+    }
+
     async fn get_transaction_by_hash(
         &self,
         hash: H256,
     ) -> anyhow::Result<Option<api::Transaction>> {
+        if let Some(file) = self.file() {
+            return file.get_transaction_by_hash(hash).await;
+        }
         if let Some(tx) = self.read().cache.get_transaction(&hash).cloned() {
             tracing::debug!(?hash, "using cached transaction");
             return Ok(Some(tx));
         }
 
         let tx = self
-            .make_call("get_transaction_by_hash", |client| async move {
+            .make_call("get_transaction_by_hash", (hash,), |client| async move {
                 client
                     .get_transaction_by_hash(hash)
                     .await
@@ -626,10 +1567,13 @@ impl ForkSource for Fork {
         &self,
         hash: H256,
     ) -> anyhow::Result<Option<api::TransactionDetails>> {
+        if let Some(file) = self.file() {
+            return file.get_transaction_details(hash).await;
+        }
         // N.B. We don't cache these responses as they will change through the lifecycle of the transaction
         // and caching could be error-prone. In theory, we could cache responses once the txn status
         // is `final` or `failed` but currently this does not warrant the additional complexity.
-        self.make_call("get_transaction_details", |client| async move {
+        self.make_call("get_transaction_details", (hash,), |client| async move {
             client
                 .get_transaction_details(hash)
                 .await
@@ -639,10 +1583,66 @@ impl ForkSource for Fork {
         .unwrap_or(Ok(None))
     }
 
+    async fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<api::TransactionReceipt>> {
+        if let Some(file) = self.file() {
+            return file.get_transaction_receipt(hash).await;
+        }
+        self.make_call("get_transaction_receipt", (hash,), |client| async move {
+            client
+                .get_transaction_receipt(hash)
+                .await
+                .with_context(|| format!("(hash={hash:?})"))
+        })
+        .await
+        .unwrap_or(Ok(None))
+    }
+
+    async fn get_transaction_return_data(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<zksync_types::web3::Bytes>> {
+        if let Some(file) = self.file() {
+            return file.get_transaction_return_data(hash).await;
+        }
+        // Trace is requested as raw JSON since its shape differs between node implementations;
+        // only the top-level call's output is of interest here.
+        let trace = self
+            .make_call(
+                "get_transaction_return_data",
+                (hash,),
+                |client| async move {
+                    let config = serde_json::json!({
+                        "tracer": "callTracer",
+                        "tracerConfig": { "onlyTopCall": true }
+                    });
+                    client
+                        .request::<Option<serde_json::Value>, _>(
+                            "debug_traceTransaction",
+                            rpc_params![hash, config],
+                        )
+                        .await
+                        .with_context(|| format!("(hash={hash:?})"))
+                },
+            )
+            .await
+            .unwrap_or(Ok(None))?;
+        let Some(output) = trace.as_ref().and_then(|trace| trace.get("output")) else {
+            return Ok(None);
+        };
+        serde_json::from_value(output.clone())
+            .with_context(|| format!("malformed trace output of transaction {hash:?}"))
+    }
+
     async fn get_raw_block_transactions(
         &self,
         block_number: L2BlockNumber,
     ) -> anyhow::Result<Vec<zksync_types::Transaction>> {
+        if let Some(file) = self.file() {
+            return file.get_raw_block_transactions(block_number).await;
+        }
         if let Some(txs) = self
             .read()
             .cache
@@ -654,12 +1654,16 @@ impl ForkSource for Fork {
         }
 
         let txs = self
-            .make_call("get_raw_block_transactions", |client| async move {
-                client
-                    .get_raw_block_transactions(block_number)
-                    .await
-                    .with_context(|| format!("(block_number={block_number})"))
-            })
+            .make_call(
+                "get_raw_block_transactions",
+                (block_number,),
+                |client| async move {
+                    client
+                        .get_raw_block_transactions(block_number)
+                        .await
+                        .with_context(|| format!("(block_number={block_number})"))
+                },
+            )
             .await
             .unwrap_or(Err(Web3Error::NoBlock.into()))?;
 
@@ -673,13 +1677,16 @@ impl ForkSource for Fork {
         &self,
         hash: H256,
     ) -> anyhow::Result<Option<api::Block<api::TransactionVariant>>> {
+        if let Some(file) = self.file() {
+            return file.get_block_by_hash(hash).await;
+        }
         if let Some(block) = self.read().cache.get_block(&hash, true).cloned() {
             tracing::debug!(?hash, "using cached block");
             return Ok(Some(block));
         }
 
         let block = self
-            .make_call("get_block_by_hash", |client| async move {
+            .make_call("get_block_by_hash", (hash, true), |client| async move {
                 client
                     .get_block_by_hash(hash, true)
                     .await
@@ -700,6 +1707,9 @@ impl ForkSource for Fork {
         &self,
         block_number: api::BlockNumber,
     ) -> anyhow::Result<Option<api::Block<api::TransactionVariant>>> {
+        if let Some(file) = self.file() {
+            return file.get_block_by_number(block_number).await;
+        }
         match block_number {
             api::BlockNumber::Number(block_number) => {
                 {
@@ -716,14 +1726,18 @@ impl ForkSource for Fork {
                 }
 
                 let block = self
-                    .make_call("get_block_by_number", |client| async move {
-                        client
-                            .get_block_by_number(api::BlockNumber::Number(block_number), true)
-                            .await
-                            .with_context(|| {
-                                format!("(block_number={block_number}, full_transactions=true)")
-                            })
-                    })
+                    .make_call(
+                        "get_block_by_number",
+                        (api::BlockNumber::Number(block_number), true),
+                        |client| async move {
+                            client
+                                .get_block_by_number(api::BlockNumber::Number(block_number), true)
+                                .await
+                                .with_context(|| {
+                                    format!("(block_number={block_number}, full_transactions=true)")
+                                })
+                        },
+                    )
                     .await
                     .unwrap_or(Ok(None))?;
 
@@ -737,14 +1751,18 @@ impl ForkSource for Fork {
                 }
             }
             _ => self
-                .make_call("get_block_by_number", |client| async move {
-                    client
-                        .get_block_by_number(block_number, true)
-                        .await
-                        .with_context(|| {
-                            format!("(block_number={block_number}, full_transactions=true)")
-                        })
-                })
+                .make_call(
+                    "get_block_by_number",
+                    (block_number, true),
+                    |client| async move {
+                        client
+                            .get_block_by_number(block_number, true)
+                            .await
+                            .with_context(|| {
+                                format!("(block_number={block_number}, full_transactions=true)")
+                            })
+                    },
+                )
                 .await
                 .unwrap_or(Ok(None)),
         }
@@ -754,10 +1772,13 @@ impl ForkSource for Fork {
         &self,
         block_number: L2BlockNumber,
     ) -> anyhow::Result<Option<api::BlockDetails>> {
+        if let Some(file) = self.file() {
+            return file.get_block_details(block_number).await;
+        }
         // N.B. We don't cache these responses as they will change through the lifecycle of the block
         // and caching could be error-prone. In theory, we could cache responses once the block
         // is finalized but currently this does not warrant the additional complexity.
-        self.make_call("get_block_details", |client| async move {
+        self.make_call("get_block_details", (block_number,), |client| async move {
             client
                 .get_block_details(block_number)
                 .await
@@ -771,13 +1792,20 @@ impl ForkSource for Fork {
         &self,
         block_hash: H256,
     ) -> anyhow::Result<Option<U256>> {
+        if let Some(file) = self.file() {
+            return file.get_block_transaction_count_by_hash(block_hash).await;
+        }
         // TODO: Cache?
-        self.make_call("get_block_transaction_count_by_hash", |client| async move {
-            client
-                .get_block_transaction_count_by_hash(block_hash)
-                .await
-                .with_context(|| format!("(block_hash={block_hash:?})"))
-        })
+        self.make_call(
+            "get_block_transaction_count_by_hash",
+            (block_hash,),
+            |client| async move {
+                client
+                    .get_block_transaction_count_by_hash(block_hash)
+                    .await
+                    .with_context(|| format!("(block_hash={block_hash:?})"))
+            },
+        )
         .await
         .unwrap_or(Ok(None))
     }
@@ -786,9 +1814,15 @@ impl ForkSource for Fork {
         &self,
         block_number: api::BlockNumber,
     ) -> anyhow::Result<Option<U256>> {
+        if let Some(file) = self.file() {
+            return file
+                .get_block_transaction_count_by_number(block_number)
+                .await;
+        }
         // TODO: Cache?
         self.make_call(
             "get_block_transaction_count_by_number",
+            (block_number,),
             |client| async move {
                 client
                     .get_block_transaction_count_by_number(block_number)
@@ -805,9 +1839,15 @@ impl ForkSource for Fork {
         block_hash: H256,
         index: Index,
     ) -> anyhow::Result<Option<api::Transaction>> {
+        if let Some(file) = self.file() {
+            return file
+                .get_transaction_by_block_hash_and_index(block_hash, index)
+                .await;
+        }
         // TODO: Cache?
         self.make_call(
             "get_transaction_by_block_hash_and_index",
+            (block_hash, index),
             |client| async move {
                 client
                     .get_transaction_by_block_hash_and_index(block_hash, index)
@@ -824,9 +1864,15 @@ impl ForkSource for Fork {
         block_number: api::BlockNumber,
         index: Index,
     ) -> anyhow::Result<Option<api::Transaction>> {
+        if let Some(file) = self.file() {
+            return file
+                .get_transaction_by_block_number_and_index(block_number, index)
+                .await;
+        }
         // TODO: Cache?
         self.make_call(
             "get_transaction_by_block_number_and_index",
+            (block_number, index),
             |client| async move {
                 client
                     .get_transaction_by_block_number_and_index(block_number, index)
@@ -839,13 +1885,16 @@ impl ForkSource for Fork {
     }
 
     async fn get_bridge_contracts(&self) -> anyhow::Result<Option<api::BridgeAddresses>> {
+        if let Some(file) = self.file() {
+            return file.get_bridge_contracts().await;
+        }
         if let Some(bridge_contracts) = self.read().cache.get_bridge_addresses().cloned() {
             tracing::debug!("using cached bridge contracts");
             return Ok(Some(bridge_contracts));
         }
 
         let bridge_contracts = self
-            .make_call("get_bridge_contracts", |client| async move {
+            .make_call("get_bridge_contracts", (), |client| async move {
                 Ok(Some(client.get_bridge_contracts().await?))
             })
             .await
@@ -866,6 +1915,9 @@ impl ForkSource for Fork {
         from: u32,
         limit: u8,
     ) -> anyhow::Result<Option<Vec<zksync_web3_decl::types::Token>>> {
+        if let Some(file) = self.file() {
+            return file.get_confirmed_tokens(from, limit).await;
+        }
         if let Some(confirmed_tokens) = self.read().cache.get_confirmed_tokens(from, limit).cloned()
         {
             tracing::debug!(from, limit, "using cached confirmed tokens");
@@ -873,7 +1925,7 @@ impl ForkSource for Fork {
         }
 
         let confirmed_tokens = self
-            .make_call("get_block_details", |client| async move {
+            .make_call("get_confirmed_tokens", (from, limit), |client| async move {
                 Ok(Some(
                     client
                         .get_confirmed_tokens(from, limit)
@@ -924,10 +1976,15 @@ impl fmt::Display for SupportedProtocolVersions {
 mod test {
     use super::*;
     use crate::deps::InMemoryStorage;
+    use crate::testing::{ForkBlockConfig, MockServer};
+    use httptest::matchers::{json_decoded, request};
+    use httptest::responders::{json_encoded, status_code};
+    use httptest::{cycle, Expectation};
     use maplit::hashmap;
     use zksync_types::block::{pack_block_info, unpack_block_info};
     use zksync_types::fee_model::{BaseTokenConversionRatio, FeeModelConfigV2, FeeParamsV2};
-    use zksync_types::{h256_to_u256, u256_to_h256, AccountTreeId, StorageKey};
+    use zksync_types::{u256_to_h256, AccountTreeId};
+    use zksync_web3_decl::jsonrpsee::types::ErrorObjectOwned;
 
     impl Default for ForkDetails {
         fn default() -> Self {
@@ -1035,4 +2092,336 @@ mod test {
             .expect("missing bytecode");
         assert_eq!(input_bytecode, actual_bytecode);
     }
+
+    #[tokio::test]
+    async fn test_recorded_fixture_is_replayed_without_network() {
+        let key = StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(0x42)),
+            H256::from_low_u64_be(7),
+        );
+        let value = H256::repeat_byte(0x11);
+        let bytecode = vec![0x4];
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.jsonl");
+
+        let client = ForkClient::mock(
+            ForkDetails::default(),
+            InMemoryStorage {
+                state: hashmap! { key => value },
+                factory_deps: hashmap! { H256::repeat_byte(0x1) => bytecode.clone() },
+            },
+        )
+        .with_recording(&path, &[])
+        .unwrap();
+        let fork = Fork::new(Some(client), CacheConfig::None);
+        assert_eq!(
+            fork.get_storage_at_forked_batch(&[key]).await.unwrap(),
+            vec![value]
+        );
+        assert_eq!(
+            fork.get_bytecode_by_hash(H256::repeat_byte(0x1))
+                .await
+                .unwrap(),
+            Some(bytecode.clone())
+        );
+
+        let (client, earlier_txs) = ForkClient::from_fixture(&path).unwrap();
+        assert!(earlier_txs.is_empty());
+        let fork = Fork::new(Some(client), CacheConfig::None);
+        // Batched and single requests are served from the same recorded entries
+        assert_eq!(
+            fork.get_storage_at_forked(*key.address(), h256_to_u256(*key.key()))
+                .await
+                .unwrap(),
+            value
+        );
+        assert_eq!(
+            fork.get_bytecodes_by_hash_batch(&[H256::repeat_byte(0x1)])
+                .await
+                .unwrap(),
+            vec![Some(bytecode)]
+        );
+        let error = fork
+            .get_bytecode_by_hash(H256::repeat_byte(0x2))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("no recorded response"));
+    }
+
+    async fn fork_with_rpc(mock_server: &MockServer, rpc: ForkRpcConfig) -> Fork {
+        let client =
+            ForkClient::at_block_number(ForkConfig::unknown(mock_server.url()).with_rpc(rpc), None)
+                .await
+                .unwrap();
+        Fork::new(Some(client), CacheConfig::None)
+    }
+
+    #[tokio::test]
+    async fn test_retries_honour_retry_after() {
+        let mock_server = MockServer::run_with_config(ForkBlockConfig {
+            number: 10,
+            transaction_count: 0,
+            hash: H256::repeat_byte(0xab),
+        });
+        let last_id = Arc::new(Mutex::new(serde_json::Value::Null));
+        let last_id_clone = last_id.clone();
+        mock_server.inner.expect(
+            Expectation::matching(request::body(json_decoded(
+                move |request: &serde_json::Value| {
+                    let is_match = request["method"] == "zks_getBytecodeByHash";
+                    if is_match {
+                        *last_id.lock().unwrap() = request["id"].clone();
+                    }
+                    is_match
+                },
+            )))
+            .times(2)
+            .respond_with(cycle![
+                status_code(429).insert_header("Retry-After", "0"),
+                move || json_encoded(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": *last_id_clone.lock().unwrap(),
+                    "result": [1, 2, 3],
+                })),
+            ]),
+        );
+        // Backoff is long enough for the test to time out unless `Retry-After` is honoured
+        let fork = fork_with_rpc(
+            &mock_server,
+            ForkRpcConfig {
+                retries: 1,
+                retry_backoff: Duration::from_secs(3600),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let bytecode = fork
+            .get_bytecode_by_hash(H256::repeat_byte(0x1))
+            .await
+            .expect("request should succeed after a retry");
+        assert_eq!(bytecode, Some(vec![1, 2, 3]));
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_return_fork_request_failed() {
+        let mock_server = MockServer::run_with_config(ForkBlockConfig {
+            number: 10,
+            transaction_count: 0,
+            hash: H256::repeat_byte(0xab),
+        });
+        mock_server.inner.expect(
+            Expectation::matching(request::body(json_decoded(
+                |request: &serde_json::Value| request["method"] == "zks_getBytecodeByHash",
+            )))
+            .times(3)
+            .respond_with(status_code(503)),
+        );
+        let fork = fork_with_rpc(
+            &mock_server,
+            ForkRpcConfig {
+                retries: 2,
+                retry_backoff: Duration::from_millis(1),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let error = fork
+            .get_bytecode_by_hash(H256::repeat_byte(0x1))
+            .await
+            .expect_err("request should fail once retries are exhausted");
+        assert!(matches!(
+            error.downcast_ref::<AnvilNodeError>(),
+            Some(AnvilNodeError::ForkRequestFailed { method, .. }) if method == "get_bytecode_by_hash"
+        ));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let rejected = |status_code| {
+            anyhow::Error::from(ClientError::Transport(Box::new(
+                HttpTransportError::Rejected { status_code },
+            )))
+        };
+        assert!(is_retryable(&rejected(429)));
+        assert!(is_retryable(&rejected(503)));
+        assert!(!is_retryable(&rejected(401)));
+        assert!(!is_retryable(&rejected(403)));
+        assert!(is_retryable(&anyhow::Error::from(
+            ClientError::RequestTimeout
+        )));
+
+        // Rate limited item of a batch request
+        let error = ErrorObjectOwned::owned(-32005, "limit exceeded", None::<()>);
+        let error = anyhow::Error::from(ClientError::Call(error)).context("batched request failed");
+        assert!(is_retryable(&error));
+        let error = ErrorObjectOwned::owned(-32602, "invalid params", None::<()>);
+        assert!(!is_retryable(&anyhow::Error::from(ClientError::Call(
+            error
+        ))));
+    }
+
+    /// Starts a server that can act as an additional endpoint for a fork created from
+    /// [`MockServer::run_with_config`] with block #10 that has the given hash.
+    fn run_additional_endpoint(block_hash: H256) -> MockServer {
+        let mock_server = MockServer::run();
+        mock_server.expect("eth_chainId", None, serde_json::json!("0x104"));
+        mock_server.expect(
+            "zks_getBlockDetails",
+            Some(serde_json::json!([10])),
+            serde_json::json!({
+                "number": 10,
+                "l1BatchNumber": 1,
+                "timestamp": 1676461082u64,
+                "l1TxCount": 0,
+                "l2TxCount": 0,
+                "rootHash": format!("{:#x}", block_hash),
+                "status": "verified",
+                "l1GasPrice": 29860969933u64,
+                "l2FairGasPrice": 500000000u64,
+                "baseSystemContractsHashes": {
+                    "bootloader": "0x0100038581be3d0e201b3cc45d151ef5cc59eb3a0f146ad44f0f72abf00b594c",
+                    "default_aa": "0x0100038dc66b69be75ec31653c64cb931678299b9b659472772b2550b703f41c"
+                },
+                "operatorAddress": "0xfeee860e7aae671124e9a4e61139f3a5085dfeee",
+                "protocolVersion": ProtocolVersionId::Version26,
+            }),
+        );
+        mock_server
+    }
+
+    #[tokio::test]
+    async fn test_failing_endpoint_is_failed_over() {
+        let block_hash = H256::repeat_byte(0xab);
+        let primary = MockServer::run_with_config(ForkBlockConfig {
+            number: 10,
+            transaction_count: 0,
+            hash: block_hash,
+        });
+        primary.inner.expect(
+            Expectation::matching(request::body(json_decoded(
+                |request: &serde_json::Value| request["method"] == "zks_getBytecodeByHash",
+            )))
+            .respond_with(status_code(503)),
+        );
+        let additional = run_additional_endpoint(block_hash);
+        additional.expect(
+            "zks_getBytecodeByHash",
+            Some(serde_json::json!([H256::repeat_byte(0x1)])),
+            serde_json::json!([1, 2, 3]),
+        );
+
+        let client = ForkClient::at_block_number(
+            ForkConfig::unknown(primary.url())
+                .with_additional_urls([additional.url()])
+                .with_rpc(ForkRpcConfig {
+                    retries: 1,
+                    retry_backoff: Duration::from_millis(1),
+                    ..Default::default()
+                }),
+            None,
+        )
+        .await
+        .unwrap();
+        let fork = Fork::new(Some(client), CacheConfig::None);
+
+        let bytecode = fork
+            .get_bytecode_by_hash(H256::repeat_byte(0x1))
+            .await
+            .expect("request should be served by the additional endpoint");
+        assert_eq!(bytecode, Some(vec![1, 2, 3]));
+    }
+
+    #[tokio::test]
+    async fn test_inconsistent_endpoints_are_rejected() {
+        let primary = MockServer::run_with_config(ForkBlockConfig {
+            number: 10,
+            transaction_count: 0,
+            hash: H256::repeat_byte(0xab),
+        });
+        let additional = run_additional_endpoint(H256::repeat_byte(0xcd));
+
+        let error = ForkClient::at_block_number(
+            ForkConfig::unknown(primary.url()).with_additional_urls([additional.url()]),
+            None,
+        )
+        .await
+        .expect_err("fork should not be created from inconsistent endpoints");
+        assert!(error.to_string().contains("does not agree"));
+    }
+
+    #[test]
+    fn test_endpoint_headers_include_credentials() {
+        let rpc = ForkRpcConfig {
+            headers: [("x-api-key".to_owned(), "secret".to_owned())].into(),
+            basic_auth: Some(anvil_zksync_config::types::ForkBasicAuth {
+                username: "user".to_owned(),
+                password: "pass".to_owned(),
+            }),
+            ..Default::default()
+        };
+        let headers = build_headers(&rpc).unwrap();
+        assert_eq!(headers["x-api-key"], "secret");
+        assert_eq!(headers[http::header::AUTHORIZATION], "Basic dXNlcjpwYXNz");
+        assert!(headers["x-api-key"].is_sensitive());
+
+        let rpc = ForkRpcConfig {
+            headers: [("not a header".to_owned(), "value".to_owned())].into(),
+            ..Default::default()
+        };
+        assert!(build_headers(&rpc).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_block_at_timestamp() {
+        // Block #n is sealed at timestamp `1000 + 10 * n`
+        let client: Box<DynClient<L2>> = Box::new(
+            zksync_web3_decl::client::MockClient::builder(L2::default())
+                .method("zks_getBlockDetails", |block_number: L2BlockNumber| {
+                    Ok(Some(api::BlockDetails {
+                        number: block_number,
+                        l1_batch_number: L1BatchNumber(0),
+                        base: api::BlockDetailsBase {
+                            timestamp: 1000 + 10 * block_number.0 as u64,
+                            l1_tx_count: 0,
+                            l2_tx_count: 0,
+                            root_hash: None,
+                            status: api::BlockStatus::Sealed,
+                            commit_tx_hash: None,
+                            committed_at: None,
+                            commit_chain_id: None,
+                            prove_tx_hash: None,
+                            proven_at: None,
+                            prove_chain_id: None,
+                            execute_tx_hash: None,
+                            executed_at: None,
+                            execute_chain_id: None,
+                            l1_gas_price: 0,
+                            l2_fair_gas_price: 0,
+                            fair_pubdata_price: None,
+                            base_system_contracts_hashes: Default::default(),
+                        },
+                        operator_address: Address::zero(),
+                        protocol_version: None,
+                    }))
+                })
+                .build(),
+        );
+        let latest = L2BlockNumber(100);
+
+        for (timestamp, expected) in [(1000, 0), (1055, 5), (1060, 6), (1999, 99), (5000, 100)] {
+            assert_eq!(
+                find_block_at_timestamp(&client, latest, timestamp)
+                    .await
+                    .unwrap(),
+                L2BlockNumber(expected),
+                "timestamp {timestamp}"
+            );
+        }
+        let error = find_block_at_timestamp(&client, latest, 999)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("precedes the genesis block"));
+    }
 }
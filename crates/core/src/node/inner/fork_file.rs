@@ -0,0 +1,735 @@
+//! Forking from a local file instead of a remote network.
+//!
+//! Two kinds of files are supported: a [`VersionedState`] dump produced by another `anvil-zksync`
+//! instance (see `--dump-state` and `anvil_dumpState`) and a [`ForkStateSnapshot`], i.e. a set of
+//! storage slots and factory deps along with the metadata of the block they belong to.
+
+use super::fork::{ForkDetails, ForkSource};
+use super::SerializableForkStorage;
+use crate::node::fee_model::TestNodeFeeInputProvider;
+use crate::node::state::StateV1;
+use crate::node::{TransactionResult, VersionedState};
+use crate::utils::utc_datetime_from_epoch_ms;
+use anvil_zksync_common::sh_warn;
+use anvil_zksync_config::constants::{
+    DEFAULT_ESTIMATE_GAS_PRICE_SCALE_FACTOR, DEFAULT_ESTIMATE_GAS_SCALE_FACTOR,
+    DEFAULT_FAIR_PUBDATA_PRICE, DEFAULT_L1_GAS_PRICE, DEFAULT_L2_GAS_PRICE, TEST_NODE_NETWORK_ID,
+};
+use anvil_zksync_config::DEFAULT_PROTOCOL_VERSION;
+use anyhow::Context;
+use async_trait::async_trait;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+use url::Url;
+use zksync_types::fee_model::{BaseTokenConversionRatio, FeeModelConfigV2, FeeParams, FeeParamsV2};
+use zksync_types::web3::Index;
+use zksync_types::{
+    api, get_system_context_key, h256_to_u256, u256_to_h256, Address, L1BatchNumber, L2BlockNumber,
+    L2ChainId, ProtocolVersionId, StorageKey, H256, SYSTEM_CONTEXT_CHAIN_ID_POSITION, U256,
+};
+use zksync_web3_decl::error::Web3Error;
+
+/// Storage slots and factory deps of a chain at a given block, along with the block's metadata.
+/// Fee-related fields fall back to the node's defaults when absent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct ForkStateSnapshot {
+    pub chain_id: L2ChainId,
+    #[serde(default = "default_protocol_version")]
+    pub protocol_version: ProtocolVersionId,
+    pub batch_number: L1BatchNumber,
+    pub block_number: L2BlockNumber,
+    pub block_hash: H256,
+    pub block_timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_gas_price: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l2_fair_gas_price: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fair_pubdata_price: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_params: Option<FeeParams>,
+    /// Historical blocks that can be queried through the fork (may be empty).
+    #[serde(default)]
+    pub blocks: Vec<api::Block<api::TransactionVariant>>,
+    /// Storage slots and factory deps at `block_number`.
+    #[serde(flatten)]
+    pub state: SerializableForkStorage,
+}
+
+/// Files that do not record their protocol version were most likely produced by a node running the
+/// default one.
+fn default_protocol_version() -> ProtocolVersionId {
+    DEFAULT_PROTOCOL_VERSION
+}
+
+/// Fork source that serves state, historical blocks, [`ForkDetails`] and fee params from a local
+/// file. The remote network is never contacted.
+#[derive(Debug, Clone)]
+pub(super) struct FileForkSource {
+    inner: Arc<FileForkSourceInner>,
+}
+
+#[derive(Debug)]
+struct FileForkSourceInner {
+    url: Url,
+    details: ForkDetails,
+    storage: HashMap<StorageKey, H256>,
+    factory_deps: HashMap<H256, Vec<u8>>,
+    blocks: HashMap<H256, api::Block<api::TransactionVariant>>,
+    block_hashes: HashMap<u64, H256>,
+    transactions: HashMap<H256, api::Transaction>,
+    transaction_results: HashMap<H256, TransactionResult>,
+}
+
+impl FileForkSource {
+    /// Loads a (possibly gzipped) state dump or state snapshot from `path`.
+    pub(super) fn load(path: &Path) -> anyhow::Result<Self> {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("failed to resolve fork state file {}", path.display()))?;
+        let bytes = std::fs::read(&path)
+            .with_context(|| format!("failed to read fork state file {}", path.display()))?;
+        let mut decoder = GzDecoder::new(&bytes[..]);
+        let mut decoded = Vec::new();
+        // Support both compressed and non-compressed files
+        let decoded = if decoder.header().is_some() {
+            decoder
+                .read_to_end(&mut decoded)
+                .with_context(|| format!("failed to decompress {}", path.display()))?;
+            &decoded
+        } else {
+            &bytes
+        };
+        let url = Url::from_file_path(&path)
+            .map_err(|()| anyhow::anyhow!("invalid fork state file path {}", path.display()))?;
+
+        match serde_json::from_slice::<VersionedState>(decoded) {
+            Ok(VersionedState::V1 { state, .. }) => Self::from_state(url, state),
+            Ok(VersionedState::Unknown { version }) => {
+                anyhow::bail!(
+                    "fork state file {} has unsupported state version `{version}`",
+                    path.display()
+                )
+            }
+            Err(_) => {
+                let snapshot =
+                    serde_json::from_slice::<ForkStateSnapshot>(decoded).with_context(|| {
+                        format!(
+                            "fork state file {} is neither a state dump nor a state snapshot",
+                            path.display()
+                        )
+                    })?;
+                Ok(Self::from_snapshot(url, snapshot))
+            }
+        }
+    }
+
+    /// Forks from the last block of a state dumped by another `anvil-zksync` instance.
+    fn from_state(url: Url, state: StateV1) -> anyhow::Result<Self> {
+        let block = state
+            .blocks
+            .iter()
+            .max_by_key(|block| block.number)
+            .cloned()
+            .context("state dump does not contain any blocks")?;
+        let storage = state
+            .fork_storage
+            .storage
+            .0
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let chain_id = storage
+            .get(&get_system_context_key(SYSTEM_CONTEXT_CHAIN_ID_POSITION))
+            .map(|value| h256_to_u256(*value).as_u64())
+            .unwrap_or(TEST_NODE_NETWORK_ID as u64);
+        let chain_id = L2ChainId::try_from(chain_id)
+            .map_err(|e| anyhow::anyhow!("state dump has malformed chain id: {e}"))?;
+        let protocol_version = state.protocol_version.unwrap_or_else(|| {
+            sh_warn!(
+                "State dump does not record its protocol version, assuming {DEFAULT_PROTOCOL_VERSION}"
+            );
+            DEFAULT_PROTOCOL_VERSION
+        });
+        let details = file_fork_details(
+            chain_id,
+            protocol_version,
+            block,
+            DEFAULT_L1_GAS_PRICE,
+            DEFAULT_L2_GAS_PRICE,
+            DEFAULT_FAIR_PUBDATA_PRICE,
+            None,
+        );
+        let mut source =
+            FileForkSourceInner::new(url, details, storage, state.fork_storage.factory_deps);
+        for block in state.blocks {
+            source.insert_block(block);
+        }
+        source.transaction_results = state
+            .transactions
+            .into_iter()
+            .map(|tx| (tx.info.tx.hash(), tx))
+            .collect();
+        Ok(Self {
+            inner: Arc::new(source),
+        })
+    }
+
+    /// Forks from the block described by the snapshot.
+    fn from_snapshot(url: Url, snapshot: ForkStateSnapshot) -> Self {
+        let block = snapshot
+            .blocks
+            .iter()
+            .find(|block| block.hash == snapshot.block_hash)
+            .cloned()
+            .unwrap_or_else(|| api::Block {
+                hash: snapshot.block_hash,
+                number: snapshot.block_number.0.into(),
+                l1_batch_number: Some(snapshot.batch_number.0.into()),
+                timestamp: snapshot.block_timestamp.into(),
+                ..Default::default()
+            });
+        let details = file_fork_details(
+            snapshot.chain_id,
+            snapshot.protocol_version,
+            block.clone(),
+            snapshot.l1_gas_price.unwrap_or(DEFAULT_L1_GAS_PRICE),
+            snapshot.l2_fair_gas_price.unwrap_or(DEFAULT_L2_GAS_PRICE),
+            snapshot
+                .fair_pubdata_price
+                .unwrap_or(DEFAULT_FAIR_PUBDATA_PRICE),
+            snapshot.fee_params,
+        );
+        let mut source = FileForkSourceInner::new(
+            url,
+            details,
+            snapshot.state.storage.0.into_iter().collect(),
+            snapshot.state.factory_deps,
+        );
+        source.insert_block(block);
+        for block in snapshot.blocks {
+            source.insert_block(block);
+        }
+        Self {
+            inner: Arc::new(source),
+        }
+    }
+
+    pub(super) fn fork_url(&self) -> &Url {
+        &self.inner.url
+    }
+
+    pub(super) fn fork_details(&self) -> &ForkDetails {
+        &self.inner.details
+    }
+
+    fn block_by_number(&self, block_number: u64) -> Option<&api::Block<api::TransactionVariant>> {
+        self.inner
+            .block_hashes
+            .get(&block_number)
+            .and_then(|hash| self.inner.blocks.get(hash))
+    }
+
+    fn resolve_block_number(&self, block_number: api::BlockNumber) -> Option<u64> {
+        match block_number {
+            api::BlockNumber::Number(number) => Some(number.as_u64()),
+            api::BlockNumber::Earliest => Some(0),
+            api::BlockNumber::Pending => None,
+            // Nothing is ever sealed on top of the file's state, so every other tag resolves to
+            // the block the fork was created at
+            _ => Some(self.inner.details.block_number.0 as u64),
+        }
+    }
+
+    fn transaction_at(
+        block: &api::Block<api::TransactionVariant>,
+        index: Index,
+    ) -> Option<&api::TransactionVariant> {
+        block.transactions.get(index.as_usize())
+    }
+
+    fn full_transaction(&self, tx: &api::TransactionVariant) -> Option<api::Transaction> {
+        match tx {
+            api::TransactionVariant::Full(tx) => Some(tx.clone()),
+            api::TransactionVariant::Hash(hash) => self.inner.transactions.get(hash).cloned(),
+        }
+    }
+}
+
+impl FileForkSourceInner {
+    fn new(
+        url: Url,
+        details: ForkDetails,
+        storage: HashMap<StorageKey, H256>,
+        factory_deps: impl IntoIterator<Item = (H256, zksync_types::web3::Bytes)>,
+    ) -> Self {
+        Self {
+            url,
+            details,
+            storage,
+            factory_deps: factory_deps
+                .into_iter()
+                .map(|(hash, bytecode)| (hash, bytecode.0))
+                .collect(),
+            blocks: HashMap::new(),
+            block_hashes: HashMap::new(),
+            transactions: HashMap::new(),
+            transaction_results: HashMap::new(),
+        }
+    }
+
+    fn insert_block(&mut self, block: api::Block<api::TransactionVariant>) {
+        for tx in &block.transactions {
+            if let api::TransactionVariant::Full(tx) = tx {
+                self.transactions.insert(tx.hash, tx.clone());
+            }
+        }
+        self.block_hashes.insert(block.number.as_u64(), block.hash);
+        self.blocks.insert(block.hash, block);
+    }
+}
+
+fn file_fork_details(
+    chain_id: L2ChainId,
+    protocol_version: ProtocolVersionId,
+    block: api::Block<api::TransactionVariant>,
+    l1_gas_price: u64,
+    l2_fair_gas_price: u64,
+    fair_pubdata_price: u64,
+    fee_params: Option<FeeParams>,
+) -> ForkDetails {
+    let fee_params = fee_params.unwrap_or_else(|| {
+        FeeParams::V2(FeeParamsV2::new(
+            FeeModelConfigV2 {
+                minimal_l2_gas_price: l2_fair_gas_price,
+                ..TestNodeFeeInputProvider::default().get_fee_model_config()
+            },
+            l1_gas_price,
+            fair_pubdata_price,
+            BaseTokenConversionRatio::default(),
+        ))
+    });
+    ForkDetails {
+        chain_id,
+        protocol_version,
+        batch_number: L1BatchNumber(block.l1_batch_number.unwrap_or_default().as_u32()),
+        block_number: L2BlockNumber(block.number.as_u32()),
+        block_hash: block.hash,
+        block_timestamp: block.timestamp.as_u64(),
+        api_block: block,
+        l1_gas_price,
+        l2_fair_gas_price,
+        fair_pubdata_price,
+        estimate_gas_price_scale_factor: DEFAULT_ESTIMATE_GAS_PRICE_SCALE_FACTOR,
+        estimate_gas_scale_factor: DEFAULT_ESTIMATE_GAS_SCALE_FACTOR,
+        fee_params,
+    }
+}
+
+#[async_trait]
+impl ForkSource for FileForkSource {
+    fn dyn_cloned(&self) -> Box<dyn ForkSource> {
+        Box::new(self.clone())
+    }
+
+    fn url(&self) -> Option<Url> {
+        Some(self.inner.url.clone())
+    }
+
+    fn details(&self) -> Option<ForkDetails> {
+        Some(self.inner.details.clone())
+    }
+
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        idx: U256,
+        block: Option<api::BlockIdVariant>,
+    ) -> anyhow::Result<H256> {
+        // Only the state at the fork block is known
+        let details = &self.inner.details;
+        let is_fork_block = match &block {
+            None => true,
+            Some(api::BlockIdVariant::BlockNumber(number))
+            | Some(api::BlockIdVariant::BlockNumberObject(api::BlockNumberObject {
+                block_number: number,
+            })) => self.resolve_block_number(*number) == Some(details.block_number.0 as u64),
+            Some(api::BlockIdVariant::BlockHashObject(api::BlockHashObject { block_hash })) => {
+                *block_hash == details.block_hash
+            }
+        };
+        anyhow::ensure!(
+            is_fork_block,
+            "fork file only holds state at block #{}, storage at {block:?} is not available",
+            details.block_number
+        );
+        let key = StorageKey::new(address.into(), u256_to_h256(idx));
+        Ok(self.inner.storage.get(&key).copied().unwrap_or_default())
+    }
+
+    async fn get_storage_at_forked(&self, address: Address, idx: U256) -> anyhow::Result<H256> {
+        self.get_storage_at(address, idx, None).await
+    }
+
+    async fn get_bytecode_by_hash(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.inner.factory_deps.get(&hash).cloned())
+    }
+
+    async fn get_storage_at_forked_batch(&self, keys: &[StorageKey]) -> anyhow::Result<Vec<H256>> {
+        Ok(keys
+            .iter()
+            .map(|key| self.inner.storage.get(key).copied().unwrap_or_default())
+            .collect())
+    }
+
+    async fn get_bytecodes_by_hash_batch(
+        &self,
+        hashes: &[H256],
+    ) -> anyhow::Result<Vec<Option<Vec<u8>>>> {
+        Ok(hashes
+            .iter()
+            .map(|hash| self.inner.factory_deps.get(hash).cloned())
+            .collect())
+    }
+
+    async fn get_enumeration_indices_batch(
+        &self,
+        _keys: &[StorageKey],
+    ) -> anyhow::Result<Option<Vec<u64>>> {
+        // Neither state dumps nor snapshots hold the Merkle tree
+        Ok(None)
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<api::Transaction>> {
+        Ok(self.inner.transactions.get(&hash).cloned())
+    }
+
+    async fn get_transaction_details(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<api::TransactionDetails>> {
+        Ok(self.inner.transaction_results.get(&hash).map(
+            |TransactionResult { info, receipt, .. }| api::TransactionDetails {
+                is_l1_originated: false,
+                status: api::TransactionStatus::Included,
+                // if these are not set, fee is effectively 0
+                fee: receipt.effective_gas_price.unwrap_or_default()
+                    * receipt.gas_used.unwrap_or_default(),
+                gas_per_pubdata: info.tx.gas_per_pubdata_byte_limit(),
+                initiator_address: info.tx.initiator_account(),
+                received_at: utc_datetime_from_epoch_ms(info.tx.received_timestamp_ms),
+                eth_commit_tx_hash: None,
+                eth_prove_tx_hash: None,
+                eth_execute_tx_hash: None,
+            },
+        ))
+    }
+
+    async fn get_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<api::TransactionReceipt>> {
+        Ok(self
+            .inner
+            .transaction_results
+            .get(&hash)
+            .map(|result| result.receipt.clone()))
+    }
+
+    async fn get_transaction_return_data(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<zksync_types::web3::Bytes>> {
+        Ok(self
+            .inner
+            .transaction_results
+            .get(&hash)
+            .map(|result| result.debug.output.clone()))
+    }
+
+    async fn get_raw_block_transactions(
+        &self,
+        block_number: L2BlockNumber,
+    ) -> anyhow::Result<Vec<zksync_types::Transaction>> {
+        let block = self
+            .block_by_number(block_number.0 as u64)
+            .ok_or(Web3Error::NoBlock)?;
+        block
+            .transactions
+            .iter()
+            .map(|tx| {
+                let hash = match tx {
+                    api::TransactionVariant::Full(tx) => tx.hash,
+                    api::TransactionVariant::Hash(hash) => *hash,
+                };
+                self.inner
+                    .transaction_results
+                    .get(&hash)
+                    .map(|result| result.info.tx.clone())
+                    .with_context(|| {
+                        format!("fork state file has no raw transaction with hash={hash:?}")
+                    })
+            })
+            .collect()
+    }
+
+    async fn get_block_by_hash(
+        &self,
+        hash: H256,
+    ) -> anyhow::Result<Option<api::Block<api::TransactionVariant>>> {
+        Ok(self.inner.blocks.get(&hash).cloned())
+    }
+
+    async fn get_block_by_number(
+        &self,
+        block_number: api::BlockNumber,
+    ) -> anyhow::Result<Option<api::Block<api::TransactionVariant>>> {
+        Ok(self
+            .resolve_block_number(block_number)
+            .and_then(|number| self.block_by_number(number))
+            .cloned())
+    }
+
+    async fn get_block_details(
+        &self,
+        block_number: L2BlockNumber,
+    ) -> anyhow::Result<Option<api::BlockDetails>> {
+        let details = &self.inner.details;
+        Ok(self
+            .block_by_number(block_number.0 as u64)
+            .map(|block| api::BlockDetails {
+                number: L2BlockNumber(block.number.as_u32()),
+                l1_batch_number: L1BatchNumber(block.l1_batch_number.unwrap_or_default().as_u32()),
+                base: api::BlockDetailsBase {
+                    timestamp: block.timestamp.as_u64(),
+                    l1_tx_count: 0,
+                    l2_tx_count: block.transactions.len(),
+                    root_hash: Some(block.hash),
+                    status: api::BlockStatus::Verified,
+                    commit_tx_hash: None,
+                    commit_chain_id: None,
+                    committed_at: None,
+                    prove_tx_hash: None,
+                    prove_chain_id: None,
+                    proven_at: None,
+                    execute_tx_hash: None,
+                    execute_chain_id: None,
+                    executed_at: None,
+                    l1_gas_price: details.l1_gas_price,
+                    l2_fair_gas_price: details.l2_fair_gas_price,
+                    fair_pubdata_price: Some(details.fair_pubdata_price),
+                    base_system_contracts_hashes: Default::default(),
+                },
+                operator_address: Address::zero(),
+                protocol_version: Some(details.protocol_version),
+            }))
+    }
+
+    async fn get_block_transaction_count_by_hash(
+        &self,
+        block_hash: H256,
+    ) -> anyhow::Result<Option<U256>> {
+        Ok(self
+            .inner
+            .blocks
+            .get(&block_hash)
+            .map(|block| U256::from(block.transactions.len())))
+    }
+
+    async fn get_block_transaction_count_by_number(
+        &self,
+        block_number: api::BlockNumber,
+    ) -> anyhow::Result<Option<U256>> {
+        Ok(self
+            .resolve_block_number(block_number)
+            .and_then(|number| self.block_by_number(number))
+            .map(|block| U256::from(block.transactions.len())))
+    }
+
+    async fn get_transaction_by_block_hash_and_index(
+        &self,
+        block_hash: H256,
+        index: Index,
+    ) -> anyhow::Result<Option<api::Transaction>> {
+        Ok(self
+            .inner
+            .blocks
+            .get(&block_hash)
+            .and_then(|block| Self::transaction_at(block, index))
+            .and_then(|tx| self.full_transaction(tx)))
+    }
+
+    async fn get_transaction_by_block_number_and_index(
+        &self,
+        block_number: api::BlockNumber,
+        index: Index,
+    ) -> anyhow::Result<Option<api::Transaction>> {
+        Ok(self
+            .resolve_block_number(block_number)
+            .and_then(|number| self.block_by_number(number))
+            .and_then(|block| Self::transaction_at(block, index))
+            .and_then(|tx| self.full_transaction(tx)))
+    }
+
+    async fn get_bridge_contracts(&self) -> anyhow::Result<Option<api::BridgeAddresses>> {
+        Ok(None)
+    }
+
+    async fn get_confirmed_tokens(
+        &self,
+        _from: u32,
+        _limit: u8,
+    ) -> anyhow::Result<Option<Vec<zksync_web3_decl::types::Token>>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::inner::fork::{Fork, ForkClient};
+    use crate::node::inner::SerializableStorage;
+    use anvil_zksync_common::cache::CacheConfig;
+    use maplit::btreemap;
+    use std::collections::BTreeMap;
+    use zksync_types::web3::Bytes;
+    use zksync_types::AccountTreeId;
+
+    #[tokio::test]
+    async fn test_snapshot_is_served_from_file() {
+        let key = StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(0x42)),
+            H256::from_low_u64_be(7),
+        );
+        let snapshot = ForkStateSnapshot {
+            chain_id: L2ChainId::from(123),
+            protocol_version: ProtocolVersionId::latest(),
+            batch_number: L1BatchNumber(4),
+            block_number: L2BlockNumber(5),
+            block_hash: H256::repeat_byte(0x5),
+            block_timestamp: 1_000,
+            l1_gas_price: Some(42),
+            l2_fair_gas_price: None,
+            fair_pubdata_price: None,
+            fee_params: None,
+            blocks: Vec::new(),
+            state: SerializableForkStorage {
+                storage: SerializableStorage(btreemap! { key => H256::repeat_byte(0x11) }),
+                factory_deps: BTreeMap::from([(H256::repeat_byte(0x1), Bytes(vec![0x4]))]),
+            },
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.json");
+        std::fs::write(&path, serde_json::to_vec(&snapshot).unwrap()).unwrap();
+
+        let source = FileForkSource::load(&path).unwrap();
+        let details = source.details().unwrap();
+        assert_eq!(details.chain_id, L2ChainId::from(123));
+        assert_eq!(details.batch_number, L1BatchNumber(4));
+        assert_eq!(details.block_number, L2BlockNumber(5));
+        assert_eq!(details.block_hash, H256::repeat_byte(0x5));
+        assert_eq!(details.l1_gas_price, 42);
+        assert_eq!(details.l2_fair_gas_price, DEFAULT_L2_GAS_PRICE);
+        assert_eq!(
+            source
+                .get_storage_at_forked(*key.address(), h256_to_u256(*key.key()))
+                .await
+                .unwrap(),
+            H256::repeat_byte(0x11)
+        );
+        assert_eq!(
+            source
+                .get_bytecode_by_hash(H256::repeat_byte(0x1))
+                .await
+                .unwrap(),
+            Some(vec![0x4])
+        );
+        let block = source
+            .get_block_by_number(api::BlockNumber::Latest)
+            .await
+            .unwrap()
+            .expect("fork block must be known");
+        assert_eq!(block.hash, H256::repeat_byte(0x5));
+        assert!(source
+            .get_block_by_number(api::BlockNumber::Number(4.into()))
+            .await
+            .unwrap()
+            .is_none());
+
+        // Storage is only known at the fork block
+        let at_block = |number: u64| {
+            Some(api::BlockIdVariant::BlockNumber(api::BlockNumber::Number(
+                number.into(),
+            )))
+        };
+        assert_eq!(
+            source
+                .get_storage_at(*key.address(), h256_to_u256(*key.key()), at_block(5))
+                .await
+                .unwrap(),
+            H256::repeat_byte(0x11)
+        );
+        assert!(source
+            .get_storage_at(*key.address(), h256_to_u256(*key.key()), at_block(4))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fork_client_from_state_dump() {
+        let key = StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(0x42)),
+            H256::from_low_u64_be(7),
+        );
+        let state = VersionedState::v1(StateV1 {
+            blocks: vec![api::Block {
+                hash: H256::repeat_byte(0x7),
+                number: 7.into(),
+                l1_batch_number: Some(3.into()),
+                timestamp: 2_000.into(),
+                ..Default::default()
+            }],
+            transactions: Vec::new(),
+            fork_storage: SerializableForkStorage {
+                storage: SerializableStorage(btreemap! { key => H256::repeat_byte(0x11) }),
+                factory_deps: BTreeMap::new(),
+            },
+            historical_states: Vec::new(),
+            signer_accounts: None,
+            protocol_version: Some(ProtocolVersionId::Version25),
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        std::fs::write(&path, serde_json::to_vec(&state).unwrap()).unwrap();
+
+        let client = ForkClient::from_file(&path).unwrap();
+        assert_eq!(
+            client.details.protocol_version,
+            ProtocolVersionId::Version25
+        );
+        assert_eq!(client.details.block_number, L2BlockNumber(7));
+        assert_eq!(client.details.batch_number, L1BatchNumber(3));
+        // Fee params are served from the file as there is no remote endpoint
+        client.get_fee_params().await.unwrap();
+
+        let fork = Fork::new(Some(client), CacheConfig::None);
+        assert_eq!(
+            fork.get_storage_at_forked(*key.address(), h256_to_u256(*key.key()))
+                .await
+                .unwrap(),
+            H256::repeat_byte(0x11)
+        );
+        let block = fork
+            .get_block_by_number(api::BlockNumber::Latest)
+            .await
+            .unwrap()
+            .expect("fork block must be known");
+        assert_eq!(block.hash, H256::repeat_byte(0x7));
+    }
+}
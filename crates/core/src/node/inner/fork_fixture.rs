@@ -0,0 +1,205 @@
+//! Fixtures capturing the traffic between the node and the forked network.
+//!
+//! A fixture is a JSON Lines file. The first line holds everything that was fetched from the fork
+//! at startup ([`FixtureHeader`]) and every following line holds a single request along with its
+//! response ([`FixtureEntry`]). Recording a forked run produces a fixture that a later run can be
+//! served from without any network access, which makes forked tests hermetic.
+
+use super::fork::ForkDetails;
+use anyhow::Context;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use url::Url;
+use zksync_types::Transaction;
+
+/// Everything that was fetched from the fork at startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct FixtureHeader {
+    pub url: Url,
+    pub details: ForkDetails,
+    /// Transactions preceding the one the fork was created before (if any).
+    pub earlier_txs: Vec<Transaction>,
+}
+
+/// Single request made to the fork along with its response.
+#[derive(Debug, Serialize, Deserialize)]
+struct FixtureEntry {
+    method: String,
+    params: serde_json::Value,
+    result: serde_json::Value,
+}
+
+fn request_key(method: &str, params: &serde_json::Value) -> String {
+    format!("{method}{params}")
+}
+
+/// Fixture attached to a fork client.
+#[derive(Debug, Clone)]
+pub(super) enum ForkFixture {
+    /// Requests are sent to the fork and their responses are appended to the fixture.
+    Record(Arc<FixtureRecorder>),
+    /// Requests are served from the fixture, the fork is never contacted.
+    Replay(Arc<FixtureReplayer>),
+}
+
+#[derive(Debug)]
+pub(super) struct FixtureRecorder {
+    path: PathBuf,
+    state: Mutex<RecorderState>,
+}
+
+#[derive(Debug)]
+struct RecorderState {
+    file: File,
+    recorded: HashSet<String>,
+}
+
+impl FixtureRecorder {
+    /// Creates (or truncates) the fixture at `path` and writes `header` to it.
+    pub(super) fn create(path: &Path, header: &FixtureHeader) -> anyhow::Result<Self> {
+        let mut file = File::create(path)
+            .with_context(|| format!("failed to create fork fixture {}", path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(header)?)
+            .with_context(|| format!("failed to write fork fixture {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            state: Mutex::new(RecorderState {
+                file,
+                recorded: HashSet::new(),
+            }),
+        })
+    }
+
+    /// Appends the response to `method(params)` to the fixture unless it was recorded already.
+    pub(super) fn record(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+        result: &impl Serialize,
+    ) -> anyhow::Result<()> {
+        let params = serde_json::to_value(params)?;
+        let mut state = self.state.lock().expect("fixture lock is poisoned");
+        if !state.recorded.insert(request_key(method, &params)) {
+            return Ok(());
+        }
+        let entry = FixtureEntry {
+            method: method.to_owned(),
+            params,
+            result: serde_json::to_value(result)?,
+        };
+        // Entries are written one by one so that the fixture stays usable even if the node is
+        // killed mid-run.
+        writeln!(state.file, "{}", serde_json::to_string(&entry)?)
+            .with_context(|| format!("failed to write fork fixture {}", self.path.display()))
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct FixtureReplayer {
+    path: PathBuf,
+    pub header: FixtureHeader,
+    responses: HashMap<String, serde_json::Value>,
+}
+
+impl FixtureReplayer {
+    /// Loads a fixture previously written by [`FixtureRecorder`].
+    pub(super) fn load(path: &Path) -> anyhow::Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open fork fixture {}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+        let header = lines
+            .next()
+            .with_context(|| format!("fork fixture {} is empty", path.display()))??;
+        let header: FixtureHeader = serde_json::from_str(&header)
+            .with_context(|| format!("fork fixture {} has malformed header", path.display()))?;
+        let mut responses = HashMap::new();
+        for (idx, line) in lines.enumerate() {
+            let entry: FixtureEntry = serde_json::from_str(&line?).with_context(|| {
+                format!(
+                    "fork fixture {} has malformed entry on line {}",
+                    path.display(),
+                    idx + 2
+                )
+            })?;
+            responses.insert(request_key(&entry.method, &entry.params), entry.result);
+        }
+        tracing::debug!(
+            path = %path.display(),
+            responses = responses.len(),
+            "loaded fork fixture"
+        );
+        Ok(Self {
+            path: path.to_path_buf(),
+            header,
+            responses,
+        })
+    }
+
+    /// Returns the recorded response to `method(params)`. Fails if the request was never recorded.
+    pub(super) fn replay<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &impl Serialize,
+    ) -> anyhow::Result<T> {
+        let params = serde_json::to_value(params)?;
+        let Some(result) = self.responses.get(&request_key(method, &params)) else {
+            anyhow::bail!(
+                "fork fixture {} has no recorded response for {method}({params}); \
+                re-record it with `--fork-record` to include this request",
+                self.path.display()
+            );
+        };
+        serde_json::from_value(result.clone()).with_context(|| {
+            format!(
+                "fork fixture {} has malformed response for {method}({params})",
+                self.path.display()
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_types::{Address, H256, U256};
+
+    #[test]
+    fn test_recorded_responses_are_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.jsonl");
+        let header = FixtureHeader {
+            url: Url::parse("http://fixture.local").unwrap(),
+            details: ForkDetails::default(),
+            earlier_txs: Vec::new(),
+        };
+        let params = (Address::repeat_byte(0x11), U256::from(1), None::<()>);
+        let recorder = FixtureRecorder::create(&path, &header).unwrap();
+        recorder
+            .record("get_storage_at", &params, &H256::repeat_byte(0x22))
+            .unwrap();
+        // Duplicates are not written twice
+        recorder
+            .record("get_storage_at", &params, &H256::repeat_byte(0x22))
+            .unwrap();
+        drop(recorder);
+
+        let replayer = FixtureReplayer::load(&path).unwrap();
+        assert_eq!(replayer.header.url, header.url);
+        assert_eq!(replayer.responses.len(), 1);
+        let value: H256 = replayer.replay("get_storage_at", &params).unwrap();
+        assert_eq!(value, H256::repeat_byte(0x22));
+
+        let error = replayer
+            .replay::<H256>(
+                "get_storage_at",
+                &(Address::zero(), U256::zero(), None::<()>),
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("no recorded response"));
+    }
+}
@@ -7,15 +7,21 @@ use crate::deps::InMemoryStorage;
 use crate::node::inner::fork::{Fork, ForkSource};
 use crate::node::inner::storage::ReadStorageDyn;
 use crate::utils;
+use anvil_zksync_common::sh_warn;
 use anvil_zksync_config::constants::TEST_NODE_NETWORK_ID;
 use anvil_zksync_config::types::SystemContractsOptions;
 use async_trait::async_trait;
 use eyre::eyre;
+use indexmap::IndexSet;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
 use std::iter::FromIterator;
 use std::path::Path;
-use std::sync::{Arc, RwLock};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use zksync_error::anvil_zksync::node::AnvilNodeError;
 use zksync_multivm::interface::storage::ReadStorage;
 use zksync_types::bytecode::BytecodeHash;
 use zksync_types::web3::Bytes;
@@ -30,6 +36,10 @@ use zksync_types::{
 pub struct ForkStorage {
     pub inner: Arc<RwLock<ForkStorageInner>>,
     pub chain_id: L2ChainId,
+    // First error encountered while the VM was reading data from remote location through this
+    // handle (or its clones). `ReadStorage` cannot fail, so the error is kept here until the caller
+    // that drives the VM picks it up. See [`ForkStorage::with_error_scope`].
+    fork_error: Arc<Mutex<Option<AnvilNodeError>>>,
 }
 
 // TODO: Hide mutable state and mark everything with `pub(super)`
@@ -41,9 +51,59 @@ pub struct ForkStorageInner {
     pub(super) value_read_cache: HashMap<StorageKey, H256>,
     // Cache of factory deps that were read from remote location.
     pub(super) factory_dep_cache: HashMap<H256, Option<Vec<u8>>>,
+    // Cache of storage slots' enumeration indices that were read from remote Merkle tree.
+    pub(super) tree_cache: ForkTreeCache,
     // If set - it hold the necessary information on where to fetch the data.
     // If not set - it will simply read from underlying storage.
     fork: Fork,
+    // Statistics on data fetched from remote location since they were last taken.
+    fetch_stats: ForkFetchStats,
+}
+
+/// Statistics on data fetched from the forked network.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ForkFetchStats {
+    /// Number of batched prefetch rounds that were needed to discover the data.
+    pub prefetch_rounds: usize,
+    /// Number of storage slots fetched in batches ahead of execution.
+    pub prefetched_slots: usize,
+    /// Number of bytecodes fetched in batches ahead of execution.
+    pub prefetched_bytecodes: usize,
+    /// Number of storage slots fetched one by one during execution.
+    pub fetched_slots: usize,
+    /// Number of bytecodes fetched one by one during execution.
+    pub fetched_bytecodes: usize,
+    /// Number of storage slots looked up in the fork's Merkle tree to detect initial writes.
+    pub tree_lookups: usize,
+    /// Whether initial writes had to be estimated from slot values as the fork does not serve its
+    /// Merkle tree.
+    pub heuristic_initial_writes: bool,
+    /// Total time spent waiting for the forked network.
+    pub fetch_time: Duration,
+}
+
+/// Enumeration indices of storage slots in the fork's Merkle tree (`0` for slots that were never
+/// written to).
+#[derive(Debug, Default)]
+pub(super) struct ForkTreeCache {
+    indices: HashMap<StorageKey, u64>,
+    /// Set once the fork turned out not to serve its Merkle tree. From then on, initial writes and
+    /// enumeration indices are estimated from slot values.
+    unavailable: bool,
+}
+
+/// Storage slots and bytecodes that are not available locally and have to be fetched from the fork.
+#[derive(Debug, Default)]
+pub(crate) struct MissingForkData {
+    storage_keys: IndexSet<StorageKey>,
+    factory_deps: IndexSet<H256>,
+    tree_keys: IndexSet<StorageKey>,
+}
+
+impl MissingForkData {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.storage_keys.is_empty() && self.factory_deps.is_empty() && self.tree_keys.is_empty()
+    }
 }
 
 impl ForkStorage {
@@ -74,79 +134,176 @@ impl ForkStorage {
                 value_read_cache: Default::default(),
                 fork,
                 factory_dep_cache: Default::default(),
+                tree_cache: Default::default(),
+                fetch_stats: Default::default(),
             })),
             chain_id,
+            fork_error: Default::default(),
         }
     }
 
-    pub fn read_value_internal(&self, key: &StorageKey) -> eyre::Result<StorageValue> {
+    /// Reads the value from local storage or from the cache of data read from the fork.
+    fn read_value_local(&self, key: &StorageKey) -> Option<StorageValue> {
         let inner = self.inner.read().unwrap();
-        if let Some(local_value) = inner.raw_storage.read_value_opt(key) {
-            return Ok(local_value);
+        inner
+            .raw_storage
+            .read_value_opt(key)
+            .or_else(|| inner.value_read_cache.get(key).copied())
+    }
+
+    /// Loads the factory dep from local storage or from the cache of data read from the fork.
+    /// Returns `None` if the fork has not been asked about this hash yet.
+    fn load_factory_dep_local(&self, hash: H256) -> Option<Option<Vec<u8>>> {
+        let mut writer = self.inner.write().unwrap();
+        let local_storage = writer.raw_storage.load_factory_dep(hash);
+        if local_storage.is_some() {
+            return Some(local_storage);
         }
-        if let Some(cached_value) = inner.value_read_cache.get(key) {
-            return Ok(*cached_value);
+        writer.factory_dep_cache.get(&hash).cloned()
+    }
+
+    pub fn read_value_internal(&self, key: &StorageKey) -> eyre::Result<StorageValue> {
+        if let Some(value) = self.read_value_local(key) {
+            return Ok(value);
         }
-        let fork = inner.fork.clone();
-        drop(inner);
+        let fork = self.inner.read().unwrap().fork.clone();
         let address = *key.account().address();
         let idx = h256_to_u256(*key.key());
-        let value =
-            utils::block_on(async move { fork.get_storage_at_forked(address, idx).await }).unwrap();
+        let started_at = Instant::now();
+        let value = utils::block_on(async move { fork.get_storage_at_forked(address, idx).await })
+            .map_err(|error| fork_request_error("eth_getStorageAt", error))?;
 
         let mut writer = self.inner.write().unwrap();
         writer.value_read_cache.insert(*key, value);
+        writer.fetch_stats.fetched_slots += 1;
+        writer.fetch_stats.fetch_time += started_at.elapsed();
         Ok(value)
     }
 
     pub fn load_factory_dep_internal(&self, hash: H256) -> eyre::Result<Option<Vec<u8>>> {
-        let fork = {
-            let mut writer = self.inner.write().unwrap();
-            let local_storage = writer.raw_storage.load_factory_dep(hash);
-            if local_storage.is_some() {
-                return Ok(local_storage);
-            }
-            if let Some(value) = writer.factory_dep_cache.get(&hash) {
-                return Ok(value.clone());
-            }
-            writer.fork.clone()
-        };
-
-        let result = utils::block_on(async move { fork.get_bytecode_by_hash(hash).await }).unwrap();
+        if let Some(value) = self.load_factory_dep_local(hash) {
+            return Ok(value);
+        }
+        let fork = self.inner.read().unwrap().fork.clone();
+        let started_at = Instant::now();
+        let result = utils::block_on(async move { fork.get_bytecode_by_hash(hash).await })
+            .map_err(|error| fork_request_error("zks_getBytecodeByHash", error))?;
 
         let mut writer = self.inner.write().unwrap();
         writer.factory_dep_cache.insert(hash, result.clone());
+        writer.fetch_stats.fetched_bytecodes += 1;
+        writer.fetch_stats.fetch_time += started_at.elapsed();
         Ok(result)
     }
 
+    /// Returns the enumeration index of `key` in the fork's Merkle tree (`0` if the slot was never
+    /// written to on the fork). `None` means that the index has to be estimated, either because
+    /// there is no fork or because it does not serve its Merkle tree.
+    fn fork_enumeration_index(&self, key: &StorageKey) -> Option<u64> {
+        if let Some(index) = self.fork_enumeration_index_local(key) {
+            return index;
+        }
+        let fork = self.inner.read().unwrap().fork.clone();
+        let keys = [*key];
+        let started_at = Instant::now();
+        let indices =
+            utils::block_on(async move { fork.get_enumeration_indices_batch(&keys).await });
+
+        let mut writer = self.inner.write().unwrap();
+        writer.fetch_stats.fetch_time += started_at.elapsed();
+        writer.store_enumeration_indices(&keys, indices);
+        writer.tree_cache.indices.get(key).copied()
+    }
+
+    /// Same as [`ForkStorage::fork_enumeration_index`] but never reaches out to the fork. Returns
+    /// `None` if the fork has not been asked about this slot yet.
+    fn fork_enumeration_index_local(&self, key: &StorageKey) -> Option<Option<u64>> {
+        let mut writer = self.inner.write().unwrap();
+        if writer.fork.url().is_none() {
+            return Some(None);
+        }
+        if writer.tree_cache.unavailable {
+            writer.fetch_stats.heuristic_initial_writes = true;
+            return Some(None);
+        }
+        writer.tree_cache.indices.get(key).map(|index| Some(*index))
+    }
+
+    /// Fetches all `missing` storage slots, bytecodes and enumeration indices from the fork in
+    /// batched requests and caches them so that subsequent reads are served locally.
+    pub(crate) async fn prefetch(&self, missing: MissingForkData) -> anyhow::Result<()> {
+        let fork = self.inner.read().unwrap().fork.clone();
+        let keys = missing.storage_keys.into_iter().collect::<Vec<_>>();
+        let hashes = missing.factory_deps.into_iter().collect::<Vec<_>>();
+        let tree_keys = missing.tree_keys.into_iter().collect::<Vec<_>>();
+        let started_at = Instant::now();
+        let (values, bytecodes, indices) = futures::join!(
+            fork.get_storage_at_forked_batch(&keys),
+            fork.get_bytecodes_by_hash_batch(&hashes),
+            async {
+                if tree_keys.is_empty() {
+                    return Ok(Some(Vec::new()));
+                }
+                fork.get_enumeration_indices_batch(&tree_keys).await
+            }
+        );
+        let (values, bytecodes) = (values?, bytecodes?);
+
+        let mut writer = self.inner.write().unwrap();
+        writer.fetch_stats.prefetch_rounds += 1;
+        writer.fetch_stats.prefetched_slots += keys.len();
+        writer.fetch_stats.prefetched_bytecodes += hashes.len();
+        writer.fetch_stats.fetch_time += started_at.elapsed();
+        writer.value_read_cache.extend(keys.into_iter().zip(values));
+        writer
+            .factory_dep_cache
+            .extend(hashes.into_iter().zip(bytecodes));
+        writer.store_enumeration_indices(&tree_keys, indices);
+        Ok(())
+    }
+
+    /// Whether this storage is backed by a forked network.
+    pub(crate) fn is_forked(&self) -> bool {
+        self.inner.read().unwrap().fork.url().is_some()
+    }
+
+    /// Takes the statistics on data fetched from the forked network since the last call.
+    pub(crate) fn take_fetch_stats(&self) -> ForkFetchStats {
+        std::mem::take(&mut self.inner.write().unwrap().fetch_stats)
+    }
+
     /// Check if this is the first time when we're ever writing to this key.
     /// This has impact on amount of pubdata that we have to spend for the write.
     pub fn is_write_initial_internal(&self, key: &StorageKey) -> eyre::Result<bool> {
-        // Currently we don't have the zks API to return us the information on whether a given
-        // key was written to before a given block.
-        // This means, we have to depend on the following heuristic: we'll read the value of the slot.
-        //  - if value != 0 -> this means that the slot was written to in the past (so we can return intitial_write = false)
-        //  - but if the value = 0 - there is a chance, that slot was written to in the past - and later was reset.
-        //                            but unfortunately we cannot detect that with the current zks api, so we'll attempt to do it
-        //                           only on local storage.
+        // If value != 0, the slot was written to in the past (so it is not an initial write).
         let value = self.read_value_internal(key)?;
         if value != H256::zero() {
             return Ok(false);
         }
 
-        // If value was 0, there is still a chance, that the slot was written to in the past - and only now set to 0.
-        // We unfortunately don't have the API to check it on the fork, but we can at least try to check it on local storage.
-        let mut mutator = self
+        // If value was 0, there is still a chance that the slot was written to in the past and
+        // only later set to 0. Check local storage first...
+        let is_write_initial_locally = self
             .inner
             .write()
-            .map_err(|err| eyre!("failed acquiring write lock on fork storage: {:?}", err))?;
-        Ok(mutator.raw_storage.is_write_initial(key))
+            .map_err(|err| eyre!("failed acquiring write lock on fork storage: {:?}", err))?
+            .raw_storage
+            .is_write_initial(key);
+        if !is_write_initial_locally {
+            return Ok(false);
+        }
+        // ...and then the fork's Merkle tree, where every slot that was ever written to has an
+        // enumeration index. Without the tree, the slot is assumed to have never been written to.
+        Ok(self
+            .fork_enumeration_index(key)
+            .is_none_or(|index| index == 0))
     }
 
     /// Retrieves the enumeration index for a given `key`.
-    fn get_enumeration_index_internal(&self, _key: &StorageKey) -> Option<u64> {
-        // TODO: Update this file to use proper enumeration index value once it's exposed for forks via API
-        Some(0_u64)
+    fn get_enumeration_index_internal(&self, key: &StorageKey) -> Option<u64> {
+        // Slots that are yet to be written to for the first time (as well as all slots when the
+        // index is unknown) get `0`.
+        Some(self.fork_enumeration_index(key).unwrap_or(0))
     }
 
     /// Creates a serializable representation of current storage state. It will contain both locally
@@ -194,33 +351,36 @@ impl ForkStorage {
 
 impl ReadStorage for ForkStorage {
     fn is_write_initial(&mut self, key: &StorageKey) -> bool {
-        self.is_write_initial_internal(key).unwrap()
+        (&*self).is_write_initial(key)
     }
 
     fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
-        self.load_factory_dep_internal(hash).unwrap()
+        (&*self).load_factory_dep(hash)
     }
 
     fn read_value(&mut self, key: &StorageKey) -> zksync_types::StorageValue {
-        self.read_value_internal(key).unwrap()
+        (&*self).read_value(key)
     }
 
     fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
-        self.get_enumeration_index_internal(key)
+        (&*self).get_enumeration_index(key)
     }
 }
 
 impl ReadStorage for &ForkStorage {
     fn read_value(&mut self, key: &StorageKey) -> zksync_types::StorageValue {
-        self.read_value_internal(key).unwrap()
+        let result = self.read_value_internal(key);
+        self.unwrap_or_record(result)
     }
 
     fn is_write_initial(&mut self, key: &StorageKey) -> bool {
-        self.is_write_initial_internal(key).unwrap()
+        let result = self.is_write_initial_internal(key);
+        self.unwrap_or_record(result)
     }
 
     fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
-        self.load_factory_dep_internal(hash).unwrap()
+        let result = self.load_factory_dep_internal(hash);
+        self.unwrap_or_record(result)
     }
 
     fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
@@ -247,7 +407,80 @@ impl ReadStorageDyn for ForkStorage {
     }
 }
 
+impl ForkStorageInner {
+    /// Caches enumeration indices of `keys` fetched from the fork. If the fork does not serve its
+    /// Merkle tree, the heuristic is used from now on as the pubdata estimate is not worth failing
+    /// transactions over. Other failures only fall back to the heuristic for `keys` at hand; they
+    /// are looked up again the next time they are needed.
+    fn store_enumeration_indices(
+        &mut self,
+        keys: &[StorageKey],
+        indices: anyhow::Result<Option<Vec<u64>>>,
+    ) {
+        match indices {
+            Ok(Some(indices)) => {
+                self.fetch_stats.tree_lookups += keys.len();
+                self.tree_cache
+                    .indices
+                    .extend(keys.iter().copied().zip(indices));
+                return;
+            }
+            Ok(None) => {
+                if !self.tree_cache.unavailable {
+                    self.tree_cache.unavailable = true;
+                    sh_warn!(
+                        "Initial writes and enumeration indices of storage slots are estimated \
+                        from slot values from now on (forked network does not serve Merkle tree \
+                        proofs); pubdata, gas and fee estimates may differ from the network"
+                    );
+                }
+            }
+            Err(error) => {
+                tracing::warn!(
+                    "failed to fetch Merkle tree proofs for {} storage slot(s), estimating their \
+                    initial writes and enumeration indices from slot values: {error:#}",
+                    keys.len()
+                );
+            }
+        }
+        self.fetch_stats.heuristic_initial_writes = true;
+    }
+}
+
 impl ForkStorage {
+    /// Returns the value on success. Otherwise, remembers the error (unless there is one already)
+    /// and falls back to the default value so that the VM can keep going; the caller is expected
+    /// to check [`ForkStorage::take_fork_error`] once execution is over.
+    fn unwrap_or_record<T: Default>(&self, result: eyre::Result<T>) -> T {
+        result.unwrap_or_else(|report| {
+            let error = report
+                .downcast::<AnvilNodeError>()
+                .unwrap_or_else(|report| AnvilNodeError::GenericError {
+                    message: format!("{report:#}"),
+                });
+            tracing::error!(%error, "failed to read data from the forked network");
+            self.fork_error.lock().unwrap().get_or_insert(error);
+            T::default()
+        })
+    }
+
+    /// Takes the first error that was encountered while reading from the forked network through
+    /// [`ReadStorage`] on this handle since the last call.
+    pub(crate) fn take_fork_error(&self) -> Option<AnvilNodeError> {
+        self.fork_error.lock().unwrap().take()
+    }
+
+    /// Returns a handle to the same storage that keeps errors of reading from the forked network
+    /// to itself (and its clones). Every VM execution should read through its own handle so that
+    /// concurrent executions neither pick up nor drop each other's errors.
+    pub(crate) fn with_error_scope(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            chain_id: self.chain_id,
+            fork_error: Default::default(),
+        }
+    }
+
     pub fn set_value(&self, key: StorageKey, value: zksync_types::StorageValue) {
         let mut mutator = self.inner.write().unwrap();
         mutator.raw_storage.set_value(key, value)
@@ -266,6 +499,86 @@ impl ForkStorage {
     }
 }
 
+/// Read-only view over [`ForkStorage`] that never reaches out to the fork. Storage slots and
+/// bytecodes that are not available locally are recorded (and read as empty) so that they can be
+/// fetched in bulk before the transaction is executed for real.
+#[derive(Debug)]
+pub(crate) struct RecordingForkStorage {
+    storage: ForkStorage,
+    missing: Rc<RefCell<MissingForkData>>,
+}
+
+impl RecordingForkStorage {
+    pub(crate) fn new(storage: ForkStorage, missing: Rc<RefCell<MissingForkData>>) -> Self {
+        Self { storage, missing }
+    }
+}
+
+impl ReadStorage for RecordingForkStorage {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        self.storage.read_value_local(key).unwrap_or_else(|| {
+            self.missing.borrow_mut().storage_keys.insert(*key);
+            StorageValue::zero()
+        })
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        if self.read_value(key) != H256::zero() {
+            return false;
+        }
+        let is_write_initial_locally = self
+            .storage
+            .inner
+            .write()
+            .unwrap()
+            .raw_storage
+            .is_write_initial(key);
+        if !is_write_initial_locally {
+            return false;
+        }
+        self.fork_enumeration_index(key)
+            .is_none_or(|index| index == 0)
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        self.storage
+            .load_factory_dep_local(hash)
+            .unwrap_or_else(|| {
+                self.missing.borrow_mut().factory_deps.insert(hash);
+                None
+            })
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        Some(self.fork_enumeration_index(key).unwrap_or(0))
+    }
+}
+
+impl RecordingForkStorage {
+    /// Returns the enumeration index of `key` in the fork's Merkle tree if it is known locally,
+    /// otherwise records the slot to be looked up and treats it as never written to.
+    fn fork_enumeration_index(&self, key: &StorageKey) -> Option<u64> {
+        self.storage
+            .fork_enumeration_index_local(key)
+            .unwrap_or_else(|| {
+                self.missing.borrow_mut().tree_keys.insert(*key);
+                None
+            })
+    }
+}
+
+/// Turns a failed request to the forked network into [`AnvilNodeError::ForkRequestFailed`] unless
+/// it already is a domain error.
+fn fork_request_error(method: &str, error: anyhow::Error) -> eyre::Report {
+    let error = error.downcast::<AnvilNodeError>().unwrap_or_else(|error| {
+        AnvilNodeError::ForkRequestFailed {
+            method: method.to_owned(),
+            reason: format!("{error:#}"),
+        }
+    });
+    eyre::Report::new(error)
+}
+
 /// Serializable representation of [`ForkStorage`]'s state.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SerializableForkStorage {
@@ -396,15 +709,95 @@ mod tests {
 
         assert!(fork_storage.is_write_initial(&never_written_key));
         assert!(!fork_storage.is_write_initial(&key_with_some_value));
-        // This is the current limitation of the system. In theory, this should return false - as the value was written, but we don't have the API to the
-        // backend to get this information.
+        // Slot was written to on the fork and later reset to 0, which the fork's Merkle tree knows
+        assert!(!fork_storage.is_write_initial(&key_with_value_0));
+        assert_eq!(
+            fork_storage.get_enumeration_index(&key_with_value_0),
+            Some(1)
+        );
+        assert_eq!(
+            fork_storage.get_enumeration_index(&never_written_key),
+            Some(0)
+        );
+        assert!(!fork_storage.take_fetch_stats().heuristic_initial_writes);
+    }
+
+    #[test]
+    fn test_initial_writes_without_merkle_tree() {
+        let account = AccountTreeId::default();
+        let key_with_value_0 = StorageKey::new(account, H256::from_low_u64_be(3));
+        let mut in_memory_storage = InMemoryStorage::default();
+        in_memory_storage.set_value(key_with_value_0, H256::from_low_u64_be(0));
+
+        // Tree is only queried for batches sealed before the forked one, there are none here
+        let fork_details = ForkDetails {
+            chain_id: TEST_NODE_NETWORK_ID.into(),
+            batch_number: L1BatchNumber(0),
+            ..Default::default()
+        };
+        let client = ForkClient::mock(fork_details, in_memory_storage);
+        let fork = Fork::new(Some(client), CacheConfig::None);
+        let options = SystemContractsOptions::default();
+        let mut fork_storage: ForkStorage =
+            ForkStorage::new(fork, options, ProtocolVersionId::latest(), None, None);
+
+        // Falls back to the heuristic, which cannot tell that the slot was written to
         assert!(fork_storage.is_write_initial(&key_with_value_0));
+        assert!(fork_storage.take_fetch_stats().heuristic_initial_writes);
 
         // But writing any value there in the local storage (even 0) - should make it non-initial write immediately.
         fork_storage.set_value(key_with_value_0, H256::zero());
         assert!(!fork_storage.is_write_initial(&key_with_value_0));
     }
 
+    #[test]
+    fn test_merkle_tree_lookup_failures() {
+        let key = StorageKey::new(AccountTreeId::default(), H256::from_low_u64_be(1));
+        let fork_details = ForkDetails {
+            chain_id: TEST_NODE_NETWORK_ID.into(),
+            batch_number: L1BatchNumber(1),
+            ..Default::default()
+        };
+        let client = ForkClient::mock(fork_details, InMemoryStorage::default());
+        let fork = Fork::new(Some(client), CacheConfig::None);
+        let options = SystemContractsOptions::default();
+        let fork_storage: ForkStorage =
+            ForkStorage::new(fork, options, ProtocolVersionId::latest(), None, None);
+        let mut inner = fork_storage.inner.write().unwrap();
+
+        // Transient failures only fall back to the heuristic for the slots at hand
+        inner.store_enumeration_indices(&[key], Err(anyhow::anyhow!("request timed out")));
+        assert!(!inner.tree_cache.unavailable);
+        assert!(!inner.tree_cache.indices.contains_key(&key));
+        assert!(inner.fetch_stats.heuristic_initial_writes);
+
+        inner.store_enumeration_indices(&[key], Ok(Some(vec![7])));
+        assert_eq!(inner.tree_cache.indices.get(&key), Some(&7));
+
+        // Fork not serving its Merkle tree disables lookups for good
+        inner.store_enumeration_indices(&[key], Ok(None));
+        assert!(inner.tree_cache.unavailable);
+    }
+
+    #[test]
+    fn test_fork_errors_are_scoped() {
+        let fork = Fork::new(None, CacheConfig::None);
+        let options = SystemContractsOptions::default();
+        let fork_storage: ForkStorage =
+            ForkStorage::new(fork, options, ProtocolVersionId::latest(), None, None);
+        let scoped = fork_storage.with_error_scope();
+
+        let value: H256 = scoped
+            .clone()
+            .unwrap_or_record(Err(eyre::eyre!("request failed")));
+        assert_eq!(value, H256::zero());
+        // Clones share the scope, other scopes do not see the error
+        assert!(fork_storage.take_fork_error().is_none());
+        assert!(fork_storage.with_error_scope().take_fork_error().is_none());
+        assert!(scoped.take_fork_error().is_some());
+        assert!(scoped.take_fork_error().is_none());
+    }
+
     #[test]
     fn test_fork_storage_set_chain_id() {
         let fork_details = ForkDetails {
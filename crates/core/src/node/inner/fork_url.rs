@@ -0,0 +1,204 @@
+//! Networks that can be forked by name (builtin ones or the ones from the fork endpoints file) or
+//! by URL. Used both by the CLI and by `anvil_reset`, so that the same names are accepted by both.
+
+use crate::node::fork::ForkConfig;
+use anvil_zksync_config::types::{ForkEndpointsFile, ForkRpcConfig};
+use clap::ValueEnum;
+use std::str::FromStr;
+use url::Url;
+
+// Elastic Network ZK Chains
+#[derive(Debug, Clone, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum BuiltinNetwork {
+    #[value(alias = "mainnet")]
+    Era,
+    #[value(alias = "sepolia-testnet")]
+    EraTestnet,
+    Abstract,
+    AbstractTestnet,
+    #[value(alias = "sophon-mainnet")]
+    Sophon,
+    SophonTestnet,
+    Cronos,
+    CronosTestnet,
+    Lens,
+    LensTestnet,
+    Openzk,
+    OpenzkTestnet,
+    WonderchainTestnet,
+    Zkcandy,
+}
+
+/// ForkUrl is used to specify the URL of the forked network.
+#[derive(Debug, Clone)]
+pub enum ForkUrl {
+    Builtin(BuiltinNetwork),
+    Custom(Url),
+    /// Network defined in the fork endpoints file (see `--fork-endpoints`).
+    Named(String),
+}
+
+impl BuiltinNetwork {
+    /// Converts the BuiltinNetwork to a ForkConfig.
+    pub fn to_fork_config(&self) -> ForkConfig {
+        let (url, estimate_gas_price_scale_factor, estimate_gas_scale_factor) = match self {
+            BuiltinNetwork::Era => ("https://mainnet.era.zksync.io", 1.5, 1.3),
+            BuiltinNetwork::EraTestnet => ("https://sepolia.era.zksync.dev", 2.0, 1.3),
+            BuiltinNetwork::Abstract => ("https://api.mainnet.abs.xyz", 1.5, 1.3),
+            BuiltinNetwork::AbstractTestnet => ("https://api.testnet.abs.xyz", 1.5, 1.3),
+            BuiltinNetwork::Sophon => ("https://rpc.sophon.xyz", 1.5, 1.3),
+            BuiltinNetwork::SophonTestnet => ("https://rpc.testnet.sophon.xyz", 1.5, 1.3),
+            BuiltinNetwork::Cronos => ("https://mainnet.zkevm.cronos.org", 1.5, 1.3),
+            BuiltinNetwork::CronosTestnet => ("https://testnet.zkevm.cronos.org", 1.5, 1.3),
+            BuiltinNetwork::Lens => ("https://rpc.lens.xyz", 1.5, 1.3),
+            BuiltinNetwork::LensTestnet => ("https://rpc.testnet.lens.xyz", 1.5, 1.3),
+            BuiltinNetwork::Openzk => ("https://rpc.openzk.net", 1.5, 1.3),
+            BuiltinNetwork::OpenzkTestnet => {
+                ("https://openzk-testnet.rpc.caldera.xyz/http", 1.5, 1.3)
+            }
+            BuiltinNetwork::WonderchainTestnet => ("https://rpc.testnet.wonderchain.org", 1.5, 1.3),
+            BuiltinNetwork::Zkcandy => ("https://rpc.zkcandy.io", 1.5, 1.3),
+        };
+        ForkConfig::unknown(url.parse().unwrap())
+            .with_scale_factors(estimate_gas_price_scale_factor, estimate_gas_scale_factor)
+    }
+}
+
+impl ForkUrl {
+    /// Converts the ForkUrl to a ForkConfig, resolving networks against `endpoints` first.
+    /// Settings of a network found in `endpoints` are applied on top of `rpc`.
+    pub fn to_config(
+        &self,
+        endpoints: &ForkEndpointsFile,
+        rpc: &ForkRpcConfig,
+    ) -> anyhow::Result<ForkConfig> {
+        let (config, endpoint) = match self {
+            ForkUrl::Builtin(net) => {
+                // An entry named after the network (or one of its aliases) overrides its URL but
+                // keeps chain-specific settings
+                let endpoint = net.to_possible_value().and_then(|value| {
+                    value
+                        .get_name_and_aliases()
+                        .find_map(|name| endpoints.get(name))
+                });
+                let mut config = net.to_fork_config();
+                if let Some(endpoint) = endpoint {
+                    config.url = endpoint.url.clone();
+                }
+                (config, endpoint)
+            }
+            ForkUrl::Custom(url) => (ForkConfig::unknown(url.clone()), endpoints.find_by_url(url)),
+            ForkUrl::Named(name) => {
+                let endpoint = endpoints.get(name).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "`{name}` is neither a known network, a network from the fork endpoints \
+                        file nor a valid URL"
+                    )
+                })?;
+                (ForkConfig::unknown(endpoint.url.clone()), Some(endpoint))
+            }
+        };
+        let endpoint_rpc = endpoint.map(|endpoint| (config.url.clone(), endpoint.rpc_config(rpc)));
+        Ok(config.with_rpc(rpc.clone()).with_endpoint_rpc(endpoint_rpc))
+    }
+}
+
+/// Converts a list of fork URLs to a single ForkConfig. The first URL is the primary endpoint and
+/// determines chain-specific settings, the rest are used as additional endpoints.
+pub fn fork_urls_to_config(
+    fork_urls: &[ForkUrl],
+    endpoints: &ForkEndpointsFile,
+    rpc: &ForkRpcConfig,
+) -> anyhow::Result<ForkConfig> {
+    let (primary, additional) = fork_urls
+        .split_first()
+        .expect("clap requires at least one fork URL");
+    additional
+        .iter()
+        .try_fold(primary.to_config(endpoints, rpc)?, |config, fork_url| {
+            let additional = fork_url.to_config(endpoints, rpc)?;
+            Ok(config
+                .with_additional_urls([additional.url])
+                .with_endpoint_rpc(additional.endpoint_rpc))
+        })
+}
+
+impl FromStr for ForkUrl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(net) = BuiltinNetwork::from_str(s, true) {
+            return Ok(ForkUrl::Builtin(net));
+        }
+        match Url::parse(s) {
+            Ok(url) => Ok(ForkUrl::Custom(url)),
+            // Resolved against the fork endpoints file once it is loaded
+            Err(url::ParseError::RelativeUrlWithoutBase) => Ok(ForkUrl::Named(s.to_owned())),
+            Err(e) => Err(format!(
+                "`{}` is neither a known network nor a valid URL: {}",
+                s, e
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    #[test]
+    fn fork_url_resolves_against_endpoints_file() {
+        let endpoints: ForkEndpointsFile = serde_json::from_value(json!({
+            "networks": {
+                "internal": {
+                    "url": "https://internal.example.com/rpc",
+                    "headers": { "x-api-key": "secret" },
+                    "timeout": 5
+                },
+                "mainnet": {
+                    "url": "https://era.example.com/",
+                    "maxConcurrentRequests": 4
+                }
+            }
+        }))
+        .unwrap();
+        let rpc = ForkRpcConfig::default();
+        let parse = |s: &str| s.parse::<ForkUrl>().unwrap();
+
+        let config = parse("internal").to_config(&endpoints, &rpc).unwrap();
+        assert_eq!(config.url.as_str(), "https://internal.example.com/rpc");
+        let endpoint_rpc = &config.endpoint_rpc[&config.url];
+        assert_eq!(endpoint_rpc.headers["x-api-key"], "secret");
+        assert_eq!(endpoint_rpc.request_timeout, Duration::from_secs(5));
+
+        // Entries take precedence over builtins, both by name and by alias
+        let config = parse("era").to_config(&endpoints, &rpc).unwrap();
+        assert_eq!(config.url.as_str(), "https://era.example.com/");
+        assert_eq!(
+            config.endpoint_rpc[&config.url].max_concurrent_requests,
+            NonZeroUsize::new(4)
+        );
+        assert_eq!(config.estimate_gas_price_scale_factor, 1.5);
+
+        let config = parse("abstract").to_config(&endpoints, &rpc).unwrap();
+        assert_eq!(config.url.as_str(), "https://api.mainnet.abs.xyz/");
+        assert!(config.endpoint_rpc.is_empty());
+
+        assert!(parse("unknown").to_config(&endpoints, &rpc).is_err());
+        assert!(parse("internal")
+            .to_config(&ForkEndpointsFile::default(), &rpc)
+            .is_err());
+
+        let config =
+            fork_urls_to_config(&[parse("abstract"), parse("internal")], &endpoints, &rpc).unwrap();
+        assert_eq!(
+            config.additional_urls,
+            vec!["https://internal.example.com/rpc".parse::<Url>().unwrap()]
+        );
+        assert!(config.endpoint_rpc.contains_key(&config.additional_urls[0]));
+    }
+}
@@ -4,7 +4,8 @@ use crate::node::boojumos::BoojumOsVM;
 use crate::node::diagnostics::transaction::known_addresses_after_transaction;
 use crate::node::diagnostics::vm::traces::extract_addresses;
 use crate::node::error::{ToHaltError, ToRevertReason};
-use crate::node::inner::blockchain::Blockchain;
+use crate::node::gas_report::GasReporter;
+use crate::node::inner::blockchain::{Blockchain, TxIndexes};
 use crate::node::inner::fork::{Fork, ForkClient, ForkSource};
 use crate::node::inner::fork_storage::{ForkStorage, SerializableStorage};
 use crate::node::inner::storage::ReadStorageDyn;
@@ -12,6 +13,7 @@ use crate::node::inner::time::Time;
 use crate::node::inner::vm_runner::TxBatchExecutionResult;
 use crate::node::keys::StorageKeyLayout;
 use crate::node::state::StateV1;
+use crate::node::tokens::TokenRegistry;
 use crate::node::traces::decoder::CallTraceDecoderBuilder;
 use crate::node::vm::AnvilVM;
 use crate::node::{
@@ -20,6 +22,7 @@ use crate::node::{
 };
 use crate::system_contracts::SystemContracts;
 use crate::{delegate_vm, utils};
+use alloy::signers::local::PrivateKeySigner;
 use anvil_zksync_common::sh_println;
 use anvil_zksync_common::shell::get_shell;
 use anvil_zksync_config::constants::{
@@ -31,6 +34,7 @@ use anvil_zksync_traces::identifier::SignaturesIdentifier;
 use anvil_zksync_traces::{
     build_call_trace_arena, decode_trace_arena, filter_call_trace_arena, render_trace_arena_inner,
 };
+use anvil_zksync_types::api::GasReport;
 use indexmap::IndexMap;
 use once_cell::sync::OnceCell;
 use std::collections::{HashMap, HashSet};
@@ -99,6 +103,10 @@ pub struct InMemoryNodeInner {
     /// Keeps track of historical states indexed via block hash. Limited to [MAX_PREVIOUS_STATES].
     previous_states: IndexMap<H256, HashMap<StorageKey, StorageValue>>,
     storage_key_layout: StorageKeyLayout,
+    /// Session-wide gas usage aggregated by contract and function.
+    gas_reporter: GasReporter,
+    /// Tokens known to the local chain.
+    pub(crate) tokens: TokenRegistry,
 }
 
 impl InMemoryNodeInner {
@@ -115,6 +123,7 @@ impl InMemoryNodeInner {
         impersonation: ImpersonationManager,
         system_contracts: SystemContracts,
         storage_key_layout: StorageKeyLayout,
+        gas_reporter: GasReporter,
     ) -> Self {
         InMemoryNodeInner {
             blockchain,
@@ -129,9 +138,16 @@ impl InMemoryNodeInner {
             rich_accounts: HashSet::new(),
             previous_states: Default::default(),
             storage_key_layout,
+            gas_reporter,
+            tokens: Default::default(),
         }
     }
 
+    /// Returns session-wide gas report aggregated over all executed transactions.
+    pub fn gas_report(&self) -> GasReport {
+        self.gas_reporter.report()
+    }
+
     pub fn create_system_env(
         &self,
         base_system_contracts: BaseSystemContracts,
@@ -588,6 +604,8 @@ impl InMemoryNodeInner {
         let execution_mode = TxExecutionMode::EstimateFee;
         let (mut batch_env, _) = self.create_l1_batch_env().await;
         batch_env.fee_input = fee_input;
+        // Estimation may run concurrently with other executions, keep its fork errors separate
+        let fork_storage = self.fork_storage.with_error_scope();
 
         let initiator_address = tx.initiator_account();
         let impersonating = self.impersonation.is_impersonating(&initiator_address);
@@ -660,10 +678,10 @@ impl InMemoryNodeInner {
                     try_gas_limit,
                     batch_env.clone(),
                     system_env.clone(),
-                    &self.fork_storage,
+                    &fork_storage,
                     &self.system_contracts.boojum,
                     false,
-                )
+                )?
                 .tx_result;
 
             if estimate_gas_result.result.is_failed() {
@@ -695,10 +713,10 @@ impl InMemoryNodeInner {
                 suggested_gas_limit,
                 batch_env,
                 system_env,
-                &self.fork_storage,
+                &fork_storage,
                 &self.system_contracts.boojum,
                 false,
-            )
+            )?
             .tx_result;
 
         let overhead = derive_overhead(
@@ -784,7 +802,7 @@ impl InMemoryNodeInner {
         fork_storage: &ForkStorage,
         boojum: &BoojumConfig,
         trace_calls: bool,
-    ) -> BatchTransactionExecutionResult {
+    ) -> AnvilNodeResult<BatchTransactionExecutionResult> {
         // Set gas_limit for transaction
         let gas_limit_with_overhead = tx_gas_limit
             + derive_overhead(
@@ -874,15 +892,19 @@ impl InMemoryNodeInner {
                 vm.inspect(&mut tracer_dispatcher.into(), InspectExecutionMode::OneTx)
             }
         };
+        // A failed request to the forked network makes the estimate meaningless
+        if let Some(error) = fork_storage.take_fork_error() {
+            return Err(error);
+        }
         let call_traces = Arc::try_unwrap(call_tracer_result)
             .expect("failed extracting call traces")
             .take()
             .unwrap_or_default();
-        BatchTransactionExecutionResult {
+        Ok(BatchTransactionExecutionResult {
             tx_result: Box::new(tx_result),
             compression_result: Ok(()),
             call_traces,
-        }
+        })
     }
 
     async fn check_if_executable(
@@ -909,7 +931,7 @@ impl InMemoryNodeInner {
             &self.fork_storage,
             &self.system_contracts.boojum,
             true,
-        );
+        )?;
 
         let result: zksync_error::anvil_zksync::gas_estim::GasEstimationResult<()> =
             match tx_result.result {
@@ -1016,6 +1038,7 @@ impl InMemoryNodeInner {
         blockchain.current_block = snapshot.current_block;
         blockchain.current_block_hash = snapshot.current_block_hash;
         self.fee_input_provider = snapshot.fee_input_provider;
+        blockchain.tx_indexes = TxIndexes::new(snapshot.tx_results.values());
         blockchain.tx_results = snapshot.tx_results;
         blockchain.blocks = snapshot.blocks;
         blockchain.hashes = snapshot.hashes;
@@ -1050,11 +1073,22 @@ impl InMemoryNodeInner {
             Vec::new()
         };
 
+        // Private keys are only dumped on request as they are stored in plain text
+        let signer_accounts = self.config.dump_signer_accounts.then(|| {
+            self.config
+                .signer_accounts
+                .iter()
+                .map(|signer| H256(signer.to_bytes().0))
+                .collect()
+        });
+
         Ok(VersionedState::v1(StateV1 {
             blocks,
             transactions,
             fork_storage,
             historical_states,
+            signer_accounts,
+            protocol_version: Some(self.blockchain.protocol_version),
         }))
     }
 
@@ -1094,6 +1128,19 @@ impl InMemoryNodeInner {
                 .into_iter()
                 .map(|(k, v)| (k, v.0.into_iter().collect())),
         );
+        drop(storage);
+
+        // Managed accounts from the state are added to the current ones
+        for key in state.signer_accounts.unwrap_or_default() {
+            match PrivateKeySigner::from_bytes(&key.0.into()) {
+                Ok(signer) => {
+                    self.add_signer_account(signer);
+                }
+                Err(err) => {
+                    tracing::warn!(%err, "skipping invalid managed account from supplied state");
+                }
+            }
+        }
 
         Ok(true)
     }
@@ -1191,12 +1238,15 @@ impl InMemoryNodeInner {
         old_storage.raw_storage = std::mem::take(&mut new_storage.raw_storage);
         old_storage.value_read_cache = std::mem::take(&mut new_storage.value_read_cache);
         old_storage.factory_dep_cache = std::mem::take(&mut new_storage.factory_dep_cache);
+        old_storage.tree_cache = std::mem::take(&mut new_storage.tree_cache);
         self.fork_storage.chain_id = fork_storage.chain_id;
         drop(old_storage);
         drop(new_storage);
 
         self.rich_accounts.clear();
         self.previous_states.clear();
+        self.gas_reporter.clear();
+        self.tokens = Default::default();
 
         let rich_addresses = itertools::chain!(
             self.config
@@ -1239,6 +1289,37 @@ impl InMemoryNodeInner {
         self.rich_accounts.insert(address);
     }
 
+    /// Starts managing `signer`: the node can sign on its behalf and lists it in `eth_accounts`.
+    /// Returns `false` if the account was already managed.
+    pub fn add_signer_account(&mut self, signer: PrivateKeySigner) -> bool {
+        let address = H160::from_slice(signer.address().as_ref());
+        if self.is_signer_account(&address) {
+            return false;
+        }
+        self.config.signer_accounts.push(signer);
+        true
+    }
+
+    /// Stops managing the account and no longer lists it in `eth_accounts`. Returns `false` if the
+    /// account was not managed.
+    pub fn remove_signer_account(&mut self, address: &Address) -> bool {
+        if !self.is_signer_account(address) {
+            return false;
+        }
+        self.config
+            .signer_accounts
+            .retain(|signer| H160::from_slice(signer.address().as_ref()) != *address);
+        self.rich_accounts.remove(address);
+        true
+    }
+
+    pub fn is_signer_account(&self, address: &Address) -> bool {
+        self.config
+            .signer_accounts
+            .iter()
+            .any(|signer| H160::from_slice(signer.address().as_ref()) == *address)
+    }
+
     pub fn read_storage(&self) -> Box<dyn ReadStorage + '_> {
         Box::new(&self.fork_storage)
     }
@@ -1324,11 +1405,9 @@ pub mod testing {
         }
 
         pub async fn insert_tx_result(&mut self, hash: H256, tx_result: TransactionResult) {
-            self.blockchain
-                .write()
-                .await
-                .tx_results
-                .insert(hash, tx_result);
+            let mut blockchain = self.blockchain.write().await;
+            blockchain.tx_indexes.insert(&tx_result);
+            blockchain.tx_results.insert(hash, tx_result);
         }
 
         pub fn insert_previous_state(
@@ -1382,15 +1461,9 @@ mod tests {
             blockchain
                 .hashes
                 .insert(L2BlockNumber(1), H256::repeat_byte(0x1));
-            blockchain.tx_results.insert(
-                H256::repeat_byte(0x1),
-                TransactionResult {
-                    info: testing::default_tx_execution_info(),
-                    new_bytecodes: vec![],
-                    receipt: Default::default(),
-                    debug: testing::default_tx_debug_info(),
-                },
-            );
+            blockchain
+                .tx_results
+                .insert(H256::repeat_byte(0x1), testing::default_tx_result());
             blockchain.current_batch = L1BatchNumber(1);
             blockchain.current_block = L2BlockNumber(1);
             blockchain.current_block_hash = H256::repeat_byte(0x1);
@@ -1492,15 +1565,9 @@ mod tests {
             blockchain
                 .hashes
                 .insert(L2BlockNumber(1), H256::repeat_byte(0x1));
-            blockchain.tx_results.insert(
-                H256::repeat_byte(0x1),
-                TransactionResult {
-                    info: testing::default_tx_execution_info(),
-                    new_bytecodes: vec![],
-                    receipt: Default::default(),
-                    debug: testing::default_tx_debug_info(),
-                },
-            );
+            blockchain
+                .tx_results
+                .insert(H256::repeat_byte(0x1), testing::default_tx_result());
             blockchain.current_batch = L1BatchNumber(1);
             blockchain.current_block = L2BlockNumber(1);
             blockchain.current_block_hash = H256::repeat_byte(0x1);
@@ -1555,15 +1622,9 @@ mod tests {
             blockchain
                 .hashes
                 .insert(L2BlockNumber(2), H256::repeat_byte(0x2));
-            blockchain.tx_results.insert(
-                H256::repeat_byte(0x2),
-                TransactionResult {
-                    info: testing::default_tx_execution_info(),
-                    new_bytecodes: vec![],
-                    receipt: Default::default(),
-                    debug: testing::default_tx_debug_info(),
-                },
-            );
+            blockchain
+                .tx_results
+                .insert(H256::repeat_byte(0x2), testing::default_tx_result());
             blockchain.current_batch = L1BatchNumber(2);
             blockchain.current_block = L2BlockNumber(2);
             blockchain.current_block_hash = H256::repeat_byte(0x2);
@@ -11,18 +11,25 @@
 //!        are available outside of this module)
 pub mod blockchain;
 pub mod fork;
+mod fork_file;
+mod fork_fixture;
 mod fork_storage;
+pub mod fork_url;
 mod in_memory_inner;
 pub mod node_executor;
+mod simulator;
 pub mod storage;
 pub mod time;
 mod vm_runner;
 
+pub(crate) use fork_storage::{ForkFetchStats, ForkStorage};
 pub use fork_storage::{SerializableForkStorage, SerializableStorage};
 pub use in_memory_inner::InMemoryNodeInner;
+pub(crate) use simulator::simulate_blocks;
 
 use crate::filters::EthFilters;
 use crate::node::blockchain::Blockchain;
+use crate::node::gas_report::GasReporter;
 use crate::node::inner::storage::ReadStorageDyn;
 use crate::node::inner::vm_runner::VmRunner;
 use crate::node::keys::StorageKeyLayout;
@@ -32,7 +39,6 @@ use anvil_zksync_config::constants::NON_FORK_FIRST_BLOCK_TIMESTAMP;
 use anvil_zksync_config::TestNodeConfig;
 use blockchain::ReadBlockchain;
 use fork::{Fork, ForkClient, ForkSource};
-use fork_storage::ForkStorage;
 use std::sync::Arc;
 use time::{ReadTime, Time};
 use tokio::sync::RwLock;
@@ -82,6 +88,7 @@ impl InMemoryNodeInner {
             config.chain_id,
             config.system_contracts_path.as_deref(),
         );
+        let gas_reporter = GasReporter::default();
         let vm_runner = VmRunner::new(
             time.clone(),
             fork_storage.clone(),
@@ -89,6 +96,8 @@ impl InMemoryNodeInner {
             generate_system_logs,
             config.is_bytecode_compression_enforced(),
             storage_key_layout,
+            gas_reporter.clone(),
+            config.struct_logs.then_some(config.struct_logs_limit),
         );
 
         let node_inner = InMemoryNodeInner::new(
@@ -102,6 +111,7 @@ impl InMemoryNodeInner {
             impersonation.clone(),
             system_contracts.clone(),
             storage_key_layout,
+            gas_reporter,
         );
 
         (
@@ -1,5 +1,5 @@
 use super::InMemoryNodeInner;
-use crate::node::fork::ForkConfig;
+use crate::node::fork::{ForkAt, ForkConfig};
 use crate::node::inner::fork::{ForkClient, ForkSource};
 use crate::node::inner::vm_runner::VmRunner;
 use crate::node::keys::StorageKeyLayout;
@@ -59,8 +59,8 @@ impl NodeExecutor {
                 Command::SetNonce(address, nonce, reply) => {
                     self.set_nonce(address, nonce, reply).await;
                 }
-                Command::ResetFork(url, block_number, reply) => {
-                    self.reset_fork(url, block_number, reply).await;
+                Command::ResetFork(fork_config, fork_at, reply) => {
+                    self.reset_fork(fork_config, fork_at, reply).await;
                 }
                 Command::ResetForkBlockNumber(block_number, reply) => {
                     self.reset_fork_block_number(block_number, reply).await;
@@ -254,14 +254,12 @@ impl NodeExecutor {
 
     async fn reset_fork(
         &mut self,
-        url: Url,
-        block_number: Option<L2BlockNumber>,
+        fork_config: ForkConfig,
+        fork_at: ForkAt,
         reply: oneshot::Sender<AnvilNodeResult<()>>,
     ) {
         let result = async {
-            // We don't know what chain this is so we assume default scale configuration.
-            let fork_client =
-                ForkClient::at_block_number(ForkConfig::unknown(url), block_number).await?;
+            let fork_client = ForkClient::at(fork_config, fork_at).await?;
             self.node_inner.write().await.reset(Some(fork_client)).await;
 
             Ok(())
@@ -302,6 +300,9 @@ impl NodeExecutor {
                     url,
                     estimate_gas_price_scale_factor: details.estimate_gas_price_scale_factor,
                     estimate_gas_scale_factor: details.estimate_gas_scale_factor,
+                    additional_urls: node_inner.fork.additional_urls(),
+                    rpc: node_inner.config.fork_rpc.clone(),
+                    endpoint_rpc: node_inner.fork.endpoint_rpc(),
                 },
                 Some(block_number),
             )
@@ -504,15 +505,15 @@ impl NodeExecutorHandle {
         .await
     }
 
-    /// Request [`NodeExecutor`] to reset fork to given url and block number. All local state will
-    /// be wiped. Waits for the change to take place.
+    /// Request [`NodeExecutor`] to reset fork to the network described by `fork_config` at the
+    /// given point of its chain. All local state will be wiped. Waits for the change to take place.
     pub async fn reset_fork_sync(
         &self,
-        url: Url,
-        block_number: Option<L2BlockNumber>,
+        fork_config: ForkConfig,
+        fork_at: ForkAt,
     ) -> AnvilNodeResult<()> {
         execute_with_response(&self.command_sender, move |response_sender| {
-            Command::ResetFork(url, block_number, response_sender)
+            Command::ResetFork(fork_config, fork_at, response_sender)
         })
         .await?
     }
@@ -655,11 +656,7 @@ enum Command {
     SetBalance(Address, U256, oneshot::Sender<()>),
     SetNonce(Address, U256, oneshot::Sender<()>),
     // Fork manipulation commands
-    ResetFork(
-        Url,
-        Option<L2BlockNumber>,
-        oneshot::Sender<AnvilNodeResult<()>>,
-    ),
+    ResetFork(ForkConfig, ForkAt, oneshot::Sender<AnvilNodeResult<()>>),
     ResetForkBlockNumber(L2BlockNumber, oneshot::Sender<AnvilNodeResult<()>>),
     SetForkUrl(Url, oneshot::Sender<Option<Url>>),
     RemoveFork(oneshot::Sender<()>),
@@ -709,8 +706,8 @@ impl Command {
             Command::SetNonce(account, nonce, _) => {
                 format!("set nonce of account {account} to {nonce}")
             }
-            Command::ResetFork(url, l2_block_number, _) => {
-                format!("reset fork to url {url} and block number {l2_block_number:?}")
+            Command::ResetFork(fork_config, fork_at, _) => {
+                format!("reset fork to url {} at {fork_at:?}", fork_config.url)
             }
             Command::ResetForkBlockNumber(l2_block_number, _) => {
                 format!("reset fork block number to {l2_block_number}")
@@ -0,0 +1,414 @@
+//! Implementation of `eth_simulateV1`. Simulated blocks are executed on top of the latest state by a
+//! dedicated batch executor; neither the blocks nor their state changes are ever persisted.
+
+use crate::node::batch::{MainBatchExecutorFactory, TraceCalls};
+use crate::node::inner::vm_runner::new_bytecodes;
+use crate::node::inner::ForkStorage;
+use crate::node::state_override::apply_state_override;
+use crate::node::{compute_hash, create_block, InMemoryNodeInner, MAX_TX_SIZE};
+use crate::system_contracts::SystemContracts;
+use crate::utils::create_debug_output;
+use anvil_zksync_types::api::{
+    SimulateBlock, SimulatePayload, SimulatedBlock, SimulatedCall, SimulatedCallError,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use zksync_multivm::interface::executor::BatchExecutor;
+use zksync_multivm::interface::storage::ReadStorage;
+use zksync_multivm::interface::{
+    BatchTransactionExecutionResult, ExecutionResult, L1BatchEnv, SystemEnv, TxExecutionMode,
+};
+use zksync_multivm::vm_latest::constants::ETH_CALL_GAS_LIMIT;
+use zksync_types::api::state_override::StateOverride;
+use zksync_types::commitment::{PubdataParams, PubdataType};
+use zksync_types::l2::L2Tx;
+use zksync_types::transaction_request::CallRequest;
+use zksync_types::web3::Bytes;
+use zksync_types::{
+    api, Address, Bloom, L2BlockNumber, PackedEthSignature, StorageKey, StorageValue, Transaction,
+    H256, U256, U64,
+};
+
+/// Maximum number of blocks (including empty ones filling gaps between overridden block numbers)
+/// that can be simulated by a single request.
+const MAX_SIMULATED_BLOCKS: u64 = 256;
+
+/// JSON-RPC error code reported for reverted calls.
+const REVERTED_CALL_ERROR_CODE: i64 = 3;
+/// JSON-RPC error code reported for calls that halted VM execution.
+const HALTED_CALL_ERROR_CODE: i64 = -32015;
+
+/// Simulates blocks described by `payload` on top of the latest state.
+pub(crate) async fn simulate_blocks(
+    inner: &InMemoryNodeInner,
+    system_contracts: &SystemContracts,
+    payload: SimulatePayload,
+) -> anyhow::Result<Vec<SimulatedBlock>> {
+    anyhow::ensure!(
+        !payload.validation,
+        "`validation` mode is not supported by eth_simulateV1"
+    );
+    anyhow::ensure!(
+        !system_contracts.boojum.use_boojum,
+        "eth_simulateV1 is not supported by BoojumOS"
+    );
+
+    let system_env = inner.create_system_env(
+        system_contracts.contracts_for_l2_call().clone(),
+        TxExecutionMode::EthCall,
+    );
+    let (batch_env, _) = inner.create_l1_batch_env().await;
+    // Simulations may run concurrently with other executions, keep their fork errors separate
+    let fork_storage = inner.fork_storage.with_error_scope();
+    let mut simulator = BlockSimulator {
+        executor_factory: MainBatchExecutorFactory::<TraceCalls>::new(
+            false,
+            Arc::new(RwLock::new(Err("Tracer has not been run yet".to_string()))),
+            system_contracts.boojum.clone(),
+            None,
+        ),
+        system_env,
+        min_timestamp: batch_env.timestamp,
+        batch_env,
+        storage: Some(SimulationStorage::new(Box::new(fork_storage.clone()))),
+        fork_storage,
+        allow_no_target: system_contracts.allow_no_target(),
+        return_full_transactions: payload.return_full_transactions,
+    };
+
+    let first_number = u64::from(simulator.batch_env.first_l2_block.number);
+    let mut blocks = Vec::with_capacity(payload.block_state_calls.len());
+    for SimulateBlock {
+        block_overrides,
+        state_overrides,
+        calls,
+    } in payload.block_state_calls
+    {
+        let block_overrides = block_overrides.unwrap_or_default();
+        let next_number = u64::from(simulator.batch_env.first_l2_block.number);
+        let number = block_overrides
+            .number
+            .map_or(next_number, |number| number.as_u64());
+        anyhow::ensure!(
+            number >= next_number,
+            "block number override ({number}) must be greater than the previous block number ({})",
+            next_number - 1
+        );
+        anyhow::ensure!(
+            number - first_number < MAX_SIMULATED_BLOCKS,
+            "too many blocks to simulate (at most {MAX_SIMULATED_BLOCKS} are allowed)"
+        );
+
+        // Fill the gap with empty blocks
+        while u64::from(simulator.batch_env.first_l2_block.number) < number {
+            blocks.push(simulator.simulate_block(None, None, vec![]).await?);
+        }
+
+        if let Some(time) = block_overrides.time {
+            simulator.set_timestamp(time.as_u64())?;
+        }
+        let base_fee = block_overrides
+            .base_fee_per_gas
+            .map(|base_fee| {
+                u64::try_from(base_fee)
+                    .map_err(|_| anyhow::anyhow!("base fee override ({base_fee}) is too big"))
+            })
+            .transpose()?;
+        blocks.push(
+            simulator
+                .simulate_block(state_overrides, base_fee, calls)
+                .await?,
+        );
+    }
+    Ok(blocks)
+}
+
+struct BlockSimulator {
+    executor_factory: MainBatchExecutorFactory<TraceCalls>,
+    system_env: SystemEnv,
+    /// Environment of the next block (every simulated block is sealed in its own batch).
+    batch_env: L1BatchEnv,
+    /// Minimum timestamp of the next block.
+    min_timestamp: u64,
+    /// State left by the last simulated block. Only `None` while a block is being simulated.
+    storage: Option<SimulationStorage>,
+    /// Storage the simulation reads through, checked for failed requests to the forked network.
+    fork_storage: ForkStorage,
+    allow_no_target: bool,
+    return_full_transactions: bool,
+}
+
+impl BlockSimulator {
+    /// Fails the simulation if reading from the forked network failed, as results are meaningless
+    /// in that case.
+    fn check_fork_error(&self) -> anyhow::Result<()> {
+        match self.fork_storage.take_fork_error() {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn set_timestamp(&mut self, timestamp: u64) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            timestamp >= self.min_timestamp,
+            "block timestamp override ({timestamp}) must be greater than the previous block timestamp ({})",
+            self.min_timestamp - 1
+        );
+        self.batch_env.timestamp = timestamp;
+        self.batch_env.first_l2_block.timestamp = timestamp;
+        Ok(())
+    }
+
+    async fn simulate_block(
+        &mut self,
+        state_override: Option<StateOverride>,
+        base_fee: Option<u64>,
+        calls: Vec<CallRequest>,
+    ) -> anyhow::Result<SimulatedBlock> {
+        let storage = self
+            .storage
+            .take()
+            .expect("storage is only taken while simulating a block");
+        let storage = match state_override {
+            Some(state_override) => {
+                SimulationStorage::new(Box::new(apply_state_override(storage, state_override)))
+            }
+            None => storage,
+        };
+        let mut batch_env = self.batch_env.clone();
+        batch_env.enforced_base_fee = base_fee;
+        let number = batch_env.first_l2_block.number;
+        let timestamp = batch_env.first_l2_block.timestamp;
+        let prev_block_hash = batch_env.first_l2_block.prev_block_hash;
+
+        let mut executor = self.executor_factory.init_main_batch(
+            storage,
+            batch_env.clone(),
+            self.system_env.clone(),
+            PubdataParams {
+                l2_da_validator_address: Address::zero(),
+                pubdata_type: PubdataType::Rollup,
+            },
+            None,
+        );
+
+        // Halted calls are rolled back and hence are not included in the block
+        let mut executed = Vec::with_capacity(calls.len());
+        let mut call_results = Vec::with_capacity(calls.len());
+        for request in calls {
+            let tx = self.prepare_tx(request)?;
+            let BatchTransactionExecutionResult {
+                tx_result,
+                call_traces,
+                ..
+            } = executor.execute_tx(tx.clone()).await?;
+            self.check_fork_error()?;
+            if let ExecutionResult::Halt { reason } = &tx_result.result {
+                executor.rollback_last_tx().await?;
+                call_results.push(Err(reason.to_string()));
+                continue;
+            }
+            call_results.push(Ok(executed.len()));
+            executed.push((tx, tx_result, call_traces));
+        }
+        let (_, storage_view) = executor.bootloader().await?;
+        self.check_fork_error()?;
+
+        let hash = compute_hash(
+            self.system_env.version,
+            L2BlockNumber(number),
+            timestamp,
+            prev_block_hash,
+            executed
+                .iter()
+                .map(|(tx, ..)| tx.hash())
+                .collect::<Vec<_>>()
+                .iter(),
+        );
+        let mut next_storage = SimulationStorage::new(Box::new(storage_view));
+        let mut gas_used = U256::zero();
+        let mut transactions = Vec::with_capacity(executed.len());
+        let mut executed_calls = Vec::with_capacity(executed.len());
+        for (tx_index, (tx, tx_result, call_traces)) in executed.into_iter().enumerate() {
+            next_storage
+                .factory_deps
+                .extend(new_bytecodes(&tx, &tx_result));
+            let tx_gas_used = tx.gas_limit() - tx_result.refunds.gas_refunded;
+            gas_used += tx_gas_used;
+
+            let logs = tx_result
+                .logs
+                .events
+                .iter()
+                .enumerate()
+                .map(|(log_idx, log)| api::Log {
+                    address: log.address,
+                    topics: log.indexed_topics.clone(),
+                    data: Bytes(log.value.clone()),
+                    block_hash: Some(hash),
+                    block_number: Some(number.into()),
+                    l1_batch_number: Some(U64::from(batch_env.number.0)),
+                    transaction_hash: Some(tx.hash()),
+                    transaction_index: Some(U64::from(tx_index)),
+                    log_index: Some(U256::from(log_idx)),
+                    transaction_log_index: Some(U256::from(log_idx)),
+                    log_type: None,
+                    removed: Some(false),
+                    block_timestamp: Some(timestamp.into()),
+                })
+                .collect();
+            let (status, return_data, error) = match &tx_result.result {
+                ExecutionResult::Success { output } => (U64::one(), output.clone().into(), None),
+                ExecutionResult::Revert { output } => {
+                    let data: Bytes = output.encoded_data().into();
+                    let message = output.to_user_friendly_string();
+                    let error = SimulatedCallError {
+                        code: REVERTED_CALL_ERROR_CODE,
+                        message: if message.is_empty() {
+                            "execution reverted".to_string()
+                        } else {
+                            format!("execution reverted: {message}")
+                        },
+                        data: Some(data.clone()),
+                    };
+                    (U64::zero(), data, Some(error))
+                }
+                ExecutionResult::Halt { .. } => unreachable!("halted calls are rolled back"),
+            };
+            let trace = create_debug_output(&tx, &tx_result, call_traces).ok();
+
+            transactions.push(if self.return_full_transactions {
+                api::TransactionVariant::Full(api::Transaction {
+                    hash: tx.hash(),
+                    nonce: tx.nonce().map(|nonce| nonce.0.into()).unwrap_or_default(),
+                    block_hash: Some(hash),
+                    block_number: Some(number.into()),
+                    transaction_index: Some(U64::from(tx_index)),
+                    from: Some(tx.initiator_account()),
+                    to: tx.recipient_account(),
+                    value: tx.execute.value,
+                    gas: tx.gas_limit(),
+                    input: tx.execute.calldata.clone().into(),
+                    chain_id: self.system_env.chain_id.as_u64().into(),
+                    l1_batch_number: Some(U64::from(batch_env.number.0)),
+                    ..Default::default()
+                })
+            } else {
+                api::TransactionVariant::Hash(tx.hash())
+            });
+            executed_calls.push(SimulatedCall {
+                return_data,
+                logs,
+                gas_used: tx_gas_used,
+                status,
+                error,
+                trace,
+            });
+        }
+
+        let mut executed_calls = executed_calls.into_iter().map(Some).collect::<Vec<_>>();
+        let calls = call_results
+            .into_iter()
+            .map(|result| match result {
+                Ok(index) => executed_calls[index]
+                    .take()
+                    .expect("every executed call is reported once"),
+                Err(reason) => SimulatedCall {
+                    return_data: Bytes::default(),
+                    logs: vec![],
+                    gas_used: U256::zero(),
+                    status: U64::zero(),
+                    error: Some(SimulatedCallError {
+                        code: HALTED_CALL_ERROR_CODE,
+                        message: format!("execution halted: {reason}"),
+                        data: None,
+                    }),
+                    trace: None,
+                },
+            })
+            .collect();
+
+        let block = create_block(
+            &batch_env,
+            hash,
+            prev_block_hash,
+            number.into(),
+            timestamp,
+            transactions,
+            gas_used,
+            Bloom::zero(),
+        );
+
+        self.storage = Some(next_storage);
+        self.min_timestamp = timestamp + 1;
+        self.batch_env.number = self.batch_env.number + 1;
+        self.batch_env.timestamp = timestamp + 1;
+        self.batch_env.first_l2_block.number = number + 1;
+        self.batch_env.first_l2_block.timestamp = timestamp + 1;
+        self.batch_env.first_l2_block.prev_block_hash = hash;
+
+        Ok(SimulatedBlock {
+            inner: block,
+            calls,
+        })
+    }
+
+    fn prepare_tx(&self, request: CallRequest) -> anyhow::Result<Transaction> {
+        let gas = request.gas;
+        let mut tx = L2Tx::from_request(request.into(), MAX_TX_SIZE, self.allow_no_target)?;
+        if gas.is_none() {
+            tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
+        }
+        // We must inject *some* signature (otherwise bootloader code fails to generate hash).
+        if tx.common_data.signature.is_empty() {
+            tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
+        }
+        Ok(tx.into())
+    }
+}
+
+/// Storage of a simulated block: the state left by the previous simulated block (or the node's
+/// latest state for the first one) along with bytecodes deployed by the previous block.
+struct SimulationStorage {
+    inner: Box<dyn ReadStorage + Send>,
+    factory_deps: HashMap<H256, Vec<u8>>,
+}
+
+impl SimulationStorage {
+    fn new(inner: Box<dyn ReadStorage + Send>) -> Self {
+        Self {
+            inner,
+            factory_deps: HashMap::new(),
+        }
+    }
+}
+
+impl fmt::Debug for SimulationStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimulationStorage")
+            .field("factory_deps", &self.factory_deps.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReadStorage for SimulationStorage {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        self.inner.read_value(key)
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        self.inner.is_write_initial(key)
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        match self.factory_deps.get(&hash) {
+            Some(bytecode) => Some(bytecode.clone()),
+            None => self.inner.load_factory_dep(hash),
+        }
+    }
+
+    fn get_enumeration_index(&mut self, key: &StorageKey) -> Option<u64> {
+        self.inner.get_enumeration_index(key)
+    }
+}
@@ -8,11 +8,15 @@ use crate::node::diagnostics::transaction::known_addresses_after_transaction;
 use crate::node::diagnostics::vm::balance_diff::extract_balance_diffs;
 use crate::node::diagnostics::vm::traces::extract_addresses;
 use crate::node::error::ToHaltError;
-use crate::node::inner::fork_storage::ForkStorage;
+use crate::node::gas_profile::FoldedStacks;
+use crate::node::gas_report::GasReporter;
+use crate::node::inner::fork_storage::{ForkStorage, MissingForkData, RecordingForkStorage};
 use crate::node::inner::in_memory_inner::BlockContext;
 use crate::node::storage_logs::print_storage_logs_details;
 use crate::node::time::Time;
+use crate::node::trace::flatten_call_trace;
 use crate::node::traces::decoder::CallTraceDecoderBuilder;
+use crate::node::traces::struct_log::StructLogsResult;
 use crate::node::{
     compute_hash, InMemoryNodeInner, StorageKeyLayout, TestNodeFeeInputProvider, TransactionResult,
     TxBatch, TxExecutionInfo,
@@ -20,25 +24,31 @@ use crate::node::{
 use crate::system_contracts::SystemContracts;
 use crate::utils::create_debug_output;
 use anvil_zksync_common::shell::get_shell;
-use anvil_zksync_common::{sh_eprintln, sh_err, sh_println};
+use anvil_zksync_common::{sh_eprintln, sh_err, sh_println, sh_warn};
 use anvil_zksync_config::TestNodeConfig;
+use anvil_zksync_console::console_log::ConsoleLogHandler;
 use anvil_zksync_traces::{
     build_call_trace_arena, decode_trace_arena, filter_call_trace_arena,
     identifier::SignaturesIdentifier, render_trace_arena_inner,
 };
+use anvil_zksync_types::api::StructLogConfig;
 use anvil_zksync_types::{ShowGasDetails, ShowStorageLogs, ShowVMDetails};
 use indicatif::ProgressBar;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_error::anvil_zksync;
 use zksync_error::anvil_zksync::node::{AnvilNodeError, AnvilNodeResult};
 use zksync_multivm::interface::executor::BatchExecutor;
-use zksync_multivm::interface::storage::{ReadStorage, WriteStorage};
+use zksync_multivm::interface::storage::{ReadStorage, StorageView, WriteStorage};
 use zksync_multivm::interface::{
-    BatchTransactionExecutionResult, ExecutionResult, FinishedL1Batch, L1BatchEnv, L2BlockEnv,
-    TxExecutionMode, VmEvent, VmExecutionResultAndLogs,
+    BatchTransactionExecutionResult, ExecutionResult, FinishedL1Batch, InspectExecutionMode,
+    L1BatchEnv, L2BlockEnv, SystemEnv, TxExecutionMode, VmEvent, VmExecutionResultAndLogs,
+    VmFactory, VmInterface,
 };
+use zksync_multivm::vm_latest::{HistoryDisabled, Vm};
 use zksync_multivm::zk_evm_latest::ethereum_types::{Address, H160, U256, U64};
 use zksync_types::block::L2BlockHasher;
 use zksync_types::bytecode::BytecodeHash;
@@ -49,9 +59,14 @@ use zksync_types::{
     L2TxCommonData, StorageKey, StorageValue, Transaction, ACCOUNT_CODE_STORAGE_ADDRESS,
 };
 
+/// Maximum number of dry runs used to discover fork data needed by a single transaction.
+const MAX_PREFETCH_ROUNDS: usize = 10;
+
 pub struct VmRunner {
     executor_factory: MainBatchExecutorFactory<TraceCalls>,
     bootloader_debug_result: Arc<RwLock<eyre::Result<BootloaderDebug, String>>>,
+    /// Struct logs of the last executed transaction, `None` if struct-log tracing is disabled.
+    struct_logs_result: Option<StructLogsResult>,
 
     time: Time,
     fork_storage: ForkStorage,
@@ -61,6 +76,9 @@ pub struct VmRunner {
     /// Optional field for reporting progress while replaying transactions.
     progress_report: Option<ProgressBar>,
     storage_layout: StorageKeyLayout,
+    /// Decoder for `console.log` calls made by executed transactions.
+    console_log_handler: ConsoleLogHandler,
+    gas_reporter: GasReporter,
 }
 
 pub(super) struct TxBatchExecutionResult {
@@ -80,24 +98,41 @@ impl VmRunner {
         generate_system_logs: bool,
         enforced_bytecode_compression: bool,
         storage_layout: StorageKeyLayout,
+        gas_reporter: GasReporter,
+        struct_logs_limit: Option<usize>,
     ) -> Self {
         let bootloader_debug_result = Arc::new(std::sync::RwLock::new(Err(
             "Tracer has not been run yet".to_string(),
         )));
+        // Storage and stack are always recorded, they are filtered out on request if needed.
+        // Memory is too heavy to be kept for every executed transaction.
+        let struct_logs = struct_logs_limit.map(|limit| {
+            let config = StructLogConfig {
+                limit,
+                ..Default::default()
+            };
+            (config, StructLogsResult::default())
+        });
+        let struct_logs_result = struct_logs.as_ref().map(|(_, result)| result.clone());
         Self {
             executor_factory: MainBatchExecutorFactory::<TraceCalls>::new(
                 enforced_bytecode_compression,
                 bootloader_debug_result.clone(),
                 system_contracts.boojum.clone(),
+                struct_logs,
             ),
             bootloader_debug_result,
+            struct_logs_result,
 
             time,
-            fork_storage,
+            // Batches are executed one at a time, so they can share a single error scope
+            fork_storage: fork_storage.with_error_scope(),
             system_contracts,
             generate_system_logs,
             progress_report: None,
             storage_layout,
+            console_log_handler: ConsoleLogHandler::default(),
+            gas_reporter,
         }
     }
 }
@@ -170,7 +205,8 @@ impl VmRunner {
         executor: &mut dyn BatchExecutor<ForkStorage>,
         config: &TestNodeConfig,
         fee_input_provider: &TestNodeFeeInputProvider,
-    ) -> AnvilNodeResult<BatchTransactionExecutionResult> {
+        auto_funded: Option<U256>,
+    ) -> AnvilNodeResult<(BatchTransactionExecutionResult, Option<FoldedStacks>)> {
         let verbosity = get_shell().verbosity;
 
         let BatchTransactionExecutionResult {
@@ -178,6 +214,11 @@ impl VmRunner {
             compression_result,
             call_traces,
         } = executor.execute_tx(tx.clone()).await?;
+        // Storage reads cannot fail from the VM's perspective, so a failed request to the forked
+        // network only shows up here (execution result is meaningless in that case).
+        if let Some(error) = self.fork_storage.take_fork_error() {
+            return Err(error);
+        }
         compression_result.map_err(|_inner| {
             // We ignore `inner` because bytecode
             // compression error currently does not hold
@@ -193,6 +234,7 @@ impl VmRunner {
 
         let mut known_addresses = known_addresses_after_transaction(tx);
         let mut trace_output = None;
+        let mut gas_profile = None;
 
         if !call_traces.is_empty() {
             let mut builder = CallTraceDecoderBuilder::default();
@@ -211,6 +253,17 @@ impl VmRunner {
             decode_trace_arena(&mut arena, &decoder).await;
 
             extract_addresses(&arena, &mut known_addresses);
+            if config.gas_report {
+                self.gas_reporter
+                    .record(&arena, tx.initiator_account(), spent_on_pubdata);
+            }
+            if config.is_gas_profile_enabled() {
+                gas_profile = Some(FoldedStacks::from_arena(
+                    &arena,
+                    self.bootloader_debug_result.read().unwrap().as_ref().ok(),
+                    spent_on_pubdata,
+                ));
+            }
 
             if verbosity >= 2 {
                 let filtered_arena = filter_call_trace_arena(&arena, verbosity);
@@ -232,6 +285,7 @@ impl VmRunner {
                 &tx_result,
                 (verbosity >= 1).then_some(balance_diffs),
             )
+            .with_auto_funded(auto_funded)
         );
 
         if let Some(trace_output) = trace_output {
@@ -254,16 +308,23 @@ impl VmRunner {
             print_storage_logs_details(config.show_storage_logs, &tx_result);
         }
         // Print VM details if enabled
+        let fetch_stats = self.fork_storage.take_fetch_stats();
         if config.show_vm_details != ShowVMDetails::None {
             let mut formatter = Formatter::new();
             formatter.print_vm_details(&tx_result);
+            if self.fork_storage.is_forked() {
+                formatter.print_fork_fetch_stats(&fetch_stats);
+            }
         }
 
-        Ok(BatchTransactionExecutionResult {
-            tx_result,
-            compression_result: Ok(()),
-            call_traces,
-        })
+        Ok((
+            BatchTransactionExecutionResult {
+                tx_result,
+                compression_result: Ok(()),
+                call_traces,
+            },
+            gas_profile,
+        ))
     }
 
     /// Runs transaction and commits it to a new block.
@@ -279,6 +340,7 @@ impl VmRunner {
         config: &TestNodeConfig,
         fee_input_provider: &TestNodeFeeInputProvider,
         impersonating: bool,
+        auto_funded: Option<U256>,
     ) -> AnvilNodeResult<TransactionResult> {
         let tx_hash = tx.hash();
         let transaction_type = tx.tx_format();
@@ -293,12 +355,15 @@ impl VmRunner {
             })?;
         }
 
-        let BatchTransactionExecutionResult {
-            tx_result: result,
-            compression_result: _,
-            call_traces,
-        } = self
-            .run_tx_pretty(tx, executor, config, fee_input_provider)
+        let (
+            BatchTransactionExecutionResult {
+                tx_result: result,
+                compression_result: _,
+                call_traces,
+            },
+            gas_profile,
+        ) = self
+            .run_tx_pretty(tx, executor, config, fee_input_provider, auto_funded)
             .await?;
 
         if let ExecutionResult::Halt { reason } = result.result {
@@ -397,7 +462,25 @@ impl VmRunner {
             logs_bloom: Default::default(),
         };
         *next_log_index += result.logs.user_l2_to_l1_logs.len();
-        let debug = create_debug_output(tx, &result, call_traces).expect("create debug output"); // OK to unwrap here as Halt is handled above
+        let console_logs = self
+            .console_log_handler
+            .collect_calls_recursive(&call_traces);
+        if let (Some(gas_profile_dir), Some(gas_profile)) = (&config.gas_profile_dir, &gas_profile)
+        {
+            if !gas_profile.is_empty() {
+                if let Err(err) = gas_profile.write_to_dir(gas_profile_dir, tx_hash) {
+                    sh_warn!("Failed to write gas profile for transaction {tx_hash:?}: {err}");
+                }
+            }
+        }
+        let struct_logs = self
+            .struct_logs_result
+            .as_ref()
+            .map(|struct_logs| std::mem::take(&mut *struct_logs.write().unwrap()))
+            .unwrap_or_default();
+        let debug =
+            create_debug_output(tx, &result, call_traces.clone()).expect("create debug output"); // OK to unwrap here as Halt is handled above
+        let flat_traces = flatten_call_trace(&debug, Some(call_traces.as_slice()));
 
         Ok(TransactionResult {
             info: TxExecutionInfo {
@@ -408,9 +491,143 @@ impl VmRunner {
             new_bytecodes,
             receipt: tx_receipt,
             debug,
+            console_logs,
+            gas_profile,
+            struct_logs,
+            auto_funded,
+            flat_traces,
         })
     }
 
+    /// Tops up initiators of `txs` that cannot cover `gas_limit * max_fee_per_gas + value` (fees
+    /// are only accounted for if the initiator pays them itself, i.e. there is no paymaster). Every
+    /// initiator is funded once for all of its transactions in the batch, before the batch starts
+    /// executing. Returns the top-up attributed to each transaction so that it can be taken back
+    /// with [`Self::revoke_top_ups`] if the transaction does not make it into a block.
+    fn auto_fund(&mut self, txs: &[Transaction]) -> Vec<Option<U256>> {
+        // Initiator -> (balance before the batch, total amount required so far)
+        let mut accounts: HashMap<Address, (U256, U256)> = HashMap::new();
+        let top_ups = txs
+            .iter()
+            .map(|tx| {
+                let ExecuteTransactionCommon::L2(l2_tx_data) = &tx.common_data else {
+                    return None;
+                };
+                let initiator = tx.initiator_account();
+                let fee = if tx.payer() == initiator {
+                    l2_tx_data
+                        .fee
+                        .gas_limit
+                        .saturating_mul(l2_tx_data.fee.max_fee_per_gas)
+                } else {
+                    U256::zero()
+                };
+                let required = fee.saturating_add(tx.execute.value);
+                let (balance, total_required) = accounts.entry(initiator).or_insert_with(|| {
+                    let balance_key = self
+                        .storage_layout
+                        .get_storage_key_for_base_token(&initiator);
+                    (
+                        h256_to_u256(self.fork_storage.read_value(&balance_key)),
+                        U256::zero(),
+                    )
+                });
+                let shortfall_before = total_required.saturating_sub(*balance);
+                *total_required = total_required.saturating_add(required);
+                let top_up = total_required.saturating_sub(*balance) - shortfall_before;
+                (!top_up.is_zero()).then_some(top_up)
+            })
+            .collect();
+
+        for (initiator, (balance, total_required)) in accounts {
+            if total_required > balance {
+                // Same write path as `NodeExecutorHandle::set_balance_sync`
+                let balance_key = self
+                    .storage_layout
+                    .get_storage_key_for_base_token(&initiator);
+                self.fork_storage
+                    .set_value(balance_key, u256_to_h256(total_required));
+                tracing::info!(
+                    "Auto-funded {:?} with {} wei",
+                    initiator,
+                    total_required - balance
+                );
+            }
+        }
+        top_ups
+    }
+
+    /// Takes back top-ups minted by [`Self::auto_fund`] for transactions that did not make it into
+    /// a block. Balances the batch has written to are adjusted in `modified_storage_keys`, the rest
+    /// directly in fork storage.
+    fn revoke_top_ups(
+        &mut self,
+        top_ups: HashMap<Address, U256>,
+        modified_storage_keys: &mut HashMap<StorageKey, StorageValue>,
+    ) {
+        for (initiator, top_up) in top_ups {
+            let balance_key = self
+                .storage_layout
+                .get_storage_key_for_base_token(&initiator);
+            if let Some(balance) = modified_storage_keys.get_mut(&balance_key) {
+                *balance = u256_to_h256(h256_to_u256(*balance).saturating_sub(top_up));
+            } else {
+                let balance = h256_to_u256(self.fork_storage.read_value(&balance_key));
+                self.fork_storage
+                    .set_value(balance_key, u256_to_h256(balance.saturating_sub(top_up)));
+            }
+            tracing::info!(
+                "Revoked auto-funding of {} wei from {:?}",
+                top_up,
+                initiator
+            );
+        }
+    }
+
+    /// Dry-runs `tx` against locally available state to discover storage slots and bytecodes it
+    /// needs from the fork, then fetches them in batches. Repeats until the transaction no longer
+    /// touches unknown data (fetched values can lead execution down a different path) or
+    /// [`MAX_PREFETCH_ROUNDS`] is reached. Anything missed is still fetched lazily during execution.
+    async fn prefetch_fork_data(
+        &self,
+        tx: &Transaction,
+        batch_env: &L1BatchEnv,
+        system_env: &SystemEnv,
+    ) {
+        for _ in 0..MAX_PREFETCH_ROUNDS {
+            let missing = self.find_missing_fork_data(tx, batch_env, system_env);
+            if missing.is_empty() {
+                break;
+            }
+            if let Err(err) = self.fork_storage.prefetch(missing).await {
+                tracing::warn!(
+                    "failed to prefetch data from fork, falling back to on-demand reads: {err:#}"
+                );
+                break;
+            }
+        }
+    }
+
+    fn find_missing_fork_data(
+        &self,
+        tx: &Transaction,
+        batch_env: &L1BatchEnv,
+        system_env: &SystemEnv,
+    ) -> MissingForkData {
+        let missing = Rc::new(RefCell::new(MissingForkData::default()));
+        let storage = StorageView::new(RecordingForkStorage::new(
+            self.fork_storage.clone(),
+            missing.clone(),
+        ))
+        .to_rc_ptr();
+        let mut vm: Vm<_, HistoryDisabled> =
+            Vm::new(batch_env.clone(), system_env.clone(), storage);
+        vm.push_transaction(tx.clone());
+        vm.inspect(&mut Default::default(), InspectExecutionMode::OneTx);
+        drop(vm);
+        missing.take()
+    }
+
     pub(super) async fn run_tx_batch(
         &mut self,
         TxBatch { txs, impersonating }: TxBatch,
@@ -433,167 +650,217 @@ impl VmRunner {
             ));
         };
 
-        let pubdata_params = PubdataParams {
-            l2_da_validator_address: Address::zero(),
-            pubdata_type: PubdataType::Rollup,
-        };
-        let mut executor = if self.system_contracts.boojum.use_boojum {
-            self.executor_factory.init_main_batch(
-                self.fork_storage.clone(),
-                batch_env.clone(),
-                system_env.clone(),
-                pubdata_params,
-                // For boojum, we have to pass the iterator handle to the storage
-                // as boojum has different storage layout, so it has to scan over whole storage.
-                Some(self.fork_storage.inner.read().unwrap().raw_storage.clone()),
-            )
+        // Drop errors and statistics left over by previous executions that did not check for them
+        self.fork_storage.take_fork_error();
+        self.fork_storage.take_fetch_stats();
+        let prefetch = !self.system_contracts.boojum.use_boojum && self.fork_storage.is_forked();
+
+        let top_ups = if node_inner.config.auto_fund {
+            self.auto_fund(&txs)
         } else {
-            self.executor_factory.init_main_batch(
-                self.fork_storage.clone(),
-                batch_env.clone(),
-                system_env.clone(),
-                pubdata_params,
-                None,
-            )
+            vec![None; txs.len()]
         };
 
-        // Compute block hash. Note that the computed block hash here will be different than that in production.
-        let tx_hashes = txs.iter().map(|t| t.hash()).collect::<Vec<_>>();
-        block_ctx.hash = compute_hash(
-            system_env.version,
-            (block_ctx.miniblock as u32).into(),
-            block_ctx.timestamp,
-            block_ctx.prev_block_hash,
-            &tx_hashes,
+        let top_ups_by_initiator = txs.iter().zip(&top_ups).fold(
+            HashMap::<Address, U256>::new(),
+            |mut acc, (tx, top_up)| {
+                if let Some(top_up) = top_up {
+                    *acc.entry(tx.initiator_account()).or_default() += *top_up;
+                }
+                acc
+            },
         );
+        // Batch state is discarded on error, so every top-up has to be taken back
+        let result: AnvilNodeResult<TxBatchExecutionResult> = async {
+            let pubdata_params = PubdataParams {
+                l2_da_validator_address: Address::zero(),
+                pubdata_type: PubdataType::Rollup,
+            };
+            let mut executor = if self.system_contracts.boojum.use_boojum {
+                self.executor_factory.init_main_batch(
+                    self.fork_storage.clone(),
+                    batch_env.clone(),
+                    system_env.clone(),
+                    pubdata_params,
+                    // For boojum, we have to pass the iterator handle to the storage
+                    // as boojum has different storage layout, so it has to scan over whole storage.
+                    Some(self.fork_storage.inner.read().unwrap().raw_storage.clone()),
+                )
+            } else {
+                self.executor_factory.init_main_batch(
+                    self.fork_storage.clone(),
+                    batch_env.clone(),
+                    system_env.clone(),
+                    pubdata_params,
+                    None,
+                )
+            };
 
-        // Execute transactions and bootloader
-        let mut tx_results = Vec::with_capacity(tx_hashes.len());
-        let mut tx_index = 0;
-        let mut next_log_index = 0;
-        let total = txs.len();
-
-        for tx in txs {
-            if let Some(ref pb) = self.progress_report {
-                pb.set_message(format!(
-                    "Replaying transaction {}/{} from 0x{:x}...",
-                    tx_index + 1,
-                    total,
-                    tx.hash()
-                ));
-            }
+            // Compute block hash. Note that the computed block hash here will be different than that in production.
+            let tx_hashes = txs.iter().map(|t| t.hash()).collect::<Vec<_>>();
+            block_ctx.hash = compute_hash(
+                system_env.version,
+                (block_ctx.miniblock as u32).into(),
+                block_ctx.timestamp,
+                block_ctx.prev_block_hash,
+                &tx_hashes,
+            );
 
-            let result = self
-                .run_tx(
-                    &tx,
-                    tx_index,
-                    &mut next_log_index,
-                    &block_ctx,
-                    &batch_env,
-                    &mut executor,
-                    &node_inner.config,
-                    &node_inner.fee_input_provider,
-                    impersonating,
-                )
-                .await;
+            // Execute transactions and bootloader
+            let mut tx_results = Vec::with_capacity(tx_hashes.len());
+            // Top-ups of transactions that did not make it into the block
+            let mut rejected_top_ups = HashMap::<Address, U256>::new();
+            let mut tx_index = 0;
+            let mut next_log_index = 0;
+            let total = txs.len();
+
+            for (tx, auto_funded) in txs.into_iter().zip(top_ups) {
+                if let Some(ref pb) = self.progress_report {
+                    pb.set_message(format!(
+                        "Replaying transaction {}/{} from 0x{:x}...",
+                        tx_index + 1,
+                        total,
+                        tx.hash()
+                    ));
+                }
 
-            // Update progress bar
-            if let Some(ref pb) = self.progress_report {
-                pb.inc(1);
-            }
-            match result {
-                Ok(tx_result) => {
-                    tx_results.push(tx_result);
-                    tx_index += 1;
+                if prefetch {
+                    self.prefetch_fork_data(&tx, &batch_env, &system_env).await;
                 }
-                Err(e) => {
-                    match &e {
-                        // Validation errors are reported and the execution proceeds
-                        AnvilNodeError::TransactionValidationFailed { .. } => {
-                            let error_report = ExecutionErrorReport::new(&e, &tx);
-                            sh_eprintln!("{error_report}");
-                            executor.rollback_last_tx().await?;
-                        }
-                        // Halts are reported and the execution proceeds
-                        AnvilNodeError::TransactionHalt { inner, .. } => {
-                            let error_report = ExecutionErrorReport::new(inner.as_ref(), &tx);
-                            sh_eprintln!("{error_report}");
-                            executor.rollback_last_tx().await?;
+
+                let result = self
+                    .run_tx(
+                        &tx,
+                        tx_index,
+                        &mut next_log_index,
+                        &block_ctx,
+                        &batch_env,
+                        &mut executor,
+                        &node_inner.config,
+                        &node_inner.fee_input_provider,
+                        impersonating,
+                        auto_funded,
+                    )
+                    .await;
+
+                // Update progress bar
+                if let Some(ref pb) = self.progress_report {
+                    pb.inc(1);
+                }
+                match result {
+                    Ok(tx_result) => {
+                        tx_results.push(tx_result);
+                        tx_index += 1;
+                    }
+                    Err(e) => {
+                        match &e {
+                            // Validation errors are reported and the execution proceeds
+                            AnvilNodeError::TransactionValidationFailed { .. } => {
+                                let error_report = ExecutionErrorReport::new(&e, &tx);
+                                sh_eprintln!("{error_report}");
+                                executor.rollback_last_tx().await?;
+                                if let Some(top_up) = auto_funded {
+                                    *rejected_top_ups.entry(tx.initiator_account()).or_default() +=
+                                        top_up;
+                                }
+                            }
+                            // Halts are reported and the execution proceeds
+                            AnvilNodeError::TransactionHalt { inner, .. } => {
+                                let error_report = ExecutionErrorReport::new(inner.as_ref(), &tx);
+                                sh_eprintln!("{error_report}");
+                                executor.rollback_last_tx().await?;
+                                if let Some(top_up) = auto_funded {
+                                    *rejected_top_ups.entry(tx.initiator_account()).or_default() +=
+                                        top_up;
+                                }
+                            }
+                            // Other errors are not recoverable so we pass them up
+                            // the execution stack immediately
+                            _ => return Err(e),
                         }
-                        // Other errors are not recoverable so we pass them up
-                        // the execution stack immediately
-                        _ => return Err(e),
                     }
                 }
             }
-        }
-        // TODO: This is the correct hash as reported by VM, but we can't compute it correct above
-        //       because we don't know which txs are going to be halted
-        block_ctx.hash = compute_hash(
-            system_env.version,
-            (block_ctx.miniblock as u32).into(),
-            block_ctx.timestamp,
-            block_ctx.prev_block_hash,
-            tx_results
-                .iter()
-                .map(|tx_result| &tx_result.receipt.transaction_hash),
-        );
+            // TODO: This is the correct hash as reported by VM, but we can't compute it correct above
+            //       because we don't know which txs are going to be halted
+            block_ctx.hash = compute_hash(
+                system_env.version,
+                (block_ctx.miniblock as u32).into(),
+                block_ctx.timestamp,
+                block_ctx.prev_block_hash,
+                tx_results
+                    .iter()
+                    .map(|tx_result| &tx_result.receipt.transaction_hash),
+            );
 
-        let mut block_ctxs = vec![block_ctx.clone()];
-        if !tx_results.is_empty() {
-            // Create an empty virtual block at the end of the batch (only if the last block was
-            // not empty, i.e. virtual).
-            let mut virtual_block_ctx = block_ctx.new_block(&mut self.time);
-            virtual_block_ctx.hash = L2BlockHasher::new(
-                L2BlockNumber(virtual_block_ctx.miniblock as u32),
-                virtual_block_ctx.timestamp,
-                block_ctx.hash,
-            )
-            .finalize(system_env.version);
-            let l2_block_env = L2BlockEnv {
-                number: (block_ctx.miniblock + 1) as u32,
-                timestamp: block_ctx.timestamp + 1,
-                prev_block_hash: block_ctx.hash,
-                max_virtual_blocks_to_create: 1,
+            let mut block_ctxs = vec![block_ctx.clone()];
+            if !tx_results.is_empty() {
+                // Create an empty virtual block at the end of the batch (only if the last block was
+                // not empty, i.e. virtual).
+                let mut virtual_block_ctx = block_ctx.new_block(&mut self.time);
+                virtual_block_ctx.hash = L2BlockHasher::new(
+                    L2BlockNumber(virtual_block_ctx.miniblock as u32),
+                    virtual_block_ctx.timestamp,
+                    block_ctx.hash,
+                )
+                .finalize(system_env.version);
+                let l2_block_env = L2BlockEnv {
+                    number: (block_ctx.miniblock + 1) as u32,
+                    timestamp: block_ctx.timestamp + 1,
+                    prev_block_hash: block_ctx.hash,
+                    max_virtual_blocks_to_create: 1,
+                };
+                executor.start_next_l2_block(l2_block_env).await?;
+                block_ctxs.push(virtual_block_ctx);
+            }
+
+            let (finished_l1_batch, mut modified_storage_keys) = if self.generate_system_logs {
+                // If system log generation is enabled we run realistic (and time-consuming) bootloader flow
+                let (finished_l1_batch, storage_view) = Box::new(executor).finish_batch().await?;
+                (
+                    finished_l1_batch,
+                    storage_view.modified_storage_keys().clone(),
+                )
+            } else {
+                // Otherwise we mock the execution with a single bootloader iteration
+                let mut finished_l1_batch = FinishedL1Batch::mock();
+                let (bootloader_execution_result, storage_view) = executor.bootloader().await?;
+                finished_l1_batch.block_tip_execution_result = bootloader_execution_result;
+                (
+                    finished_l1_batch,
+                    storage_view.modified_storage_keys().clone(),
+                )
             };
-            executor.start_next_l2_block(l2_block_env).await?;
-            block_ctxs.push(virtual_block_ctx);
-        }
+            // Reads made by the bootloader while finalizing the batch go through the same storage,
+            // so a block built on defaulted values must not be sealed either
+            if let Some(error) = self.fork_storage.take_fork_error() {
+                return Err(error);
+            }
+            assert!(
+                !finished_l1_batch
+                    .block_tip_execution_result
+                    .result
+                    .is_failed(),
+                "VM must not fail when finalizing block: {:#?}",
+                finished_l1_batch.block_tip_execution_result.result
+            );
 
-        let (finished_l1_batch, modified_storage_keys) = if self.generate_system_logs {
-            // If system log generation is enabled we run realistic (and time-consuming) bootloader flow
-            let (finished_l1_batch, storage_view) = Box::new(executor).finish_batch().await?;
-            (
-                finished_l1_batch,
-                storage_view.modified_storage_keys().clone(),
-            )
-        } else {
-            // Otherwise we mock the execution with a single bootloader iteration
-            let mut finished_l1_batch = FinishedL1Batch::mock();
-            let (bootloader_execution_result, storage_view) = executor.bootloader().await?;
-            finished_l1_batch.block_tip_execution_result = bootloader_execution_result;
-            (
-                finished_l1_batch,
-                storage_view.modified_storage_keys().clone(),
-            )
-        };
-        assert!(
-            !finished_l1_batch
-                .block_tip_execution_result
-                .result
-                .is_failed(),
-            "VM must not fail when finalizing block: {:#?}",
-            finished_l1_batch.block_tip_execution_result.result
-        );
+            self.revoke_top_ups(rejected_top_ups, &mut modified_storage_keys);
 
-        Ok(TxBatchExecutionResult {
-            tx_results,
-            base_system_contracts_hashes,
-            batch_env,
-            block_ctxs,
-            finished_l1_batch,
-            modified_storage_keys,
-        })
+            Ok(TxBatchExecutionResult {
+                tx_results,
+                base_system_contracts_hashes,
+                batch_env,
+                block_ctxs,
+                finished_l1_batch,
+                modified_storage_keys,
+            })
+        }
+        .await;
+        if result.is_err() {
+            self.revoke_top_ups(top_ups_by_initiator, &mut HashMap::new());
+        }
+        result
     }
 
     /// Set or unset the progress report.
@@ -602,7 +869,7 @@ impl VmRunner {
     }
 }
 
-fn new_bytecodes(
+pub(super) fn new_bytecodes(
     tx: &Transaction,
     result: &VmExecutionResultAndLogs,
 ) -> Vec<(zksync_types::H256, Vec<u8>)> {
@@ -662,8 +929,7 @@ mod test {
     use anvil_zksync_config::types::SystemContractsOptions;
     use std::str::FromStr;
     use zksync_multivm::interface::executor::BatchExecutorFactory;
-    use zksync_multivm::interface::storage::StorageView;
-    use zksync_multivm::interface::{L2Block, SystemEnv};
+    use zksync_multivm::interface::L2Block;
     use zksync_multivm::vm_latest::constants::BATCH_COMPUTATIONAL_GAS_LIMIT;
     use zksync_multivm::vm_latest::utils::l2_blocks::load_last_l2_block;
     use zksync_types::fee::Fee;
@@ -710,6 +976,8 @@ mod test {
                 false,
                 config.is_bytecode_compression_enforced(),
                 storage_layout,
+                GasReporter::default(),
+                None,
             );
             VmRunnerTester {
                 vm_runner,
@@ -733,10 +1001,7 @@ mod test {
             Ok(self.test_txs(vec![tx]).await?.into_iter().next().unwrap())
         }
 
-        async fn test_txs(
-            &mut self,
-            txs: Vec<Transaction>,
-        ) -> AnvilNodeResult<Vec<TransactionResult>> {
+        fn test_envs(&self) -> (BlockContext, L1BatchEnv, SystemEnv) {
             let system_env = SystemEnv {
                 zk_porter_available: false,
                 version: ProtocolVersionId::latest(),
@@ -781,6 +1046,14 @@ mod test {
                     max_virtual_blocks_to_create: 1,
                 },
             };
+            (block_ctx, batch_env, system_env)
+        }
+
+        async fn test_txs(
+            &mut self,
+            txs: Vec<Transaction>,
+        ) -> AnvilNodeResult<Vec<TransactionResult>> {
+            let (block_ctx, batch_env, system_env) = self.test_envs();
             let mut executor = self.vm_runner.executor_factory.init_batch(
                 self.vm_runner.fork_storage.clone(),
                 batch_env.clone(),
@@ -803,6 +1076,7 @@ mod test {
                             &self.config,
                             &TestNodeFeeInputProvider::default(),
                             false,
+                            None,
                         )
                         .await?,
                 );
@@ -926,6 +1200,48 @@ mod test {
             .expect("transaction must pass with mock fork client");
     }
 
+    #[tokio::test]
+    async fn test_prefetch_fetches_fork_data_ahead_of_execution() {
+        let mock_storage = VmRunnerTester::new()
+            .vm_runner
+            .fork_storage
+            .inner
+            .read()
+            .unwrap()
+            .raw_storage
+            .clone();
+        let fork_details = ForkDetails {
+            chain_id: TEST_NODE_NETWORK_ID.into(),
+            batch_number: L1BatchNumber(1),
+            block_number: L2BlockNumber(2),
+            ..Default::default()
+        };
+        let mut tester = VmRunnerTester::new_custom(
+            Some(ForkClient::mock(fork_details, mock_storage)),
+            TestNodeConfig::default(),
+        );
+        let tx: Transaction = TransactionBuilder::new().build().into();
+        tester.make_rich(&tx.initiator_account());
+
+        let (_, batch_env, system_env) = tester.test_envs();
+        tester
+            .vm_runner
+            .prefetch_fork_data(&tx, &batch_env, &system_env)
+            .await;
+        let stats = tester.vm_runner.fork_storage.take_fetch_stats();
+        assert!(stats.prefetch_rounds > 0);
+        assert!(stats.prefetched_slots > 0);
+        assert!(tester
+            .vm_runner
+            .find_missing_fork_data(&tx, &batch_env, &system_env)
+            .is_empty());
+
+        tester
+            .test_tx(tx)
+            .await
+            .expect("transaction must pass with prefetched fork data");
+    }
+
     #[tokio::test]
     async fn test_transact_returns_data_in_built_in_without_security_mode() {
         let mut tester = VmRunnerTester::new_custom(
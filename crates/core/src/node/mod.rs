@@ -1,32 +1,44 @@
 //! anvil-zksync, that supports forking other networks.
 
 mod batch;
+mod block_replay;
 mod boojumos;
 mod debug;
 pub mod diagnostics;
 pub mod error;
 mod eth;
 mod fee_model;
+mod fork_follower;
+pub mod gas_profile;
+pub mod gas_report;
 mod impersonate;
 mod in_memory;
 mod in_memory_ext;
 mod inner;
 mod keys;
+mod ots;
 mod pool;
+mod predeploys;
 mod sealer;
+mod sign;
 mod state;
 mod state_override;
 mod storage_logs;
+mod testnet_paymaster;
+mod tokens;
+mod trace;
 mod traces;
 mod vm;
 mod zks;
 
 pub use self::{
-    fee_model::TestNodeFeeInputProvider, impersonate::ImpersonationManager, keys::StorageKeyLayout,
+    fee_model::TestNodeFeeInputProvider, fork_follower::ForkFollower,
+    fork_follower::ForkFollowerState, impersonate::ImpersonationManager, keys::StorageKeyLayout,
     node_executor::NodeExecutor, pool::TxBatch, pool::TxPool, sealer::BlockSealer,
-    sealer::BlockSealerMode, state::VersionedState,
+    sealer::BlockSealerMode, state::VersionedState, testnet_paymaster::TestnetPaymasters,
 };
 pub use boojumos::boojumos_get_batch_witness;
 pub use in_memory::*;
+pub(crate) use inner::ForkFetchStats;
 pub use inner::InMemoryNodeInner;
-pub use inner::{blockchain, fork, node_executor, time};
+pub use inner::{blockchain, fork, fork_url, node_executor, time};
@@ -0,0 +1,443 @@
+//! Otterscan (`ots_*`) API built on top of blocks and call trees stored for executed transactions.
+
+use crate::node::InMemoryNode;
+use anvil_zksync_common::address_map;
+use anvil_zksync_types::api::{
+    OtsBlock, OtsBlockDetails, OtsBlockTransactions, OtsContractCreator, OtsInternalOperation,
+    OtsOperationType, OtsSearchTransactions, OtsTrace, OtsTraceType, OtsTransactionReceipt,
+};
+use std::iter::Peekable;
+use zksync_types::api::{
+    Block, BlockId, BlockIdVariant, BlockNumber, DebugCall, DebugCallType, TransactionVariant,
+};
+use zksync_types::web3::Bytes;
+use zksync_types::{Address, L2BlockNumber, Nonce, H256, U256};
+
+/// Version of the Otterscan API implemented by the node.
+const OTS_API_LEVEL: u64 = 8;
+
+impl InMemoryNode {
+    pub fn ots_get_api_level_impl(&self) -> u64 {
+        OTS_API_LEVEL
+    }
+
+    pub async fn ots_get_internal_operations_impl(
+        &self,
+        tx_hash: H256,
+    ) -> anyhow::Result<Vec<OtsInternalOperation>> {
+        let debug = self.ots_tx_debug_info(tx_hash).await?;
+        let mut operations = Vec::new();
+        for call in &debug.calls {
+            push_internal_operations(call, &mut operations);
+        }
+        Ok(operations)
+    }
+
+    pub async fn ots_has_code_impl(
+        &self,
+        address: Address,
+        block: Option<BlockIdVariant>,
+    ) -> anyhow::Result<bool> {
+        Ok(!self.get_code_impl(address, block).await?.0.is_empty())
+    }
+
+    pub async fn ots_get_transaction_error_impl(&self, tx_hash: H256) -> anyhow::Result<Bytes> {
+        let debug = self.ots_tx_debug_info(tx_hash).await?;
+        if debug.error.is_some() || debug.revert_reason.is_some() {
+            Ok(debug.output)
+        } else {
+            Ok(Bytes::default())
+        }
+    }
+
+    pub async fn ots_trace_transaction_impl(&self, tx_hash: H256) -> anyhow::Result<Vec<OtsTrace>> {
+        let debug = self.ots_tx_debug_info(tx_hash).await?;
+        let mut traces = Vec::new();
+        push_traces(&debug, 0, &mut traces);
+        Ok(traces)
+    }
+
+    pub async fn ots_get_block_details_impl(
+        &self,
+        block_number: u64,
+    ) -> anyhow::Result<Option<OtsBlockDetails>> {
+        let Some(block) = self.ots_block(block_number, false).await? else {
+            return Ok(None);
+        };
+        let mut total_fees = U256::zero();
+        for tx in &block.transactions {
+            let tx_hash = match tx {
+                TransactionVariant::Full(tx) => tx.hash,
+                TransactionVariant::Hash(hash) => *hash,
+            };
+            if let Some(receipt) = self.blockchain.get_tx_receipt(&tx_hash).await {
+                total_fees += receipt.effective_gas_price.unwrap_or_default()
+                    * receipt.gas_used.unwrap_or_default();
+            }
+        }
+
+        Ok(Some(OtsBlockDetails {
+            block: OtsBlock {
+                transaction_count: block.transactions.len(),
+                block: Block {
+                    transactions: vec![],
+                    ..block
+                },
+            },
+            issuance: Default::default(),
+            total_fees,
+        }))
+    }
+
+    pub async fn ots_get_block_transactions_impl(
+        &self,
+        block_number: u64,
+        page_number: usize,
+        page_size: usize,
+    ) -> anyhow::Result<Option<OtsBlockTransactions>> {
+        let Some(mut block) = self.ots_block(block_number, true).await? else {
+            return Ok(None);
+        };
+        let transaction_count = block.transactions.len();
+        block.transactions = block
+            .transactions
+            .into_iter()
+            .skip(page_number.saturating_mul(page_size))
+            .take(page_size)
+            .collect();
+
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+        for tx in &block.transactions {
+            let TransactionVariant::Full(tx) = tx else {
+                unreachable!("block was requested with full transactions")
+            };
+            if let Some(receipt) = self.blockchain.get_tx_receipt(&tx.hash).await {
+                receipts.push(receipt);
+            }
+        }
+
+        Ok(Some(OtsBlockTransactions {
+            fullblock: OtsBlock {
+                block,
+                transaction_count,
+            },
+            receipts,
+        }))
+    }
+
+    pub async fn ots_search_transactions_before_impl(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> anyhow::Result<OtsSearchTransactions> {
+        let tx_hashes = self.blockchain.get_address_tx_hashes(&address).await;
+        // Block number `0` means searching from the most recent block
+        let older = tx_hashes
+            .into_iter()
+            .rev()
+            .filter(|(number, _)| block_number == 0 || u64::from(number.0) < block_number);
+        let (page, has_more) = take_page(older.peekable(), page_size);
+        self.ots_search_result(page, block_number == 0, !has_more)
+            .await
+    }
+
+    pub async fn ots_search_transactions_after_impl(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> anyhow::Result<OtsSearchTransactions> {
+        let tx_hashes = self.blockchain.get_address_tx_hashes(&address).await;
+        let newer = tx_hashes
+            .into_iter()
+            .filter(|(number, _)| u64::from(number.0) > block_number);
+        let (mut page, has_more) = take_page(newer.peekable(), page_size);
+        page.reverse();
+        self.ots_search_result(page, !has_more, block_number == 0)
+            .await
+    }
+
+    pub async fn ots_get_transaction_by_sender_and_nonce_impl(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> anyhow::Result<Option<H256>> {
+        let Ok(nonce) = u32::try_from(nonce) else {
+            return Ok(None);
+        };
+        Ok(self
+            .blockchain
+            .get_tx_hash_by_sender_and_nonce(&sender, Nonce(nonce))
+            .await)
+    }
+
+    pub async fn ots_get_contract_creator_impl(
+        &self,
+        address: Address,
+    ) -> anyhow::Result<Option<OtsContractCreator>> {
+        Ok(self
+            .blockchain
+            .get_contract_creator(&address)
+            .await
+            .map(|(hash, creator)| OtsContractCreator { hash, creator }))
+    }
+
+    async fn ots_tx_debug_info(&self, tx_hash: H256) -> anyhow::Result<DebugCall> {
+        self.blockchain
+            .get_tx_debug_info(&tx_hash, false)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Transaction (hash={tx_hash}) not found"))
+    }
+
+    async fn ots_block(
+        &self,
+        block_number: u64,
+        full_transactions: bool,
+    ) -> anyhow::Result<Option<Block<TransactionVariant>>> {
+        self.get_block_impl(
+            BlockId::Number(BlockNumber::Number(block_number.into())),
+            full_transactions,
+        )
+        .await
+    }
+
+    async fn ots_search_result(
+        &self,
+        tx_hashes: Vec<(L2BlockNumber, H256)>,
+        first_page: bool,
+        last_page: bool,
+    ) -> anyhow::Result<OtsSearchTransactions> {
+        let mut txs = Vec::with_capacity(tx_hashes.len());
+        let mut receipts = Vec::with_capacity(tx_hashes.len());
+        for (block_number, tx_hash) in tx_hashes {
+            // Only L2 transactions can be represented in the API format
+            let (Some(tx), Some(receipt)) = (
+                self.blockchain.get_tx_api(&tx_hash).await.ok().flatten(),
+                self.blockchain.get_tx_receipt(&tx_hash).await,
+            ) else {
+                continue;
+            };
+            let timestamp = self
+                .blockchain
+                .get_block_by_number(block_number)
+                .await
+                .map(|block| block.timestamp)
+                .unwrap_or_default();
+            txs.push(tx);
+            receipts.push(OtsTransactionReceipt { receipt, timestamp });
+        }
+
+        Ok(OtsSearchTransactions {
+            txs,
+            receipts,
+            first_page,
+            last_page,
+        })
+    }
+}
+
+/// Takes at least `page_size` transactions (if available) without splitting transactions from the
+/// same block between pages. Returns whether there are any transactions left.
+fn take_page(
+    mut tx_hashes: Peekable<impl Iterator<Item = (L2BlockNumber, H256)>>,
+    page_size: usize,
+) -> (Vec<(L2BlockNumber, H256)>, bool) {
+    let mut page: Vec<(L2BlockNumber, H256)> = Vec::new();
+    while let Some(entry) = tx_hashes.next_if(|(number, _)| {
+        page.len() < page_size || page.last().is_some_and(|(last, _)| last == number)
+    }) {
+        page.push(entry);
+    }
+    (page, tx_hashes.peek().is_some())
+}
+
+fn is_user_address(address: &Address) -> bool {
+    !address_map::is_system(address) && !address_map::is_precompile(address)
+}
+
+fn push_internal_operations(call: &DebugCall, operations: &mut Vec<OtsInternalOperation>) {
+    // Value transfers are routed through system contracts, only report calls between user contracts
+    if is_user_address(&call.from) && is_user_address(&call.to) {
+        let op_type = match call.r#type {
+            DebugCallType::Create => Some(OtsOperationType::Create),
+            DebugCallType::Call if !call.value.is_zero() => Some(OtsOperationType::Transfer),
+            DebugCallType::Call => None,
+        };
+        if let Some(op_type) = op_type {
+            operations.push(OtsInternalOperation {
+                op_type,
+                from: call.from,
+                to: call.to,
+                value: call.value,
+            });
+        }
+    }
+    for child in &call.calls {
+        push_internal_operations(child, operations);
+    }
+}
+
+fn push_traces(call: &DebugCall, depth: usize, traces: &mut Vec<OtsTrace>) {
+    traces.push(OtsTrace {
+        trace_type: match call.r#type {
+            DebugCallType::Call => OtsTraceType::Call,
+            DebugCallType::Create => OtsTraceType::Create,
+        },
+        depth,
+        from: call.from,
+        to: call.to,
+        value: call.value,
+        input: call.input.clone(),
+        output: call.output.clone(),
+    });
+    for child in &call.calls {
+        push_traces(child, depth + 1, traces);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::TransactionResult;
+    use crate::testing;
+    use zksync_types::l2::L2TxCommonData;
+    use zksync_types::{api, ExecuteTransactionCommon, H160, U64};
+
+    fn call(
+        r#type: DebugCallType,
+        from: u8,
+        to: u8,
+        value: u64,
+        calls: Vec<DebugCall>,
+    ) -> DebugCall {
+        DebugCall {
+            r#type,
+            from: H160::repeat_byte(from),
+            to: H160::repeat_byte(to),
+            value: value.into(),
+            calls,
+            ..testing::default_tx_debug_info()
+        }
+    }
+
+    async fn insert_tx(
+        node: &InMemoryNode,
+        block_number: u32,
+        nonce: u32,
+        debug: DebugCall,
+    ) -> H256 {
+        let tx_hash = H256::from_low_u64_be(u64::from(block_number) << 32 | u64::from(nonce));
+        let mut info = testing::default_tx_execution_info();
+        info.tx.common_data = ExecuteTransactionCommon::L2(L2TxCommonData {
+            initiator_address: debug.from,
+            nonce: Nonce(nonce),
+            ..Default::default()
+        });
+        node.inner
+            .write()
+            .await
+            .insert_tx_result(
+                tx_hash,
+                TransactionResult {
+                    info,
+                    receipt: api::TransactionReceipt {
+                        transaction_hash: tx_hash,
+                        block_number: U64::from(block_number),
+                        ..Default::default()
+                    },
+                    debug,
+                    ..testing::default_tx_result()
+                },
+            )
+            .await;
+        tx_hash
+    }
+
+    #[test]
+    fn test_take_page_does_not_split_blocks() {
+        let txs = [1, 2, 2, 2, 3]
+            .into_iter()
+            .map(|n| (L2BlockNumber(n), H256::repeat_byte(n as u8)))
+            .collect::<Vec<_>>();
+
+        let (page, has_more) = take_page(txs.clone().into_iter().peekable(), 2);
+        assert_eq!(page.len(), 4);
+        assert!(has_more);
+
+        let (page, has_more) = take_page(txs.into_iter().peekable(), 5);
+        assert_eq!(page.len(), 5);
+        assert!(!has_more);
+    }
+
+    #[tokio::test]
+    async fn test_internal_operations_and_contract_creator() {
+        let node = InMemoryNode::test(None);
+        let deployed = call(DebugCallType::Create, 0x2, 0x3, 0, vec![]);
+        let transfer = call(DebugCallType::Call, 0x2, 0x4, 7, vec![]);
+        let root = call(DebugCallType::Call, 0x1, 0x2, 0, vec![deployed, transfer]);
+        let tx_hash = insert_tx(&node, 1, 5, root).await;
+
+        let operations = node
+            .ots_get_internal_operations_impl(tx_hash)
+            .await
+            .unwrap();
+        assert_eq!(operations.len(), 2);
+        assert_eq!(operations[0].op_type, OtsOperationType::Create);
+        assert_eq!(operations[1].op_type, OtsOperationType::Transfer);
+        assert_eq!(operations[1].value, U256::from(7));
+
+        let traces = node.ots_trace_transaction_impl(tx_hash).await.unwrap();
+        let depths: Vec<_> = traces.iter().map(|trace| trace.depth).collect();
+        assert_eq!(depths, vec![0, 1, 1]);
+
+        let creator = node
+            .ots_get_contract_creator_impl(H160::repeat_byte(0x3))
+            .await
+            .unwrap()
+            .expect("creator should be indexed");
+        assert_eq!(creator.hash, tx_hash);
+        assert_eq!(creator.creator, H160::repeat_byte(0x2));
+
+        let found = node
+            .ots_get_transaction_by_sender_and_nonce_impl(H160::repeat_byte(0x1), 5)
+            .await
+            .unwrap();
+        assert_eq!(found, Some(tx_hash));
+        let error = node.ots_get_transaction_error_impl(tx_hash).await.unwrap();
+        assert!(error.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_address_index() {
+        let node = InMemoryNode::test(None);
+        let first = insert_tx(&node, 1, 0, call(DebugCallType::Call, 0x1, 0x2, 0, vec![])).await;
+        let second = insert_tx(
+            &node,
+            2,
+            0,
+            call(
+                DebugCallType::Call,
+                0x3,
+                0x4,
+                0,
+                vec![call(DebugCallType::Call, 0x4, 0x2, 0, vec![])],
+            ),
+        )
+        .await;
+
+        let txs = node
+            .blockchain
+            .get_address_tx_hashes(&H160::repeat_byte(0x2))
+            .await;
+        assert_eq!(
+            txs,
+            vec![(L2BlockNumber(1), first), (L2BlockNumber(2), second)]
+        );
+        assert!(node
+            .blockchain
+            .get_address_tx_hashes(&H160::repeat_byte(0x5))
+            .await
+            .is_empty());
+    }
+}
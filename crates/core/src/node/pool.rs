@@ -4,7 +4,7 @@ use futures::channel::mpsc::{channel, Receiver, Sender};
 use std::cmp::Ordering;
 use std::collections::BTreeSet;
 use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
-use zksync_types::{Transaction, H256};
+use zksync_types::{Address, Nonce, Transaction, H256};
 
 #[derive(Debug, Clone)]
 pub struct TxPool {
@@ -98,6 +98,18 @@ impl TxPool {
         guard.clear();
     }
 
+    /// Returns the nonce following the highest nonce among `initiator`'s transactions in the pool,
+    /// or `None` if the pool has no transactions from `initiator`.
+    pub fn next_nonce(&self, initiator: Address) -> Option<Nonce> {
+        let guard = self.inner.read().expect("TxPool lock is poisoned");
+        guard
+            .iter()
+            .filter(|tx| tx.transaction.initiator_account() == initiator)
+            .filter_map(|tx| tx.transaction.nonce())
+            .max()
+            .map(|nonce| Nonce(nonce.0 + 1))
+    }
+
     /// Take up to `n` continuous transactions from the pool that are all uniform in impersonation
     /// type (either all are impersonating or all non-impersonating).
     // TODO: We should distinguish ready transactions from non-ready ones. Only ready txs should be takeable.
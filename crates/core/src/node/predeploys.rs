@@ -0,0 +1,172 @@
+//! Helper contracts that real ZKsync chains have at canonical addresses (see
+//! `TestNodeConfig::predeploys`).
+
+use crate::deps::system_contracts::get_predeployed_contracts;
+use crate::node::InMemoryNode;
+use zksync_types::Address;
+
+impl InMemoryNode {
+    /// Installs the predeployed helper contracts at their canonical addresses. Requires the EVM
+    /// emulator as the helpers are EVM contracts.
+    pub async fn install_predeploys(&self) -> anyhow::Result<()> {
+        let (use_evm_interpreter, protocol_version) = {
+            let config = &self.inner.read().await.config;
+            (config.use_evm_interpreter, config.protocol_version())
+        };
+        anyhow::ensure!(
+            use_evm_interpreter,
+            "predeployed helper contracts require the EVM interpreter to be enabled"
+        );
+        let contracts = get_predeployed_contracts(protocol_version);
+        for contract in contracts {
+            tracing::debug!(address = ?contract.account_id.address(), "installing predeploy");
+            self.node_handle
+                .set_code_sync(*contract.account_id.address(), contract.bytecode)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Returns `address` if there is a contract deployed at it.
+    pub(super) async fn deployed_contract_at(
+        &self,
+        address: Address,
+    ) -> anyhow::Result<Option<Address>> {
+        let code = self.get_code_impl(address, None).await?;
+        Ok((!code.0.is_empty()).then_some(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::system_contracts::{
+        DETERMINISTIC_DEPLOYMENT_PROXY_ADDRESS, MULTICALL3_ADDRESS, PREDEPLOYED_CONTRACT_LOCATIONS,
+        TIMESTAMP_ASSERTER_ADDRESS,
+    };
+    use alloy::primitives::{Address as AlloyAddress, U256 as AlloyU256};
+    use alloy::sol_types::SolCall;
+    use anvil_zksync_common::cache::CacheConfig;
+    use anvil_zksync_config::TestNodeConfig;
+    use zksync_types::transaction_request::CallRequest;
+
+    alloy::sol! {
+        struct Call {
+            address target;
+            bytes callData;
+        }
+
+        function aggregate(Call[] calls) external payable returns (uint256 blockNumber, bytes[] returnData);
+        function getChainId() external view returns (uint256 chainid);
+    }
+
+    fn evm_node() -> InMemoryNode {
+        InMemoryNode::test_config(
+            None,
+            TestNodeConfig {
+                use_evm_interpreter: true,
+                cache_config: CacheConfig::None,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_install_predeploys_deploys_code_at_every_location() {
+        let node = evm_node();
+
+        node.install_predeploys()
+            .await
+            .expect("failed to install predeploys");
+        for (name, address, _) in &PREDEPLOYED_CONTRACT_LOCATIONS {
+            assert_eq!(
+                node.deployed_contract_at(*address).await.unwrap(),
+                Some(*address),
+                "no code for `{name}` at {address:?}"
+            );
+        }
+        assert_eq!(
+            node.get_l2_multicall3_impl().await.unwrap(),
+            Some(MULTICALL3_ADDRESS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_predeploys_requires_evm_interpreter() {
+        let node = InMemoryNode::test(None);
+
+        let err = node
+            .install_predeploys()
+            .await
+            .expect_err("predeploys were installed without the EVM interpreter");
+        assert!(err.to_string().contains("EVM interpreter"));
+        assert_eq!(node.get_l2_multicall3_impl().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_multicall3_aggregate() {
+        let node = evm_node();
+        node.install_predeploys()
+            .await
+            .expect("failed to install predeploys");
+
+        let calldata = aggregateCall {
+            calls: vec![Call {
+                target: AlloyAddress::from(MULTICALL3_ADDRESS.0),
+                callData: getChainIdCall {}.abi_encode().into(),
+            }],
+        }
+        .abi_encode();
+        let output = node
+            .call_impl(
+                CallRequest {
+                    to: Some(MULTICALL3_ADDRESS),
+                    data: Some(calldata.into()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .expect("aggregate call failed");
+
+        let result = aggregateCall::abi_decode_returns(&output.0, true)
+            .expect("failed to decode aggregate output");
+        assert_eq!(result.returnData.len(), 1);
+        let chain_id = getChainIdCall::abi_decode_returns(&result.returnData[0], true)
+            .expect("failed to decode chain id")
+            .chainid;
+        assert_eq!(chain_id, AlloyU256::from(node.chain_id().await.as_u64()));
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_deployment_proxy_is_predeployed() {
+        let node = evm_node();
+        assert_eq!(
+            node.deployed_contract_at(DETERMINISTIC_DEPLOYMENT_PROXY_ADDRESS)
+                .await
+                .unwrap(),
+            None
+        );
+
+        node.install_predeploys()
+            .await
+            .expect("failed to install predeploys");
+        assert_eq!(
+            node.deployed_contract_at(DETERMINISTIC_DEPLOYMENT_PROXY_ADDRESS)
+                .await
+                .unwrap(),
+            Some(DETERMINISTIC_DEPLOYMENT_PROXY_ADDRESS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_timestamp_asserter() {
+        let node = InMemoryNode::test(None);
+
+        let timestamp_asserter = node
+            .get_timestamp_asserter_impl()
+            .await
+            .expect("failed to get timestamp asserter");
+        assert_eq!(timestamp_asserter, Some(TIMESTAMP_ASSERTER_ADDRESS));
+    }
+}
@@ -0,0 +1,229 @@
+//! Signing on behalf of accounts managed by the node (see `TestNodeConfig::signer_accounts`).
+
+use crate::node::eth::fill_eip712_defaults;
+use crate::node::InMemoryNode;
+use alloy::dyn_abi::TypedData;
+use anyhow::Context as _;
+use zksync_types::transaction_request::{CallRequest, TransactionRequest};
+use zksync_types::web3::Bytes;
+use zksync_types::{
+    Address, K256PrivateKey, PackedEthSignature, H160, H256, MAX_L1_TRANSACTION_GAS_LIMIT, U256,
+};
+
+impl InMemoryNode {
+    /// Signs `message` prefixed with `"\x19Ethereum Signed Message:\n" + len(message)` (EIP-191).
+    pub async fn sign_impl(&self, address: Address, message: Bytes) -> anyhow::Result<Bytes> {
+        let private_key = self.signer_private_key(address).await?;
+        let signature =
+            PackedEthSignature::sign(&private_key, &message.0).context("failed to sign message")?;
+        Ok(signature.serialize_packed().into())
+    }
+
+    /// Signs EIP-712 typed data. `typed_data` can be provided either as a JSON object or as a
+    /// string containing one.
+    pub async fn sign_typed_data_impl(
+        &self,
+        address: Address,
+        typed_data: serde_json::Value,
+    ) -> anyhow::Result<Bytes> {
+        let typed_data: TypedData = match typed_data {
+            serde_json::Value::String(typed_data) => serde_json::from_str(&typed_data),
+            typed_data => serde_json::from_value(typed_data),
+        }
+        .context("invalid typed data")?;
+        let signing_hash = typed_data
+            .eip712_signing_hash()
+            .context("failed to hash typed data")?;
+
+        let private_key = self.signer_private_key(address).await?;
+        let signature = PackedEthSignature::sign_raw(&private_key, &H256(signing_hash.0))
+            .context("failed to sign typed data")?;
+        Ok(signature.serialize_packed().into())
+    }
+
+    /// Signs `tx` as a ZKsync EIP-712 (type `0x71`) transaction and returns its raw RLP-encoded
+    /// bytes, ready to be submitted with `eth_sendRawTransaction`. Missing fields are populated
+    /// with the current node values.
+    pub async fn sign_transaction_impl(&self, mut tx: CallRequest) -> anyhow::Result<Bytes> {
+        let from = tx
+            .from
+            .context("`from` is required to sign a transaction")?;
+        let private_key = self.signer_private_key(from).await?;
+        let (chain_id, l2_gas_price, gas_per_pubdata) = {
+            let reader = self.inner.read().await;
+            (
+                reader.chain_id(),
+                reader.fee_input_provider.gas_price(),
+                reader.fee_input_provider.gas_per_pubdata(),
+            )
+        };
+
+        if tx.gas.is_none() {
+            tx.gas = Some(U256::from(MAX_L1_TRANSACTION_GAS_LIMIT));
+        }
+        if tx.nonce.is_none() {
+            // Transactions that are still waiting in the pool have not bumped the nonce yet
+            let committed_nonce = self.get_transaction_count_impl(from, None).await?;
+            let pending_nonce = self
+                .pool
+                .next_nonce(from)
+                .map_or(U256::zero(), |nonce| U256::from(nonce.0));
+            tx.nonce = Some(committed_nonce.max(pending_nonce));
+        }
+        fill_eip712_defaults(&mut tx, l2_gas_price, gas_per_pubdata);
+
+        let mut tx_req = TransactionRequest::from(tx);
+        tx_req.chain_id = Some(chain_id.as_u64());
+        let signed_message = tx_req
+            .get_default_signed_message()
+            .context("failed to build transaction signing message")?;
+        let signature = PackedEthSignature::sign_raw(&private_key, &signed_message)
+            .context("failed to sign transaction")?;
+        let bytes = tx_req
+            .get_signed_bytes(&signature)
+            .context("failed to encode signed transaction")?;
+        Ok(bytes.into())
+    }
+
+    async fn signer_private_key(&self, address: Address) -> anyhow::Result<K256PrivateKey> {
+        let reader = self.inner.read().await;
+        let signer = reader
+            .config
+            .signer_accounts
+            .iter()
+            .find(|signer| H160::from_slice(signer.address().as_ref()) == address)
+            .with_context(|| format!("no private key available for account {address:?}"))?;
+        K256PrivateKey::from_bytes(H256(signer.to_bytes().0)).context("invalid signer private key")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zksync_types::transaction_request::{Eip712Meta, PaymasterParams};
+    use zksync_types::EIP_712_TX_TYPE;
+
+    async fn node_with_signer() -> (InMemoryNode, Address) {
+        let node = InMemoryNode::test(None);
+        let address = H160::from_slice(
+            node.inner.read().await.config.signer_accounts[0]
+                .address()
+                .as_ref(),
+        );
+        (node, address)
+    }
+
+    #[tokio::test]
+    async fn test_sign_recovers_to_signer() {
+        let (node, address) = node_with_signer().await;
+
+        let signature = node
+            .sign_impl(address, Bytes(b"hello".to_vec()))
+            .await
+            .expect("failed to sign message");
+        let signature = PackedEthSignature::deserialize_packed(&signature.0).unwrap();
+        let signed_bytes = PackedEthSignature::message_to_signed_bytes(b"hello");
+        assert_eq!(
+            signature.signature_recover_signer(&signed_bytes).unwrap(),
+            address
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sign_rejects_unknown_account() {
+        let (node, _) = node_with_signer().await;
+
+        let result = node
+            .sign_impl(Address::repeat_byte(0x1), Bytes(b"hello".to_vec()))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sign_typed_data_accepts_stringified_json() {
+        let (node, address) = node_with_signer().await;
+        let typed_data = serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "chainId", "type": "uint256" }
+                ],
+                "Mail": [{ "name": "contents", "type": "string" }]
+            },
+            "primaryType": "Mail",
+            "domain": { "name": "Test", "chainId": 260 },
+            "message": { "contents": "hello" }
+        });
+
+        let from_object = node
+            .sign_typed_data_impl(address, typed_data.clone())
+            .await
+            .expect("failed to sign typed data");
+        let from_string = node
+            .sign_typed_data_impl(address, serde_json::Value::String(typed_data.to_string()))
+            .await
+            .expect("failed to sign typed data");
+        assert_eq!(from_object, from_string);
+        assert_eq!(from_object.0.len(), 65);
+    }
+
+    #[tokio::test]
+    async fn test_sign_transaction_produces_eip712_tx() {
+        let (node, address) = node_with_signer().await;
+        let paymaster_params = PaymasterParams {
+            paymaster: Address::repeat_byte(0x2),
+            paymaster_input: vec![0x8c, 0x5a, 0x34, 0x45],
+        };
+
+        let raw = node
+            .sign_transaction_impl(CallRequest {
+                from: Some(address),
+                to: Some(Address::repeat_byte(0x3)),
+                eip712_meta: Some(Eip712Meta {
+                    gas_per_pubdata: U256::zero(),
+                    factory_deps: vec![],
+                    custom_signature: None,
+                    paymaster_params: Some(paymaster_params.clone()),
+                }),
+                ..Default::default()
+            })
+            .await
+            .expect("failed to sign transaction");
+        assert_eq!(raw.0[0], EIP_712_TX_TYPE);
+
+        let (tx_req, _) = TransactionRequest::from_bytes(&raw.0, node.chain_id().await).unwrap();
+        assert_eq!(tx_req.from, Some(address));
+        let eip712_meta = tx_req.eip712_meta.unwrap();
+        assert_eq!(eip712_meta.paymaster_params, Some(paymaster_params));
+    }
+
+    #[tokio::test]
+    async fn test_send_transaction_uses_pending_nonce() {
+        let (node, address) = node_with_signer().await;
+        // Keep transactions in the pool
+        node.set_immediate_sealing(false).await.unwrap();
+
+        let mut tx_hashes = Vec::new();
+        for _ in 0..2 {
+            let tx_hash = node
+                .send_transaction_impl(CallRequest {
+                    from: Some(address),
+                    to: Some(Address::repeat_byte(0x3)),
+                    value: Some(U256::from(1)),
+                    ..Default::default()
+                })
+                .await
+                .expect("failed to send transaction");
+            tx_hashes.push(tx_hash);
+        }
+        assert_ne!(tx_hashes[0], tx_hashes[1]);
+
+        let nonces = node
+            .pool
+            .drop_transactions(|tx| tx.transaction.initiator_account() == address)
+            .into_iter()
+            .map(|tx| tx.nonce().unwrap().0)
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(nonces, [0, 1].into());
+    }
+}
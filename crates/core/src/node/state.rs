@@ -2,7 +2,7 @@ use super::inner::{SerializableForkStorage, SerializableStorage};
 use super::TransactionResult;
 use serde::{Deserialize, Serialize};
 use zksync_types::api::{Block, TransactionVariant};
-use zksync_types::H256;
+use zksync_types::{ProtocolVersionId, H256};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -64,4 +64,10 @@ pub struct StateV1 {
     pub fork_storage: SerializableForkStorage,
     /// Historical states of storage at particular block hashes.
     pub historical_states: Vec<(H256, SerializableStorage)>,
+    /// Private keys of accounts managed by the node. Only dumped with `--dump-signer-accounts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer_accounts: Option<Vec<H256>>,
+    /// Protocol version the state was produced with. Absent in states dumped by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<ProtocolVersionId>,
 }
@@ -0,0 +1,430 @@
+//! Optional genesis predeploys that make paymaster flows usable out of the box (see
+//! `TestNodeConfig::testnet_paymaster`):
+//!
+//! * `TestnetPaymaster`, sponsors any transaction using the general flow and accepts any token 1:1
+//!   using the approval-based flow;
+//! * `ERC20FixedPaymaster`, sponsors transactions in exchange for one dev ERC20 token;
+//! * `MyERC20`, the dev ERC20 token accepted by the paymaster above.
+//!
+//! These are EVM contracts, hence they require the EVM emulator. They are installed at fixed
+//! addresses and funded from the first dev account.
+
+use crate::deps::system_contracts::load_builtin_evm_contract;
+use crate::node::{InMemoryNode, TxBatch};
+use alloy::primitives::{Address as AlloyAddress, U256 as AlloyU256};
+use alloy::sol_types::SolCall;
+use anvil_zksync_types::L2TxBuilder;
+use anyhow::Context as _;
+use zksync_types::{Address, Nonce, H160, U256};
+
+alloy::sol! {
+    function mint(address to, uint256 amount) external returns (bool);
+}
+
+const TESTNET_PAYMASTER_ARTIFACT: &str = "TestnetPaymaster";
+const ERC20_PAYMASTER_ARTIFACT: &str = "ERC20FixedPaymaster";
+const ERC20_TOKEN_ARTIFACT: &str = "MyERC20";
+
+/// Storage slot of `ERC20FixedPaymaster` holding the address of the accepted token.
+const ERC20_PAYMASTER_TOKEN_SLOT: U256 = U256::zero();
+/// Base token amount each paymaster is funded with from the first dev account (10 ETH).
+const PAYMASTER_FUNDING: u128 = 10 * 10u128.pow(18);
+/// Amount of dev ERC20 tokens minted to every dev account.
+const DEV_TOKEN_MINT_AMOUNT: u128 = 1_000_000 * 10u128.pow(18);
+/// Gas limit used for every funding transaction.
+const FUNDING_GAS_LIMIT: u64 = 10_000_000;
+
+/// Addresses of the testnet paymaster predeploys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TestnetPaymasters {
+    /// General-purpose paymaster returned by `zks_getTestnetPaymaster`.
+    pub paymaster: Address,
+    /// Approval-based paymaster accepting [`TestnetPaymasters::erc20_token`].
+    pub erc20_paymaster: Address,
+    /// Dev ERC20 token.
+    pub erc20_token: Address,
+}
+
+impl TestnetPaymasters {
+    pub fn new() -> Self {
+        // `0xfefe...fe01`, `0xfefe...fe02` and `0xfefe...fe03`
+        let address = |index: u8| {
+            let mut bytes = [0xfe; 20];
+            bytes[19] = index;
+            H160(bytes)
+        };
+        Self {
+            paymaster: address(1),
+            erc20_paymaster: address(2),
+            erc20_token: address(3),
+        }
+    }
+}
+
+impl Default for TestnetPaymasters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InMemoryNode {
+    /// Installs the testnet paymasters and the dev ERC20 token, funds both paymasters from the first
+    /// dev account and mints dev tokens to every dev account. Does nothing if the contracts are
+    /// already deployed (e.g. state was loaded from a dump).
+    pub async fn deploy_testnet_paymasters(&self) -> anyhow::Result<TestnetPaymasters> {
+        let addresses = TestnetPaymasters::new();
+        if self
+            .deployed_contract_at(addresses.paymaster)
+            .await?
+            .is_some()
+        {
+            return Ok(addresses);
+        }
+
+        let (use_evm_interpreter, protocol_version, dev_accounts, gas_price) = {
+            let reader = self.inner.read().await;
+            (
+                reader.config.use_evm_interpreter,
+                reader.config.protocol_version(),
+                reader
+                    .config
+                    .genesis_accounts
+                    .iter()
+                    .map(|signer| H160::from_slice(signer.address().as_ref()))
+                    .collect::<Vec<_>>(),
+                U256::from(reader.fee_input_provider.gas_price()),
+            )
+        };
+        anyhow::ensure!(
+            use_evm_interpreter,
+            "testnet paymasters require the EVM interpreter to be enabled"
+        );
+        let funder = *dev_accounts
+            .first()
+            .context("testnet paymasters require at least one dev account to be funded from")?;
+
+        for (address, artifact_name) in [
+            (addresses.erc20_token, ERC20_TOKEN_ARTIFACT),
+            (addresses.erc20_paymaster, ERC20_PAYMASTER_ARTIFACT),
+            (addresses.paymaster, TESTNET_PAYMASTER_ARTIFACT),
+        ] {
+            self.node_handle
+                .set_code_sync(
+                    address,
+                    load_builtin_evm_contract(protocol_version, artifact_name),
+                )
+                .await?;
+        }
+        self.set_storage_at(
+            addresses.erc20_paymaster,
+            ERC20_PAYMASTER_TOKEN_SLOT,
+            U256::from_big_endian(addresses.erc20_token.as_bytes()),
+        )
+        .await?;
+
+        let chain_id = self.chain_id().await;
+        let funder_nonce = self.get_transaction_count_impl(funder, None).await?;
+        let funder_tx = |nonce: u32| {
+            L2TxBuilder::new(
+                funder,
+                Nonce(nonce),
+                U256::from(FUNDING_GAS_LIMIT),
+                gas_price,
+                chain_id,
+            )
+        };
+        let mut nonces = funder_nonce.as_u32()..;
+        let mut txs = Vec::with_capacity(dev_accounts.len() + 2);
+        for (paymaster, nonce) in [addresses.paymaster, addresses.erc20_paymaster]
+            .into_iter()
+            .zip(nonces.by_ref())
+        {
+            txs.push(
+                funder_tx(nonce)
+                    .with_to(paymaster)
+                    .with_value(U256::from(PAYMASTER_FUNDING))
+                    .build_impersonated()
+                    .into(),
+            );
+        }
+        for (dev_account, nonce) in dev_accounts.iter().zip(nonces) {
+            let calldata = mintCall {
+                to: AlloyAddress::from(dev_account.0),
+                amount: AlloyU256::from(DEV_TOKEN_MINT_AMOUNT),
+            }
+            .abi_encode();
+            txs.push(
+                funder_tx(nonce)
+                    .with_to(addresses.erc20_token)
+                    .with_calldata(calldata)
+                    .build_impersonated()
+                    .into(),
+            );
+        }
+
+        let stop_impersonating_funder = self.impersonate_account(funder)?;
+        let result = self
+            .node_handle
+            .seal_block_sync(TxBatch {
+                impersonating: true,
+                txs,
+            })
+            .await;
+        if stop_impersonating_funder {
+            self.stop_impersonating_account(funder)?;
+        }
+        result.context("failed to fund testnet paymasters")?;
+
+        Ok(addresses)
+    }
+
+    /// Returns the address of the general-purpose testnet paymaster if it has been deployed.
+    pub async fn get_testnet_paymaster_impl(&self) -> anyhow::Result<Option<Address>> {
+        self.deployed_contract_at(TestnetPaymasters::new().paymaster)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anvil_zksync_common::cache::CacheConfig;
+    use anvil_zksync_config::TestNodeConfig;
+    use zksync_types::l2::L2Tx;
+    use zksync_types::transaction_request::{CallRequest, PaymasterParams};
+    use zksync_types::U64;
+
+    alloy::sol! {
+        function general(bytes input);
+        function approvalBased(address token, uint256 minAllowance, bytes innerInput);
+        function approve(address spender, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+    }
+
+    fn paymaster_node() -> InMemoryNode {
+        InMemoryNode::test_config(
+            None,
+            TestNodeConfig {
+                use_evm_interpreter: true,
+                testnet_paymaster: true,
+                cache_config: CacheConfig::None,
+                ..Default::default()
+            },
+        )
+    }
+
+    async fn dev_account(node: &InMemoryNode) -> Address {
+        H160::from_slice(
+            node.inner.read().await.config.genesis_accounts[0]
+                .address()
+                .as_ref(),
+        )
+    }
+
+    async fn token_balance(node: &InMemoryNode, token: Address, account: Address) -> U256 {
+        let output = node
+            .call_impl(
+                CallRequest {
+                    to: Some(token),
+                    data: Some(
+                        balanceOfCall {
+                            account: AlloyAddress::from(account.0),
+                        }
+                        .abi_encode()
+                        .into(),
+                    ),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .expect("balanceOf call failed");
+        U256::from_big_endian(&output.0)
+    }
+
+    async fn l2_tx(node: &InMemoryNode, from: Address, to: Address, calldata: Vec<u8>) -> L2Tx {
+        let nonce = node.get_transaction_count_impl(from, None).await.unwrap();
+        let gas_price = node.inner.read().await.fee_input_provider.gas_price();
+        L2TxBuilder::new(
+            from,
+            Nonce(nonce.as_u32()),
+            U256::from(10_000_000),
+            U256::from(gas_price),
+            node.chain_id().await,
+        )
+        .with_to(to)
+        .with_calldata(calldata)
+        .build_impersonated()
+    }
+
+    #[tokio::test]
+    async fn test_get_testnet_paymaster_is_none_without_predeploy() {
+        let node = InMemoryNode::test(None);
+
+        let paymaster = node
+            .get_testnet_paymaster_impl()
+            .await
+            .expect("failed to get testnet paymaster");
+        assert_eq!(paymaster, None);
+    }
+
+    #[tokio::test]
+    async fn test_deploy_testnet_paymasters_requires_evm_interpreter() {
+        let node = InMemoryNode::test(None);
+
+        node.deploy_testnet_paymasters()
+            .await
+            .expect_err("testnet paymasters were deployed without the EVM interpreter");
+        assert_eq!(node.get_testnet_paymaster_impl().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_deploy_testnet_paymasters_funds_predeploys() {
+        let node = paymaster_node();
+        let addresses = node
+            .deploy_testnet_paymasters()
+            .await
+            .expect("failed to deploy testnet paymasters");
+
+        for address in [
+            addresses.paymaster,
+            addresses.erc20_paymaster,
+            addresses.erc20_token,
+        ] {
+            assert_eq!(
+                node.deployed_contract_at(address).await.unwrap(),
+                Some(address),
+                "no code at {address:?}"
+            );
+        }
+        assert_eq!(
+            node.get_testnet_paymaster_impl().await.unwrap(),
+            Some(addresses.paymaster)
+        );
+        for paymaster in [addresses.paymaster, addresses.erc20_paymaster] {
+            assert_eq!(
+                node.get_balance_impl(paymaster, None).await.unwrap(),
+                U256::from(PAYMASTER_FUNDING)
+            );
+        }
+        let genesis_accounts = node.inner.read().await.config.genesis_accounts.clone();
+        for signer in genesis_accounts {
+            let account = H160::from_slice(signer.address().as_ref());
+            assert_eq!(
+                token_balance(&node, addresses.erc20_token, account).await,
+                U256::from(DEV_TOKEN_MINT_AMOUNT)
+            );
+        }
+
+        // Deploying again is a no-op
+        assert_eq!(node.deploy_testnet_paymasters().await.unwrap(), addresses);
+    }
+
+    #[tokio::test]
+    async fn test_testnet_paymaster_sponsors_transaction() {
+        let node = paymaster_node();
+        let addresses = node
+            .deploy_testnet_paymasters()
+            .await
+            .expect("failed to deploy testnet paymasters");
+
+        // Account without any funds, its fee is covered by the paymaster
+        let from = H160::repeat_byte(0x42);
+        let paymaster_balance_before = node
+            .get_balance_impl(addresses.paymaster, None)
+            .await
+            .unwrap();
+        let mut tx = l2_tx(&node, from, H160::repeat_byte(0x43), vec![]).await;
+        tx.common_data.paymaster_params = PaymasterParams {
+            paymaster: addresses.paymaster,
+            paymaster_input: generalCall {
+                input: Default::default(),
+            }
+            .abi_encode(),
+        };
+        node.impersonate_account(from).unwrap();
+        let receipts = node
+            .apply_txs([tx.into()])
+            .await
+            .expect("sponsored transaction failed");
+
+        assert_eq!(receipts[0].status, U64::from(1));
+        assert_eq!(
+            node.get_balance_impl(from, None).await.unwrap(),
+            U256::zero()
+        );
+        assert!(
+            node.get_balance_impl(addresses.paymaster, None)
+                .await
+                .unwrap()
+                < paymaster_balance_before
+        );
+    }
+
+    #[tokio::test]
+    async fn test_erc20_paymaster_charges_dev_token() {
+        let node = paymaster_node();
+        let addresses = node
+            .deploy_testnet_paymasters()
+            .await
+            .expect("failed to deploy testnet paymasters");
+        let from = dev_account(&node).await;
+        let price = U256::exp10(18);
+        node.impersonate_account(from).unwrap();
+
+        let approve_tx = l2_tx(
+            &node,
+            from,
+            addresses.erc20_token,
+            approveCall {
+                spender: AlloyAddress::from(addresses.erc20_paymaster.0),
+                amount: AlloyU256::from(10u128.pow(18)),
+            }
+            .abi_encode(),
+        )
+        .await;
+        node.apply_txs([approve_tx.into()])
+            .await
+            .expect("approve transaction failed");
+
+        let balance_before = node.get_balance_impl(from, None).await.unwrap();
+        let token_balance_before = token_balance(&node, addresses.erc20_token, from).await;
+        let mut tx = l2_tx(&node, from, H160::repeat_byte(0x43), vec![]).await;
+        tx.common_data.paymaster_params = PaymasterParams {
+            paymaster: addresses.erc20_paymaster,
+            paymaster_input: approvalBasedCall {
+                token: AlloyAddress::from(addresses.erc20_token.0),
+                minAllowance: AlloyU256::from(10u128.pow(18)),
+                innerInput: Default::default(),
+            }
+            .abi_encode(),
+        };
+        let receipts = node
+            .apply_txs([tx.into()])
+            .await
+            .expect("sponsored transaction failed");
+
+        assert_eq!(receipts[0].status, U64::from(1));
+        assert_eq!(
+            node.get_balance_impl(from, None).await.unwrap(),
+            balance_before
+        );
+        assert_eq!(
+            token_balance(&node, addresses.erc20_token, from).await,
+            token_balance_before - price
+        );
+        assert_eq!(
+            token_balance(&node, addresses.erc20_token, addresses.erc20_paymaster).await,
+            price
+        );
+    }
+
+    #[test]
+    fn test_testnet_paymaster_addresses_are_distinct() {
+        let addresses = TestnetPaymasters::new();
+
+        assert_ne!(addresses.paymaster, addresses.erc20_paymaster);
+        assert_ne!(addresses.paymaster, addresses.erc20_token);
+        assert_ne!(addresses.erc20_paymaster, addresses.erc20_token);
+    }
+}
@@ -0,0 +1,428 @@
+//! Registry of tokens known to the local chain. Tokens get there either explicitly through
+//! `anvil_registerToken` or automatically once they emit their first ERC20 `Transfer` event (this
+//! includes tokens bridged from L1 as they are minted on deposit).
+//!
+//! Also hosts `anvil_setErc20Balance`, which rewrites ERC20 balances in place after locating the
+//! balance slot of the token.
+
+use super::boojumos::BOOJUM_CALL_GAS_LIMIT;
+use crate::node::{InMemoryNode, MAX_TX_SIZE};
+use alloy::primitives::Address as AlloyAddress;
+use alloy::sol_types::SolCall;
+use anvil_zksync_types::api::TokenMetadata;
+use anyhow::Context as _;
+use indexmap::{IndexMap, IndexSet};
+use std::collections::HashMap;
+use zksync_multivm::interface::{ExecutionResult, VmExecutionResultAndLogs};
+use zksync_multivm::vm_latest::constants::ETH_CALL_GAS_LIMIT;
+use zksync_types::api::state_override::{OverrideAccount, OverrideState, StateOverride};
+use zksync_types::l2::L2Tx;
+use zksync_types::transaction_request::CallRequest;
+use zksync_types::{
+    h256_to_u256, AccountTreeId, Address, StorageKey, StorageLogKind, H160, H256, U256,
+};
+use zksync_web3_decl::types::Token;
+
+alloy::sol! {
+    function name() external view returns (string);
+    function symbol() external view returns (string);
+    function decimals() external view returns (uint8);
+    function l1Address() external view returns (address);
+    function balanceOf(address account) external view returns (uint256);
+    function totalSupply() external view returns (uint256);
+}
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct TokenRegistry {
+    /// Tokens registered with `anvil_registerToken`, in registration order.
+    registered: IndexMap<Address, Token>,
+    /// Resolved metadata of contracts that emitted a `Transfer` event. `None` means the contract
+    /// does not look like an ERC20 token.
+    detected: HashMap<Address, Option<Token>>,
+}
+
+impl InMemoryNode {
+    /// Registers a token so that it is reported by `zks_getConfirmedTokens` and
+    /// `zks_getAllAccountBalances`. Metadata that is not provided is read from the token contract.
+    pub async fn register_token_impl(
+        &self,
+        address: Address,
+        metadata: Option<TokenMetadata>,
+    ) -> anyhow::Result<Token> {
+        let metadata = metadata.unwrap_or_default();
+        let onchain = self.read_token_metadata(address).await;
+        let token =
+            Token {
+                l1_address: metadata
+                    .l1_address
+                    .or(onchain.l1_address)
+                    .unwrap_or_default(),
+                l2_address: address,
+                name: metadata.name.or(onchain.name).context(
+                    "token name was not provided and could not be read from the contract",
+                )?,
+                symbol: metadata.symbol.or(onchain.symbol).context(
+                    "token symbol was not provided and could not be read from the contract",
+                )?,
+                decimals: metadata.decimals.or(onchain.decimals).context(
+                    "token decimals were not provided and could not be read from the contract",
+                )?,
+            };
+        self.inner
+            .write()
+            .await
+            .tokens
+            .registered
+            .insert(address, token.clone());
+        Ok(token)
+    }
+
+    /// Returns all locally known tokens: registered ones first, then the ones detected from
+    /// `Transfer` events in order of their first transfer.
+    pub(super) async fn local_tokens(&self) -> Vec<Token> {
+        let emitters = self.blockchain.get_token_transfer_emitters().await;
+        let (mut tokens, unresolved) = {
+            let reader = self.inner.read().await;
+            let tokens = reader
+                .tokens
+                .registered
+                .values()
+                .cloned()
+                .collect::<Vec<_>>();
+            let unresolved = emitters
+                .iter()
+                .filter(|address| !reader.tokens.detected.contains_key(*address))
+                .copied()
+                .collect::<Vec<_>>();
+            (tokens, unresolved)
+        };
+
+        let mut resolved = Vec::with_capacity(unresolved.len());
+        for address in unresolved {
+            let metadata = self.read_token_metadata(address).await;
+            let token = match (metadata.name, metadata.symbol, metadata.decimals) {
+                (Some(name), Some(symbol), Some(decimals)) => Some(Token {
+                    l1_address: metadata.l1_address.unwrap_or_default(),
+                    l2_address: address,
+                    name,
+                    symbol,
+                    decimals,
+                }),
+                _ => None,
+            };
+            resolved.push((address, token));
+        }
+
+        let mut writer = self.inner.write().await;
+        writer.tokens.detected.extend(resolved);
+        tokens.extend(emitters.iter().filter_map(|address| {
+            if writer.tokens.registered.contains_key(address) {
+                return None;
+            }
+            writer.tokens.detected.get(address).cloned().flatten()
+        }));
+        tokens
+    }
+
+    /// Returns `account`'s balance of a locally known token as reported by its `balanceOf`.
+    pub(super) async fn local_token_balance(
+        &self,
+        token: Address,
+        account: Address,
+    ) -> Option<U256> {
+        let call = balanceOfCall {
+            account: AlloyAddress::from(account.0),
+        };
+        let balance = self.call_token::<balanceOfCall>(token, call).await?;
+        Some(U256::from_big_endian(&balance._0.to_be_bytes::<32>()))
+    }
+
+    /// Sets `account`'s balance of `token` to `amount` by writing directly into the token's storage
+    /// (Foundry's `deal`). The balance slot is located by tracing `balanceOf(account)` and probing
+    /// every slot it reads. If `adjust_total_supply` is set, `totalSupply` is updated by the
+    /// difference between the new and the old balance.
+    pub async fn set_erc20_balance_impl(
+        &self,
+        token: Address,
+        account: Address,
+        amount: U256,
+        adjust_total_supply: bool,
+    ) -> anyhow::Result<()> {
+        if self.deployed_contract_at(token).await?.is_none() {
+            anyhow::bail!("there is no contract deployed at {token:?}");
+        }
+        let balance_of = balanceOfCall {
+            account: AlloyAddress::from(account.0),
+        };
+        let (balance_slot, old_balance) = self
+            .find_token_slot(token, balance_of)
+            .await
+            .context("failed to locate the balance slot")?;
+
+        let total_supply = if adjust_total_supply {
+            let (slot, old_total_supply) = self
+                .find_token_slot(token, totalSupplyCall {})
+                .await
+                .context("failed to locate the total supply slot")?;
+            let new_total_supply = old_total_supply
+                .checked_sub(old_balance)
+                .and_then(|rest| rest.checked_add(amount))
+                .context("total supply cannot accommodate the new balance")?;
+            Some((slot, new_total_supply))
+        } else {
+            None
+        };
+
+        let token_account = AccountTreeId::new(token);
+        self.node_handle
+            .set_storage_sync(StorageKey::new(token_account, balance_slot), amount)
+            .await?;
+        if let Some((slot, total_supply)) = total_supply {
+            self.node_handle
+                .set_storage_sync(StorageKey::new(token_account, slot), total_supply)
+                .await?;
+        }
+        tracing::info!(
+            "ERC20 balance of {:?} in token {:?} has been manually set to {}",
+            account,
+            token,
+            amount
+        );
+        Ok(())
+    }
+
+    /// Locates the storage slot of `token` that holds the `uint256` returned by `call`. Returns the
+    /// slot along with its current value.
+    ///
+    /// Candidates are the token's slots read by the call, most recent first. Each candidate is
+    /// overridden with a probe value and the slot is accepted if the call then returns that probe.
+    async fn find_token_slot<C: SolCall>(
+        &self,
+        token: Address,
+        call: C,
+    ) -> anyhow::Result<(H256, U256)> {
+        let calldata = call.abi_encode();
+        let result = self.run_token_call(token, &calldata, None).await?;
+        let current = decode_uint256(&result.result)
+            .with_context(|| format!("`{}` did not return a uint256", C::SIGNATURE))?;
+
+        let candidates = result
+            .logs
+            .storage_logs
+            .iter()
+            .rev()
+            .filter(|log| log.log.kind == StorageLogKind::Read && *log.log.key.address() == token)
+            .map(|log| (*log.log.key.key(), log.log.value))
+            .collect::<IndexSet<_>>();
+        for (slot, value) in candidates {
+            let probe = H256(value.0.map(|byte| !byte));
+            let state_override = StateOverride::new(HashMap::from([(
+                token,
+                OverrideAccount {
+                    state: Some(OverrideState::StateDiff(HashMap::from([(slot, probe)]))),
+                    ..Default::default()
+                },
+            )]));
+            let probed = self
+                .run_token_call(token, &calldata, Some(state_override))
+                .await?;
+            if decode_uint256(&probed.result) == Some(h256_to_u256(probe)) {
+                return Ok((slot, current));
+            }
+        }
+        anyhow::bail!(
+            "none of the storage slots read by `{}` holds its result",
+            C::SIGNATURE
+        )
+    }
+
+    /// Executes a read-only call to `token` returning the full VM result, storage logs included.
+    async fn run_token_call(
+        &self,
+        token: Address,
+        calldata: &[u8],
+        state_override: Option<StateOverride>,
+    ) -> anyhow::Result<VmExecutionResultAndLogs> {
+        let request = CallRequest {
+            to: Some(token),
+            data: Some(calldata.to_vec().into()),
+            ..Default::default()
+        };
+        let mut tx = L2Tx::from_request(
+            request.into(),
+            MAX_TX_SIZE,
+            self.system_contracts.allow_no_target(),
+        )?;
+        tx.common_data.fee.gas_limit = if self.system_contracts.boojum.use_boojum {
+            BOOJUM_CALL_GAS_LIMIT.into()
+        } else {
+            ETH_CALL_GAS_LIMIT.into()
+        };
+        let system_contracts = self.system_contracts.contracts_for_l2_call().clone();
+        Ok(self
+            .run_l2_call(tx, system_contracts, state_override)
+            .await?)
+    }
+
+    async fn read_token_metadata(&self, address: Address) -> TokenMetadata {
+        if !matches!(self.deployed_contract_at(address).await, Ok(Some(_))) {
+            return TokenMetadata::default();
+        }
+        TokenMetadata {
+            l1_address: self
+                .call_token(address, l1AddressCall {})
+                .await
+                .map(|r| H160::from_slice(r._0.as_ref()))
+                .filter(|address| !address.is_zero()),
+            name: self.call_token(address, nameCall {}).await.map(|r| r._0),
+            symbol: self.call_token(address, symbolCall {}).await.map(|r| r._0),
+            decimals: self
+                .call_token(address, decimalsCall {})
+                .await
+                .map(|r| r._0),
+        }
+    }
+
+    async fn call_token<C: SolCall>(&self, token: Address, call: C) -> Option<C::Return> {
+        let request = CallRequest {
+            to: Some(token),
+            data: Some(call.abi_encode().into()),
+            ..Default::default()
+        };
+        let output = self.call_impl(request, None).await.ok()?;
+        C::abi_decode_returns(&output.0, true).ok()
+    }
+}
+
+/// Decodes the output of a successful call returning a single `uint256`.
+fn decode_uint256(result: &ExecutionResult) -> Option<U256> {
+    match result {
+        ExecutionResult::Success { output } if output.len() == 32 => {
+            Some(U256::from_big_endian(output))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::system_contracts::bytecode_from_slice;
+    use alloy::primitives::U256 as AlloyU256;
+    use zksync_types::web3::keccak256;
+
+    #[tokio::test]
+    async fn test_register_token_with_metadata() {
+        let node = InMemoryNode::test(None);
+        let address = Address::repeat_byte(0x1);
+
+        let token = node
+            .register_token_impl(
+                address,
+                Some(TokenMetadata {
+                    l1_address: Some(Address::repeat_byte(0x2)),
+                    name: Some("Test".to_string()),
+                    symbol: Some("TST".to_string()),
+                    decimals: Some(6),
+                }),
+            )
+            .await
+            .expect("failed to register token");
+        assert_eq!(token.l2_address, address);
+        assert_eq!(token.l1_address, Address::repeat_byte(0x2));
+
+        let tokens = node
+            .get_confirmed_tokens_impl(0, 100)
+            .await
+            .expect("failed to get confirmed tokens");
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[1].l2_address, address);
+        assert_eq!(tokens[1].symbol, "TST");
+    }
+
+    #[tokio::test]
+    async fn test_register_token_requires_metadata() {
+        let node = InMemoryNode::test(None);
+
+        let result = node
+            .register_token_impl(Address::repeat_byte(0x1), None)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_erc20_balance_requires_contract() {
+        let node = InMemoryNode::test(None);
+
+        let result = node
+            .set_erc20_balance_impl(
+                Address::repeat_byte(0x1),
+                Address::repeat_byte(0x2),
+                U256::from(100),
+                false,
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    /// Installs the minimal EVM ERC20 from `test-contracts` (balances mapping at slot 0, total
+    /// supply at slot 2) with `holder` owning the whole `supply`.
+    async fn install_test_token(node: &InMemoryNode, holder: Address, supply: U256) -> Address {
+        let token = Address::repeat_byte(0x7e);
+        let bytecode = bytecode_from_slice(
+            "MinimalERC20",
+            include_bytes!("../deps/test-contracts/MinimalERC20.json"),
+        );
+        node.set_code(token, format!("0x{}", hex::encode(bytecode)))
+            .await
+            .expect("failed to install test token");
+
+        let mut balance_key = [0u8; 64];
+        balance_key[12..32].copy_from_slice(holder.as_bytes());
+        let balance_slot = h256_to_u256(H256(keccak256(&balance_key)));
+        for (slot, value) in [(balance_slot, supply), (U256::from(2), supply)] {
+            node.set_storage_at(token, slot, value)
+                .await
+                .expect("failed to set test token storage");
+        }
+        token
+    }
+
+    #[tokio::test]
+    async fn test_set_erc20_balance_of_deployed_token() {
+        let node = InMemoryNode::test_config(
+            None,
+            anvil_zksync_config::TestNodeConfig {
+                use_evm_interpreter: true,
+                ..Default::default()
+            },
+        );
+        let holder = Address::repeat_byte(0x1);
+        let token = install_test_token(&node, holder, U256::from(1000)).await;
+        let account = Address::repeat_byte(0x2);
+        let balance_of = |account: Address| {
+            node.call_token(
+                token,
+                balanceOfCall {
+                    account: AlloyAddress::from(account.0),
+                },
+            )
+        };
+        assert_eq!(balance_of(holder).await.unwrap()._0, AlloyU256::from(1000));
+        assert!(balance_of(account).await.unwrap()._0.is_zero());
+
+        node.set_erc20_balance_impl(token, account, U256::from(12345), true)
+            .await
+            .expect("failed to set ERC20 balance");
+
+        assert_eq!(
+            balance_of(account).await.unwrap()._0,
+            AlloyU256::from(12345)
+        );
+        assert_eq!(balance_of(holder).await.unwrap()._0, AlloyU256::from(1000));
+        assert_eq!(
+            node.call_token(token, totalSupplyCall {}).await.unwrap()._0,
+            AlloyU256::from(1000 + 12345)
+        );
+    }
+}
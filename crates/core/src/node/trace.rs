@@ -0,0 +1,449 @@
+//! Parity-style (`trace_*`) tracing built on top of call trees stored for executed transactions.
+
+use crate::node::fork::ForkSource;
+use crate::node::InMemoryNode;
+use anvil_zksync_types::api::{
+    CallAction, CallOutput, CreateAction, CreateOutput, LocalizedTransactionTrace, TraceAction,
+    TraceFilter, TraceOutput, TraceResults, TraceType, TransactionTrace,
+};
+use zksync_multivm::interface::{Call, CallType};
+use zksync_types::api::{BlockId, BlockNumber, DebugCall, DebugCallType};
+use zksync_types::zk_evm_types::FarCallOpcode;
+use zksync_types::{Address, L2BlockNumber, H256};
+
+/// Maximum number of blocks a single `trace_filter` request can scan.
+const MAX_TRACE_FILTER_BLOCK_RANGE: u32 = 10_000;
+
+impl InMemoryNode {
+    pub async fn parity_trace_transaction_impl(
+        &self,
+        tx_hash: H256,
+    ) -> anyhow::Result<Option<Vec<LocalizedTransactionTrace>>> {
+        Ok(self.blockchain.get_tx_parity_traces(&tx_hash).await)
+    }
+
+    pub async fn parity_trace_block_impl(
+        &self,
+        block: BlockNumber,
+    ) -> anyhow::Result<Option<Vec<LocalizedTransactionTrace>>> {
+        let Some(tx_hashes) = self
+            .blockchain
+            .get_block_tx_hashes_by_id(BlockId::Number(block))
+            .await
+        else {
+            return Ok(None);
+        };
+
+        let mut traces = Vec::new();
+        for tx_hash in tx_hashes {
+            let tx_traces = self
+                .blockchain
+                .get_tx_parity_traces(&tx_hash)
+                .await
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unexpectedly transaction (hash={tx_hash}) belongs to a block but could not be found"
+                    )
+                })?;
+            traces.extend(tx_traces);
+        }
+        Ok(Some(traces))
+    }
+
+    pub async fn parity_replay_transaction_impl(
+        &self,
+        tx_hash: H256,
+        trace_types: Vec<String>,
+    ) -> anyhow::Result<Option<TraceResults>> {
+        if let Some(trace_type) = trace_types
+            .iter()
+            .find(|trace_type| trace_type.as_str() != "trace")
+        {
+            anyhow::bail!("trace type `{trace_type}` is not supported, only `trace` is available");
+        }
+        let Some(debug) = self.blockchain.get_tx_debug_info(&tx_hash, true).await else {
+            return Ok(None);
+        };
+        let trace = if trace_types.is_empty() {
+            None
+        } else {
+            self.blockchain
+                .get_tx_parity_traces(&tx_hash)
+                .await
+                .map(|traces| traces.into_iter().map(|trace| trace.trace).collect())
+        };
+        Ok(Some(TraceResults {
+            output: debug.output,
+            trace,
+            state_diff: None,
+            vm_trace: None,
+        }))
+    }
+
+    pub async fn parity_trace_filter_impl(
+        &self,
+        filter: TraceFilter,
+    ) -> anyhow::Result<Vec<LocalizedTransactionTrace>> {
+        // Blocks preceding the fork are not available locally, so the scan starts at the first
+        // local block
+        let first_local_block = self
+            .fork
+            .details()
+            .map_or(L2BlockNumber(0), |details| details.block_number + 1);
+        let from_block = self
+            .resolve_block_number(filter.from_block.unwrap_or(BlockNumber::Earliest))
+            .await?
+            .max(first_local_block);
+        let to_block = self
+            .resolve_block_number(filter.to_block.unwrap_or(BlockNumber::Latest))
+            .await?;
+        anyhow::ensure!(
+            from_block <= to_block,
+            "fromBlock ({from_block}) must not be greater than toBlock ({to_block})"
+        );
+        anyhow::ensure!(
+            to_block.0 - from_block.0 < MAX_TRACE_FILTER_BLOCK_RANGE,
+            "block range ({from_block}..={to_block}) is too large, at most \
+             {MAX_TRACE_FILTER_BLOCK_RANGE} blocks can be traced at once"
+        );
+
+        let mut traces = Vec::new();
+        for block_number in from_block.0..=to_block.0 {
+            let Some(tx_hashes) = self
+                .blockchain
+                .get_block_tx_hashes_by_number(L2BlockNumber(block_number))
+                .await
+            else {
+                continue;
+            };
+            for tx_hash in tx_hashes {
+                let tx_traces = self
+                    .blockchain
+                    .get_tx_parity_traces(&tx_hash)
+                    .await
+                    .unwrap_or_default();
+                traces.extend(
+                    tx_traces
+                        .into_iter()
+                        .filter(|trace| matches_filter(&trace.trace, &filter)),
+                );
+            }
+        }
+
+        Ok(traces
+            .into_iter()
+            .skip(filter.after.unwrap_or(0))
+            .take(filter.count.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn resolve_block_number(&self, block: BlockNumber) -> anyhow::Result<L2BlockNumber> {
+        match block {
+            BlockNumber::Number(number) => Ok(L2BlockNumber(number.as_u32())),
+            BlockNumber::Earliest => Ok(L2BlockNumber(0)),
+            _ => self
+                .blockchain
+                .get_block_number_by_id(BlockId::Number(block))
+                .await
+                .ok_or_else(|| anyhow::anyhow!("Block (id={block}) not found")),
+        }
+    }
+}
+
+/// Flattens a call tree into Parity traces in depth-first order. `calls` are the VM calls that
+/// `root`'s subcalls were built from (see `create_debug_output`), they are needed to tell
+/// delegate calls apart as [DebugCall] does not keep the call kind. Without them every call is
+/// reported as a plain `call`.
+pub(super) fn flatten_call_trace(
+    root: &DebugCall,
+    calls: Option<&[Call]>,
+) -> Vec<TransactionTrace> {
+    let mut traces = Vec::new();
+    push_traces(root, "call", calls, &mut Vec::new(), &mut traces);
+    traces
+}
+
+fn push_traces(
+    call: &DebugCall,
+    call_type: &str,
+    vm_calls: Option<&[Call]>,
+    trace_address: &mut Vec<usize>,
+    traces: &mut Vec<TransactionTrace>,
+) {
+    traces.push(to_transaction_trace(call, call_type, trace_address.clone()));
+    for (idx, child) in call.calls.iter().enumerate() {
+        let vm_call = vm_calls.and_then(|vm_calls| vm_calls.get(idx));
+        trace_address.push(idx);
+        push_traces(
+            child,
+            vm_call.map_or("call", parity_call_type),
+            vm_call.map(|vm_call| vm_call.calls.as_slice()),
+            trace_address,
+            traces,
+        );
+        trace_address.pop();
+    }
+}
+
+/// EraVM does not record whether a far call was static, so static calls are reported as `call`.
+fn parity_call_type(call: &Call) -> &'static str {
+    match call.r#type {
+        CallType::Call(FarCallOpcode::Delegate) => "delegatecall",
+        _ => "call",
+    }
+}
+
+fn to_transaction_trace(
+    call: &DebugCall,
+    call_type: &str,
+    trace_address: Vec<usize>,
+) -> TransactionTrace {
+    let error = call
+        .error
+        .clone()
+        .or_else(|| call.revert_reason.as_ref().map(|_| "Reverted".to_string()));
+    let (trace_type, action, result) = match call.r#type {
+        DebugCallType::Create => (
+            TraceType::Create,
+            TraceAction::Create(CreateAction {
+                from: call.from,
+                value: call.value,
+                gas: call.gas,
+                init: call.input.clone(),
+            }),
+            TraceOutput::Create(CreateOutput {
+                gas_used: call.gas_used,
+                code: call.output.clone(),
+                address: call.to,
+            }),
+        ),
+        DebugCallType::Call => (
+            TraceType::Call,
+            TraceAction::Call(CallAction {
+                from: call.from,
+                to: call.to,
+                value: call.value,
+                gas: call.gas,
+                input: call.input.clone(),
+                call_type: call_type.to_string(),
+            }),
+            TraceOutput::Call(CallOutput {
+                gas_used: call.gas_used,
+                output: call.output.clone(),
+            }),
+        ),
+    };
+    TransactionTrace {
+        action,
+        result: error.is_none().then_some(result),
+        error,
+        subtraces: call.calls.len(),
+        trace_address,
+        trace_type,
+    }
+}
+
+fn matches_filter(trace: &TransactionTrace, filter: &TraceFilter) -> bool {
+    let (from, to) = match (&trace.action, &trace.result) {
+        (TraceAction::Call(action), _) => (action.from, Some(action.to)),
+        (TraceAction::Create(action), Some(TraceOutput::Create(output))) => {
+            (action.from, Some(output.address))
+        }
+        (TraceAction::Create(action), _) => (action.from, None),
+    };
+    let matches = |addresses: &Option<Vec<Address>>, address: Option<Address>| match addresses {
+        Some(addresses) if !addresses.is_empty() => {
+            address.is_some_and(|address| addresses.contains(&address))
+        }
+        _ => true,
+    };
+    matches(&filter.from_address, Some(from)) && matches(&filter.to_address, to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::TransactionResult;
+    use crate::testing;
+    use zksync_types::{api, H160, U64};
+
+    fn call(from: u8, to: u8, calls: Vec<DebugCall>) -> DebugCall {
+        DebugCall {
+            r#type: DebugCallType::Call,
+            from: H160::repeat_byte(from),
+            to: H160::repeat_byte(to),
+            gas: 1000.into(),
+            gas_used: 100.into(),
+            value: Default::default(),
+            output: Default::default(),
+            input: Default::default(),
+            error: None,
+            revert_reason: None,
+            calls,
+        }
+    }
+
+    #[test]
+    fn test_flatten_call_trace() {
+        let mut reverted = call(0x2, 0x4, vec![]);
+        reverted.revert_reason = Some("nope".to_string());
+        let root = call(
+            0x1,
+            0x2,
+            vec![call(0x2, 0x3, vec![call(0x3, 0x5, vec![])]), reverted],
+        );
+
+        let traces = flatten_call_trace(&root, None);
+        let addresses: Vec<_> = traces.iter().map(|t| t.trace_address.clone()).collect();
+        assert_eq!(addresses, vec![vec![], vec![0], vec![0, 0], vec![1]]);
+        let subtraces: Vec<_> = traces.iter().map(|t| t.subtraces).collect();
+        assert_eq!(subtraces, vec![2, 1, 0, 0]);
+
+        assert!(traces[0].result.is_some());
+        assert_eq!(traces[3].error.as_deref(), Some("Reverted"));
+        assert!(traces[3].result.is_none());
+    }
+
+    #[test]
+    fn test_flatten_call_trace_keeps_call_types() {
+        let root = call(0x1, 0x2, vec![call(0x2, 0x3, vec![call(0x3, 0x4, vec![])])]);
+        let vm_calls = vec![Call {
+            r#type: CallType::Call(FarCallOpcode::Delegate),
+            calls: vec![Call {
+                r#type: CallType::Call(FarCallOpcode::Mimic),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+
+        let call_types: Vec<_> = flatten_call_trace(&root, Some(vm_calls.as_slice()))
+            .into_iter()
+            .map(|trace| match trace.action {
+                TraceAction::Call(action) => action.call_type,
+                TraceAction::Create(_) => panic!("unexpected create trace"),
+            })
+            .collect();
+        assert_eq!(call_types, vec!["call", "delegatecall", "call"]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_transaction_rejects_unsupported_trace_types() {
+        let node = InMemoryNode::test(None);
+        node.inner
+            .write()
+            .await
+            .insert_tx_result(H256::repeat_byte(0x1), testing::default_tx_result())
+            .await;
+
+        let results = node
+            .parity_replay_transaction_impl(H256::repeat_byte(0x1), vec!["trace".to_string()])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(results.trace.map(|trace| trace.len()), Some(2));
+
+        for trace_type in ["stateDiff", "vmTrace"] {
+            let result = node
+                .parity_replay_transaction_impl(
+                    H256::repeat_byte(0x1),
+                    vec!["trace".to_string(), trace_type.to_string()],
+                )
+                .await;
+            assert!(result.is_err(), "{trace_type} must be rejected");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trace_filter() {
+        let node = InMemoryNode::test(None);
+        let tx = api::Transaction::default();
+        let tx_hash = tx.hash;
+        let mut block = api::Block::<api::TransactionVariant>::default();
+        block.transactions.push(api::TransactionVariant::Full(tx));
+        {
+            let mut writer = node.inner.write().await;
+            writer.insert_block(H256::repeat_byte(0x1), block).await;
+            writer
+                .insert_block_hash(L2BlockNumber(0), H256::repeat_byte(0x1))
+                .await;
+            writer
+                .insert_tx_result(
+                    tx_hash,
+                    TransactionResult {
+                        receipt: api::TransactionReceipt {
+                            transaction_hash: tx_hash,
+                            block_hash: H256::repeat_byte(0x1),
+                            block_number: U64::zero(),
+                            ..Default::default()
+                        },
+                        debug: call(0x1, 0x2, vec![call(0x2, 0x3, vec![])]),
+                        ..testing::default_tx_result()
+                    },
+                )
+                .await;
+        }
+
+        let traces = node
+            .parity_trace_block_impl(BlockNumber::Latest)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[1].transaction_hash, tx_hash);
+        assert_eq!(traces[1].block_hash, H256::repeat_byte(0x1));
+
+        let traces = node
+            .parity_trace_filter_impl(TraceFilter {
+                to_address: Some(vec![H160::repeat_byte(0x3)]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].trace.trace_address, vec![0]);
+
+        let traces = node
+            .parity_trace_filter_impl(TraceFilter {
+                from_address: Some(vec![H160::repeat_byte(0x1)]),
+                to_address: Some(vec![H160::repeat_byte(0x3)]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(traces.is_empty());
+
+        let traces = node
+            .parity_trace_filter_impl(TraceFilter {
+                after: Some(1),
+                count: Some(5),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(traces.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_trace_filter_rejects_large_ranges() {
+        let node = InMemoryNode::test(None);
+
+        let result = node
+            .parity_trace_filter_impl(TraceFilter {
+                from_block: Some(BlockNumber::Number(0.into())),
+                to_block: Some(BlockNumber::Number(MAX_TRACE_FILTER_BLOCK_RANGE.into())),
+                ..Default::default()
+            })
+            .await;
+        assert!(result.is_err());
+
+        let traces = node
+            .parity_trace_filter_impl(TraceFilter {
+                from_block: Some(BlockNumber::Number(1.into())),
+                to_block: Some(BlockNumber::Number(MAX_TRACE_FILTER_BLOCK_RANGE.into())),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(traces.is_empty());
+    }
+}
@@ -1,2 +1,3 @@
 pub(super) mod call_error;
 pub mod decoder;
+pub(super) mod struct_log;
@@ -0,0 +1,573 @@
+//! Struct-log (geth's default tracer format) tracer recording every executed EraVM instruction
+//! and, for contracts running in the EVM interpreter, every interpreted EVM opcode.
+
+use anvil_zksync_common::address_map;
+use anvil_zksync_types::api::{StructLog, StructLogConfig};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use zksync_multivm::interface::storage::{StoragePtr, WriteStorage};
+use zksync_multivm::interface::tracer::VmExecutionStopReason;
+use zksync_multivm::tracers::old::OldTracers;
+use zksync_multivm::{
+    tracers::dynamic::vm_1_5_2::DynTracer,
+    vm_latest::{BootloaderState, HistoryMode, SimpleMemory, VmTracer, ZkSyncVmState},
+    zk_evm_latest::{
+        aux_structures::MemoryPage,
+        tracing::{BeforeExecutionData, VmLocalStateData},
+        zkevm_opcode_defs::{LogOpcode, Opcode, UMAOpcode},
+    },
+    IntoOldVmTracer,
+};
+use zksync_types::{u256_to_h256, AccountTreeId, Address, StorageKey, H256, U256};
+
+/// Shared handle the tracer writes the captured logs to once VM execution finishes.
+pub type StructLogsResult = Arc<RwLock<Vec<StructLog>>>;
+
+/// Heap offset of the debug slot used by the EVM emulator (it skips 32 words of scratch space).
+const EVM_DEBUG_SLOT_OFFSET: u64 = 32 * 32;
+/// Marker the EVM emulator writes to the debug slot right before interpreting an opcode. The
+/// opcode's context is written to the following words beforehand, see [`EvmOpcodeHook`].
+const EVM_OPCODE_HOOK_MARKER: U256 = U256([
+    0xdebdebdebdebde0c,
+    0xdebdebdebdebdebd,
+    0xdebdebdebdebdebd,
+    0x00debdebdebdebde,
+]);
+/// Maximum number of heap words captured per EraVM instruction when memory is enabled.
+const MAX_MEMORY_WORDS: u32 = 1024;
+
+/// Context of an interpreted EVM opcode as reported by the EVM emulator. Each field occupies one
+/// word in the emulator's heap right after the debug slot.
+struct EvmOpcodeHook {
+    ip: u64,
+    opcode: u8,
+    gas_left: u64,
+    /// Heap offset of the bottom of the EVM stack.
+    stack_base: u64,
+    /// Heap offset of the top of the EVM stack.
+    stack_head: u64,
+    /// Heap offset where EVM memory starts.
+    memory_base: u64,
+    /// Size of EVM memory in bytes.
+    memory_size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructLogTracer {
+    config: StructLogConfig,
+    logs: Vec<StructLog>,
+    result: StructLogsResult,
+    /// Call stack depth of the first recorded instruction, reported depths are relative to it.
+    base_depth: Option<usize>,
+    /// Call stack depths of frames that are known to run the EVM interpreter.
+    evm_frames: Vec<usize>,
+    /// Storage slots accessed so far, per contract.
+    storage: HashMap<Address, BTreeMap<H256, H256>>,
+}
+
+impl StructLogTracer {
+    pub fn new(config: StructLogConfig, result: StructLogsResult) -> Self {
+        Self {
+            config,
+            logs: vec![],
+            result,
+            base_depth: None,
+            evm_frames: vec![],
+            storage: HashMap::new(),
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.config.limit != 0 && self.logs.len() >= self.config.limit
+    }
+
+    fn depth(&mut self, depth: usize) -> u64 {
+        let base_depth = *self.base_depth.get_or_insert(depth);
+        (depth.saturating_sub(base_depth) + 1) as u64
+    }
+
+    fn storage_snapshot(&self, address: &Address) -> Option<BTreeMap<H256, H256>> {
+        (!self.config.disable_storage)
+            .then(|| self.storage.get(address).cloned().unwrap_or_default())
+    }
+
+    fn record_storage_access<S: WriteStorage>(
+        &mut self,
+        address: Address,
+        opcode: LogOpcode,
+        data: &BeforeExecutionData,
+        storage: &StoragePtr<S>,
+    ) {
+        let key = u256_to_h256(data.src0_value.value);
+        let value = match opcode {
+            LogOpcode::StorageWrite => u256_to_h256(data.src1_value.value),
+            _ => storage
+                .borrow_mut()
+                .read_value(&StorageKey::new(AccountTreeId::new(address), key)),
+        };
+        self.storage.entry(address).or_default().insert(key, value);
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for StructLogTracer {
+    fn before_execution(
+        &mut self,
+        state: VmLocalStateData<'_>,
+        data: BeforeExecutionData,
+        memory: &SimpleMemory<H>,
+        storage: StoragePtr<S>,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        let callstack = &state.vm_local_state.callstack;
+        let frame = &callstack.current;
+        let address = frame.this_address;
+        if address_map::is_system(&address) || address_map::is_precompile(&address) {
+            return;
+        }
+        let depth = callstack.depth();
+        self.evm_frames.retain(|evm_depth| *evm_depth <= depth);
+        let is_evm_frame = self.evm_frames.last() == Some(&depth);
+        let heap_page = heap_page_from_base(frame.base_memory_page);
+        let opcode = data.opcode.variant.opcode;
+
+        if let Opcode::Log(log_opcode @ (LogOpcode::StorageRead | LogOpcode::StorageWrite)) = opcode
+        {
+            if !self.config.disable_storage {
+                self.record_storage_access(address, log_opcode, &data, &storage);
+                // Storage of the EVM opcode that triggered the access is updated retroactively
+                if is_evm_frame {
+                    let storage = self.storage_snapshot(&address);
+                    if let Some(log) = self.logs.last_mut() {
+                        log.storage = storage;
+                    }
+                }
+            }
+        }
+
+        if let Opcode::UMA(UMAOpcode::HeapWrite) = opcode {
+            if data.src0_value.value == U256::from(EVM_DEBUG_SLOT_OFFSET)
+                && data.src1_value.value == EVM_OPCODE_HOOK_MARKER
+            {
+                if !is_evm_frame {
+                    self.evm_frames.push(depth);
+                }
+                let hook = EvmOpcodeHook::load(memory, heap_page);
+                let log = StructLog {
+                    pc: hook.ip,
+                    op: evm_opcode_name(hook.opcode).to_string(),
+                    gas: hook.gas_left,
+                    gas_cost: 0,
+                    depth: self.depth(depth),
+                    stack: (!self.config.disable_stack).then(|| hook.stack(memory, heap_page)),
+                    memory: self
+                        .config
+                        .enable_memory
+                        .then(|| hook.memory(memory, heap_page)),
+                    storage: matches!(hook.opcode, 0x54 | 0x55)
+                        .then(|| self.storage_snapshot(&address))
+                        .flatten(),
+                };
+                self.logs.push(log);
+                return;
+            }
+        }
+
+        // Instructions of the EVM interpreter itself are not interesting, interpreted opcodes are
+        // reported via the hook above.
+        if is_evm_frame {
+            return;
+        }
+        let log = StructLog {
+            pc: frame.pc as u64,
+            op: era_opcode_name(&opcode),
+            gas: frame.ergs_remaining as u64,
+            gas_cost: 0,
+            depth: self.depth(depth),
+            stack: (!self.config.disable_stack).then(|| {
+                state
+                    .vm_local_state
+                    .registers
+                    .iter()
+                    .map(|register| register.value)
+                    .collect()
+            }),
+            memory: self.config.enable_memory.then(|| {
+                let words = frame.heap_bound.div_ceil(32).min(MAX_MEMORY_WORDS);
+                (0..words)
+                    .map(|slot| {
+                        word_to_hex(memory.read_slot(heap_page as usize, slot as usize).value)
+                    })
+                    .collect()
+            }),
+            storage: matches!(
+                opcode,
+                Opcode::Log(LogOpcode::StorageRead | LogOpcode::StorageWrite)
+            )
+            .then(|| self.storage_snapshot(&address))
+            .flatten(),
+        };
+        self.logs.push(log);
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for StructLogTracer {
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        let mut logs = std::mem::take(&mut self.logs);
+        fill_gas_costs(&mut logs);
+        *self.result.write().unwrap() = logs;
+    }
+}
+
+impl EvmOpcodeHook {
+    fn load<H: HistoryMode>(memory: &SimpleMemory<H>, heap_page: u32) -> Self {
+        let first_slot = (EVM_DEBUG_SLOT_OFFSET / 32) as usize + 1;
+        let word = |idx: usize| memory.read_slot(heap_page as usize, first_slot + idx).value;
+        Self {
+            ip: word(0).low_u64(),
+            opcode: word(1).low_u32() as u8,
+            gas_left: word(2).low_u64(),
+            stack_base: word(3).low_u64(),
+            stack_head: word(4).low_u64(),
+            memory_base: word(5).low_u64(),
+            memory_size: word(6).low_u64(),
+        }
+    }
+
+    fn stack<H: HistoryMode>(&self, memory: &SimpleMemory<H>, heap_page: u32) -> Vec<U256> {
+        if self.stack_head < self.stack_base {
+            return vec![];
+        }
+        (self.stack_base..=self.stack_head)
+            .step_by(32)
+            .map(|offset| read_word(memory, heap_page, offset))
+            .collect()
+    }
+
+    fn memory<H: HistoryMode>(&self, memory: &SimpleMemory<H>, heap_page: u32) -> Vec<String> {
+        let words = self.memory_size.div_ceil(32).min(MAX_MEMORY_WORDS as u64);
+        (0..words)
+            .map(|idx| word_to_hex(read_word(memory, heap_page, self.memory_base + idx * 32)))
+            .collect()
+    }
+}
+
+/// Reads a (possibly unaligned) 32-byte word from the heap.
+fn read_word<H: HistoryMode>(memory: &SimpleMemory<H>, heap_page: u32, offset: u64) -> U256 {
+    let slot = (offset / 32) as usize;
+    let shift = (offset % 32) as usize * 8;
+    let high = memory.read_slot(heap_page as usize, slot).value;
+    if shift == 0 {
+        return high;
+    }
+    let low = memory.read_slot(heap_page as usize, slot + 1).value;
+    (high << shift) | (low >> (256 - shift))
+}
+
+/// Mirrors `zk_evm`: every frame owns consecutive pages starting at its base page, heap is the
+/// third one.
+fn heap_page_from_base(base: MemoryPage) -> u32 {
+    base.0 + 2
+}
+
+fn word_to_hex(word: U256) -> String {
+    let mut bytes = [0u8; 32];
+    word.to_big_endian(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Gas cost of a step is the difference between its remaining gas and the remaining gas of the
+/// next step in the same frame. Steps that end their frame are reported with zero cost.
+fn fill_gas_costs(logs: &mut [StructLog]) {
+    for idx in 0..logs.len() {
+        let depth = logs[idx].depth;
+        let next_gas = logs[idx + 1..]
+            .iter()
+            .take_while(|log| log.depth >= depth)
+            .find(|log| log.depth == depth)
+            .map(|log| log.gas);
+        if let Some(next_gas) = next_gas {
+            logs[idx].gas_cost = logs[idx].gas.saturating_sub(next_gas);
+        }
+    }
+}
+
+/// Formats EraVM opcode as an upper-case mnemonic, e.g. `ADD`, `BINOP.XOR` or `LOG.STORAGEREAD`.
+fn era_opcode_name(opcode: &Opcode) -> String {
+    let name = format!("{opcode:?}");
+    let name = match name.split_once('(') {
+        Some((group, variant)) => {
+            let variant = variant.trim_end_matches(')').trim_end_matches("Opcode");
+            if variant.is_empty() || variant == group {
+                group.to_string()
+            } else {
+                format!("{group}.{variant}")
+            }
+        }
+        None => name,
+    };
+    name.to_uppercase()
+}
+
+fn evm_opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        0x00 => "STOP",
+        0x01 => "ADD",
+        0x02 => "MUL",
+        0x03 => "SUB",
+        0x04 => "DIV",
+        0x05 => "SDIV",
+        0x06 => "MOD",
+        0x07 => "SMOD",
+        0x08 => "ADDMOD",
+        0x09 => "MULMOD",
+        0x0a => "EXP",
+        0x0b => "SIGNEXTEND",
+        0x10 => "LT",
+        0x11 => "GT",
+        0x12 => "SLT",
+        0x13 => "SGT",
+        0x14 => "EQ",
+        0x15 => "ISZERO",
+        0x16 => "AND",
+        0x17 => "OR",
+        0x18 => "XOR",
+        0x19 => "NOT",
+        0x1a => "BYTE",
+        0x1b => "SHL",
+        0x1c => "SHR",
+        0x1d => "SAR",
+        0x20 => "KECCAK256",
+        0x30 => "ADDRESS",
+        0x31 => "BALANCE",
+        0x32 => "ORIGIN",
+        0x33 => "CALLER",
+        0x34 => "CALLVALUE",
+        0x35 => "CALLDATALOAD",
+        0x36 => "CALLDATASIZE",
+        0x37 => "CALLDATACOPY",
+        0x38 => "CODESIZE",
+        0x39 => "CODECOPY",
+        0x3a => "GASPRICE",
+        0x3b => "EXTCODESIZE",
+        0x3c => "EXTCODECOPY",
+        0x3d => "RETURNDATASIZE",
+        0x3e => "RETURNDATACOPY",
+        0x3f => "EXTCODEHASH",
+        0x40 => "BLOCKHASH",
+        0x41 => "COINBASE",
+        0x42 => "TIMESTAMP",
+        0x43 => "NUMBER",
+        0x44 => "PREVRANDAO",
+        0x45 => "GASLIMIT",
+        0x46 => "CHAINID",
+        0x47 => "SELFBALANCE",
+        0x48 => "BASEFEE",
+        0x49 => "BLOBHASH",
+        0x4a => "BLOBBASEFEE",
+        0x50 => "POP",
+        0x51 => "MLOAD",
+        0x52 => "MSTORE",
+        0x53 => "MSTORE8",
+        0x54 => "SLOAD",
+        0x55 => "SSTORE",
+        0x56 => "JUMP",
+        0x57 => "JUMPI",
+        0x58 => "PC",
+        0x59 => "MSIZE",
+        0x5a => "GAS",
+        0x5b => "JUMPDEST",
+        0x5c => "TLOAD",
+        0x5d => "TSTORE",
+        0x5e => "MCOPY",
+        0x5f => "PUSH0",
+        0x60..=0x7f => PUSH_NAMES[(opcode - 0x60) as usize],
+        0x80..=0x8f => DUP_NAMES[(opcode - 0x80) as usize],
+        0x90..=0x9f => SWAP_NAMES[(opcode - 0x90) as usize],
+        0xa0..=0xa4 => LOG_NAMES[(opcode - 0xa0) as usize],
+        0xf0 => "CREATE",
+        0xf1 => "CALL",
+        0xf2 => "CALLCODE",
+        0xf3 => "RETURN",
+        0xf4 => "DELEGATECALL",
+        0xf5 => "CREATE2",
+        0xfa => "STATICCALL",
+        0xfd => "REVERT",
+        0xfe => "INVALID",
+        0xff => "SELFDESTRUCT",
+        _ => "UNKNOWN",
+    }
+}
+
+const PUSH_NAMES: [&str; 32] = [
+    "PUSH1", "PUSH2", "PUSH3", "PUSH4", "PUSH5", "PUSH6", "PUSH7", "PUSH8", "PUSH9", "PUSH10",
+    "PUSH11", "PUSH12", "PUSH13", "PUSH14", "PUSH15", "PUSH16", "PUSH17", "PUSH18", "PUSH19",
+    "PUSH20", "PUSH21", "PUSH22", "PUSH23", "PUSH24", "PUSH25", "PUSH26", "PUSH27", "PUSH28",
+    "PUSH29", "PUSH30", "PUSH31", "PUSH32",
+];
+const DUP_NAMES: [&str; 16] = [
+    "DUP1", "DUP2", "DUP3", "DUP4", "DUP5", "DUP6", "DUP7", "DUP8", "DUP9", "DUP10", "DUP11",
+    "DUP12", "DUP13", "DUP14", "DUP15", "DUP16",
+];
+const SWAP_NAMES: [&str; 16] = [
+    "SWAP1", "SWAP2", "SWAP3", "SWAP4", "SWAP5", "SWAP6", "SWAP7", "SWAP8", "SWAP9", "SWAP10",
+    "SWAP11", "SWAP12", "SWAP13", "SWAP14", "SWAP15", "SWAP16",
+];
+const LOG_NAMES: [&str; 5] = ["LOG0", "LOG1", "LOG2", "LOG3", "LOG4"];
+
+//
+// The rest of the file contains stub tracer implementations for older VM versions.
+// Reasoning: `StructLogTracer` needs to implement `MultiVmTracer` to be compatible with era
+// abstractions such as `BatchExecutor` and `BatchExecutorFactory`.
+//
+
+impl<S, H: zksync_multivm::vm_1_4_1::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_4_1::DynTracer<
+        S,
+        zksync_multivm::vm_1_4_1::SimpleMemory<H>,
+    > for StructLogTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_1_4_1::HistoryMode>
+    zksync_multivm::vm_1_4_1::VmTracer<S, H> for StructLogTracer
+{
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut zksync_multivm::vm_1_4_1::ZkSyncVmState<S, H>,
+        _bootloader_state: &zksync_multivm::vm_1_4_1::BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        todo!()
+    }
+}
+
+impl<S, H: zksync_multivm::vm_1_4_2::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_4_1::DynTracer<
+        S,
+        zksync_multivm::vm_1_4_2::SimpleMemory<H>,
+    > for StructLogTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_1_4_2::HistoryMode>
+    zksync_multivm::vm_1_4_2::VmTracer<S, H> for StructLogTracer
+{
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut zksync_multivm::vm_1_4_2::ZkSyncVmState<S, H>,
+        _bootloader_state: &zksync_multivm::vm_1_4_2::BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        todo!()
+    }
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_boojum_integration::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_4_0::DynTracer<
+        S,
+        zksync_multivm::vm_boojum_integration::SimpleMemory<H>,
+    > for StructLogTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_boojum_integration::HistoryMode>
+    zksync_multivm::vm_boojum_integration::VmTracer<S, H> for StructLogTracer
+{
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut zksync_multivm::vm_boojum_integration::ZkSyncVmState<S, H>,
+        _bootloader_state: &zksync_multivm::vm_boojum_integration::BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        todo!()
+    }
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_refunds_enhancement::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_3_3::DynTracer<
+        S,
+        zksync_multivm::vm_refunds_enhancement::SimpleMemory<H>,
+    > for StructLogTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_refunds_enhancement::HistoryMode>
+    zksync_multivm::vm_refunds_enhancement::VmTracer<S, H> for StructLogTracer
+{
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut zksync_multivm::vm_refunds_enhancement::ZkSyncVmState<S, H>,
+        _bootloader_state: &zksync_multivm::vm_refunds_enhancement::BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        todo!()
+    }
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_virtual_blocks::HistoryMode>
+    zksync_multivm::tracers::dynamic::vm_1_3_3::DynTracer<
+        S,
+        zksync_multivm::vm_virtual_blocks::SimpleMemory<H>,
+    > for StructLogTracer
+{
+}
+
+impl<H: zksync_multivm::vm_virtual_blocks::HistoryMode>
+    zksync_multivm::vm_virtual_blocks::ExecutionEndTracer<H> for StructLogTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_virtual_blocks::HistoryMode>
+    zksync_multivm::vm_virtual_blocks::ExecutionProcessing<S, H> for StructLogTracer
+{
+}
+
+impl<S: WriteStorage, H: zksync_multivm::vm_virtual_blocks::HistoryMode>
+    zksync_multivm::vm_virtual_blocks::VmTracer<S, H> for StructLogTracer
+{
+}
+
+impl IntoOldVmTracer for StructLogTracer {
+    fn old_tracer(&self) -> OldTracers {
+        todo!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(depth: u64, gas: u64) -> StructLog {
+        StructLog {
+            depth,
+            gas,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fill_gas_costs() {
+        let mut logs = vec![log(1, 100), log(2, 90), log(2, 85), log(1, 60), log(1, 58)];
+        fill_gas_costs(&mut logs);
+        let costs: Vec<_> = logs.iter().map(|log| log.gas_cost).collect();
+        assert_eq!(costs, vec![40, 5, 0, 2, 0]);
+    }
+
+    #[test]
+    fn test_evm_opcode_names() {
+        assert_eq!(evm_opcode_name(0x60), "PUSH1");
+        assert_eq!(evm_opcode_name(0x7f), "PUSH32");
+        assert_eq!(evm_opcode_name(0x8f), "DUP16");
+        assert_eq!(evm_opcode_name(0x9a), "SWAP11");
+        assert_eq!(evm_opcode_name(0xa4), "LOG4");
+        assert_eq!(evm_opcode_name(0x55), "SSTORE");
+        assert_eq!(evm_opcode_name(0x0c), "UNKNOWN");
+    }
+}
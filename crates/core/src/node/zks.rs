@@ -1,6 +1,7 @@
+use crate::deps::system_contracts::{MULTICALL3_ADDRESS, TIMESTAMP_ASSERTER_ADDRESS};
 use crate::node::InMemoryNode;
 use anyhow::Context;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use zksync_error::anvil_zksync::node::AnvilNodeResult;
 use zksync_mini_merkle_tree::MiniMerkleTree;
 use zksync_types::api;
@@ -71,41 +72,95 @@ impl InMemoryNode {
             }))
     }
 
+    /// Returns known tokens. Without a fork ETH is followed by locally known tokens (see
+    /// [`InMemoryNode::local_tokens`]); in fork mode local tokens are listed first and followed by
+    /// the forked network's tokens.
     pub async fn get_confirmed_tokens_impl(
         &self,
         from: u32,
         limit: u8,
     ) -> anyhow::Result<Vec<zksync_web3_decl::types::Token>> {
-        Ok(self
+        let local_tokens = self.local_tokens().await;
+        self.confirmed_tokens(&local_tokens, from, limit).await
+    }
+
+    /// Same as [`InMemoryNode::get_confirmed_tokens_impl`] but reuses already listed local tokens.
+    async fn confirmed_tokens(
+        &self,
+        local_tokens: &[zksync_web3_decl::types::Token],
+        from: u32,
+        limit: u8,
+    ) -> anyhow::Result<Vec<zksync_web3_decl::types::Token>> {
+        let from = from as usize;
+        let limit = limit as usize;
+        let mut tokens = local_tokens
+            .iter()
+            .skip(from)
+            .take(limit)
+            .cloned()
+            .collect::<Vec<_>>();
+        let remaining = limit - tokens.len();
+        if remaining == 0 {
+            return Ok(tokens);
+        }
+
+        let fork_from = from.saturating_sub(local_tokens.len());
+        match self
             .fork
-            .get_confirmed_tokens(from, limit)
+            .get_confirmed_tokens(fork_from as u32, remaining as u8)
             .await?
-            .unwrap_or(vec![zksync_web3_decl::types::Token {
+        {
+            Some(fork_tokens) => {
+                tokens.extend(fork_tokens.into_iter().filter(|token| {
+                    !local_tokens
+                        .iter()
+                        .any(|local| local.l2_address == token.l2_address)
+                }));
+                Ok(tokens)
+            }
+            None => Ok(std::iter::once(zksync_web3_decl::types::Token {
                 l1_address: Address::zero(),
                 l2_address: L2_BASE_TOKEN_ADDRESS,
                 name: "Ether".to_string(),
                 symbol: "ETH".to_string(),
                 decimals: 18,
-            }]))
+            })
+            .chain(local_tokens.iter().cloned())
+            .skip(from)
+            .take(limit)
+            .collect()),
+        }
     }
 
     pub async fn get_all_account_balances_impl(
         &self,
         address: Address,
     ) -> Result<HashMap<Address, U256>, Web3Error> {
-        let tokens = self.get_confirmed_tokens_impl(0, 100).await?;
+        let local_tokens = self.local_tokens().await;
+        let tokens = self.confirmed_tokens(&local_tokens, 0, 100).await?;
+        let local_tokens = local_tokens
+            .into_iter()
+            .map(|token| token.l2_address)
+            .collect::<HashSet<_>>();
 
         let balances = {
             let mut balances = HashMap::new();
             for token in tokens {
-                // TODO: Use StorageKeyLayout once boojumos can lookup other tokens
-                let balance_key = storage_key_for_standard_token_balance(
-                    AccountTreeId::new(token.l2_address),
-                    &address,
-                );
-                let balance = self.storage.read_value_alt(&balance_key).await?;
+                // Local tokens are arbitrary ERC20 contracts, so their storage layout is unknown
+                let balance = if local_tokens.contains(&token.l2_address) {
+                    self.local_token_balance(token.l2_address, address)
+                        .await
+                        .unwrap_or_default()
+                } else {
+                    // TODO: Use StorageKeyLayout once boojumos can lookup other tokens
+                    let balance_key = storage_key_for_standard_token_balance(
+                        AccountTreeId::new(token.l2_address),
+                        &address,
+                    );
+                    h256_to_u256(self.storage.read_value_alt(&balance_key).await?)
+                };
                 if !balance.is_zero() {
-                    balances.insert(token.l2_address, h256_to_u256(balance));
+                    balances.insert(token.l2_address, balance);
                 }
             }
             balances
@@ -162,6 +217,14 @@ impl InMemoryNode {
         Ok(H160::from_low_u64_be(1))
     }
 
+    pub async fn get_timestamp_asserter_impl(&self) -> anyhow::Result<Option<Address>> {
+        self.deployed_contract_at(TIMESTAMP_ASSERTER_ADDRESS).await
+    }
+
+    pub async fn get_l2_multicall3_impl(&self) -> anyhow::Result<Option<Address>> {
+        self.deployed_contract_at(MULTICALL3_ADDRESS).await
+    }
+
     pub async fn get_l2_to_l1_log_proof_impl(
         &self,
         tx_hash: H256,
@@ -298,15 +361,13 @@ mod tests {
                 .insert_tx_result(
                     H256::repeat_byte(0x1),
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: api::TransactionReceipt {
                             logs: vec![],
                             gas_used: Some(U256::from(10_000)),
                             effective_gas_price: Some(U256::from(1_000_000_000)),
                             ..Default::default()
                         },
-                        debug: testing::default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
@@ -576,15 +637,13 @@ mod tests {
                 .insert_tx_result(
                     txn.hash,
                     TransactionResult {
-                        info: testing::default_tx_execution_info(),
-                        new_bytecodes: vec![],
                         receipt: api::TransactionReceipt {
                             logs: vec![],
                             gas_used: Some(U256::from(10_000)),
                             effective_gas_price: Some(U256::from(1_000_000_000)),
                             ..Default::default()
                         },
-                        debug: testing::default_tx_debug_info(),
+                        ..testing::default_tx_result()
                     },
                 )
                 .await;
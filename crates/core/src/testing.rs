@@ -5,7 +5,7 @@
 
 #![cfg(test)]
 
-use crate::node::{InMemoryNode, TxBatch, TxExecutionInfo};
+use crate::node::{InMemoryNode, TransactionResult, TxBatch, TxExecutionInfo};
 
 use anvil_zksync_config::constants::DEFAULT_ACCOUNT_BALANCE;
 use anvil_zksync_types::L2TxBuilder;
@@ -743,6 +743,21 @@ pub fn default_tx_debug_info() -> DebugCall {
     }
 }
 
+/// Returns a default instance for a successful [TransactionResult]
+pub fn default_tx_result() -> TransactionResult {
+    TransactionResult {
+        info: default_tx_execution_info(),
+        new_bytecodes: vec![],
+        receipt: Default::default(),
+        debug: default_tx_debug_info(),
+        console_logs: vec![],
+        gas_profile: None,
+        struct_logs: vec![],
+        auto_funded: None,
+        flat_traces: vec![],
+    }
+}
+
 /// Asserts that two instances of [BridgeAddresses] are equal
 pub fn assert_bridge_addresses_eq(
     expected_bridge_addresses: &BridgeAddresses,
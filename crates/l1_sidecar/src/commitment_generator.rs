@@ -200,12 +200,14 @@ impl CommitmentGenerator {
 mod tests {
     use super::*;
     use anvil_zksync_core::filters::LogFilter;
+    use anvil_zksync_core::node::gas_profile::FoldedStacks;
+    use anvil_zksync_types::api::{LocalizedTransactionTrace, StructLogConfig, StructLogTrace};
     use async_trait::async_trait;
     use zksync_types::api::{
         Block, BlockDetails, BlockId, DebugCall, Log, Transaction, TransactionDetails,
         TransactionReceipt, TransactionVariant,
     };
-    use zksync_types::{L2BlockNumber, ProtocolVersionId};
+    use zksync_types::{L2BlockNumber, Nonce, ProtocolVersionId, U256};
 
     // TODO: Consider moving to a separate testing crate
     #[derive(Clone, Debug)]
@@ -319,6 +321,33 @@ mod tests {
             unimplemented!()
         }
 
+        async fn get_tx_console_logs(&self, _tx_hash: &H256) -> Option<Vec<String>> {
+            unimplemented!()
+        }
+
+        async fn get_tx_gas_profile(&self, _tx_hash: &H256) -> Option<FoldedStacks> {
+            unimplemented!()
+        }
+
+        async fn get_tx_auto_funded(&self, _tx_hash: &H256) -> Option<U256> {
+            unimplemented!()
+        }
+
+        async fn get_tx_struct_log_trace(
+            &self,
+            _tx_hash: &H256,
+            _config: StructLogConfig,
+        ) -> Option<StructLogTrace> {
+            unimplemented!()
+        }
+
+        async fn get_tx_parity_traces(
+            &self,
+            _tx_hash: &H256,
+        ) -> Option<Vec<LocalizedTransactionTrace>> {
+            unimplemented!()
+        }
+
         async fn get_tx_api(&self, _tx_hash: &H256) -> anyhow::Result<Option<Transaction>> {
             unimplemented!()
         }
@@ -338,6 +367,26 @@ mod tests {
             unimplemented!()
         }
 
+        async fn get_address_tx_hashes(&self, _address: &Address) -> Vec<(L2BlockNumber, H256)> {
+            unimplemented!()
+        }
+
+        async fn get_tx_hash_by_sender_and_nonce(
+            &self,
+            _sender: &Address,
+            _nonce: Nonce,
+        ) -> Option<H256> {
+            unimplemented!()
+        }
+
+        async fn get_contract_creator(&self, _address: &Address) -> Option<(H256, Address)> {
+            unimplemented!()
+        }
+
+        async fn get_token_transfer_emitters(&self) -> Vec<Address> {
+            unimplemented!()
+        }
+
         async fn get_filter_logs(&self, _log_filter: &LogFilter) -> Vec<Log> {
             unimplemented!()
         }
@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use zksync_types::api::state_override::StateOverride;
+use zksync_types::api::{
+    BlockNumber, CallTracerBlockResult, CallTracerConfig, CallTracerResult, DebugCall,
+    SupportedTracers, TracerConfig,
+};
+use zksync_types::transaction_request::CallRequest;
 use zksync_types::web3::Bytes;
-use zksync_types::U64;
+use zksync_types::{Address, H256, U256, U64};
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize)]
 pub struct DetailedTransaction {
@@ -21,6 +28,12 @@ pub struct ResetRequestForking {
     pub json_rpc_url: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub block_number: Option<U64>,
+    /// Fork at the last block whose timestamp (in seconds) is at or before this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<U64>,
+    /// Fork at the last block of this L1 batch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_batch_number: Option<U64>,
 }
 
 #[derive(Clone, Debug, PartialEq, Default, Deserialize)]
@@ -32,3 +45,620 @@ pub struct ResetRequest {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub forking: Option<ResetRequestForking>,
 }
+
+/// Account to be managed by the node (as accepted by `evm_addAccount`).
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum NewAccount {
+    /// Index to derive the account at from the node's mnemonic.
+    DerivationIndex(u32),
+    /// Raw private key.
+    PrivateKey(H256),
+}
+
+/// Token metadata overrides accepted by `anvil_registerToken`. Missing fields are read from the
+/// token contract.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMetadata {
+    pub l1_address: Option<Address>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+/// Session-wide gas usage aggregated per contract and function (as returned by
+/// `anvil_getGasReport`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasReport {
+    pub contracts: Vec<ContractGasReport>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractGasReport {
+    pub address: Address,
+    /// Decoded contract label, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub functions: Vec<FunctionGasReport>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionGasReport {
+    /// 4-byte function selector or `None` for calls without calldata (fallback/receive).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<Bytes>,
+    /// Decoded function signature, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    pub calls: u64,
+    pub min: u64,
+    pub avg: u64,
+    pub median: u64,
+    pub max: u64,
+    /// Average gas spent on pubdata. Pubdata is only attributed to the transaction's entry call,
+    /// nested calls report zero.
+    pub avg_pubdata: u64,
+}
+
+/// Gas profile of a single transaction (as returned by `anvil_getGasProfile`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasProfile {
+    /// Profile in folded-stack format, one `frame;frame;... gas` entry per line.
+    pub folded: String,
+    /// Profile rendered as an SVG flamegraph.
+    pub svg: String,
+}
+
+/// Options accepted by `debug_trace*` methods. Unlike geth, the call tracer is used unless
+/// `{ "tracer": "structLogger" }` explicitly selects the struct-log (opcode) tracer, so that
+/// existing clients passing only `tracerConfig` keep getting call traces.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceOptions {
+    #[serde(default)]
+    pub tracer: Option<DebugTracer>,
+    #[serde(default)]
+    pub tracer_config: Option<CallTracerConfig>,
+    #[serde(flatten)]
+    pub struct_log: StructLogConfig,
+}
+
+impl TraceOptions {
+    /// Returns struct-log tracer options if the struct-log tracer was requested.
+    pub fn struct_log_config(&self) -> Option<StructLogConfig> {
+        matches!(self.tracer, Some(DebugTracer::StructLogger(_))).then_some(self.struct_log)
+    }
+
+    /// Returns call tracer options unless the struct-log tracer was requested.
+    pub fn call_tracer_config(self) -> Option<TracerConfig> {
+        let tracer = match self.tracer {
+            Some(DebugTracer::Call(tracer)) => tracer,
+            None => SupportedTracers::CallTracer,
+            Some(DebugTracer::StructLogger(_)) => return None,
+        };
+        let only_top_call = self
+            .tracer_config
+            .is_some_and(|config| config.only_top_call);
+        Some(TracerConfig {
+            tracer,
+            tracer_config: CallTracerConfig { only_top_call },
+        })
+    }
+}
+
+/// Tracer requested through [`TraceOptions::tracer`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum DebugTracer {
+    /// One of the call tracers supported by zksync-era.
+    Call(SupportedTracers),
+    /// geth's default struct-log tracer.
+    StructLogger(StructLoggerTracer),
+}
+
+/// Name of the struct-log tracer, `structLogger`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StructLoggerTracer {
+    StructLogger,
+}
+
+/// Options of the struct-log tracer, named as in geth's default tracer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StructLogConfig {
+    pub disable_stack: bool,
+    pub disable_storage: bool,
+    pub enable_memory: bool,
+    /// Maximum number of logs to capture, `0` means unlimited.
+    pub limit: usize,
+}
+
+/// Single executed instruction in geth's struct-log format. For contracts running in the EVM
+/// interpreter `op`, `pc` and `gas` refer to interpreted EVM opcodes; otherwise they describe
+/// EraVM instructions and `stack` contains the general purpose registers.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    pub pc: u64,
+    pub op: String,
+    pub gas: u64,
+    pub gas_cost: u64,
+    pub depth: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    /// Memory as 32-byte hex-encoded words.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<String>>,
+    /// Storage slots of the current contract accessed so far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// Result of the struct-log tracer for a single transaction.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogTrace {
+    pub gas: u64,
+    pub failed: bool,
+    pub return_value: Bytes,
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// Struct-log trace of a transaction included in a block.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxStructLogTrace {
+    pub tx_hash: H256,
+    pub result: StructLogTrace,
+}
+
+/// Result of `debug_traceTransaction` and `debug_traceCall`.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum TraceResult {
+    CallTrace(CallTracerResult),
+    StructLogs(StructLogTrace),
+}
+
+/// Result of `debug_traceBlockByNumber` and `debug_traceBlockByHash`.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum BlockTraceResult {
+    CallTrace(CallTracerBlockResult),
+    StructLogs(Vec<TxStructLogTrace>),
+}
+
+/// Kind of a Parity-style trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceType {
+    Call,
+    Create,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallAction {
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub gas: U256,
+    pub input: Bytes,
+    pub call_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAction {
+    pub from: Address,
+    pub value: U256,
+    pub gas: U256,
+    pub init: Bytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TraceAction {
+    Call(CallAction),
+    Create(CreateAction),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallOutput {
+    pub gas_used: U256,
+    pub output: Bytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOutput {
+    pub gas_used: U256,
+    pub code: Bytes,
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TraceOutput {
+    Create(CreateOutput),
+    Call(CallOutput),
+}
+
+/// Single call in Parity's flat trace format. `trace_address` is the path to the call in the
+/// transaction's call tree (empty for the top-level call).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTrace {
+    pub action: TraceAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// `None` if the call has failed.
+    pub result: Option<TraceOutput>,
+    pub subtraces: usize,
+    pub trace_address: Vec<usize>,
+    #[serde(rename = "type")]
+    pub trace_type: TraceType,
+}
+
+/// [`TransactionTrace`] along with the position of its transaction in the chain (as returned by
+/// `trace_transaction`, `trace_block` and `trace_filter`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedTransactionTrace {
+    #[serde(flatten)]
+    pub trace: TransactionTrace,
+    pub block_hash: H256,
+    pub block_number: u64,
+    pub transaction_hash: H256,
+    pub transaction_position: u64,
+}
+
+/// Result of `trace_replayTransaction`. Only `trace` is supported, so `stateDiff` and `vmTrace`
+/// are always `null`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceResults {
+    pub output: Bytes,
+    pub trace: Option<Vec<TransactionTrace>>,
+    pub state_diff: Option<serde_json::Value>,
+    pub vm_trace: Option<serde_json::Value>,
+}
+
+/// Filter accepted by `trace_filter`. Traces must match both `from_address` and `to_address`
+/// (if provided).
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceFilter {
+    /// Defaults to `earliest`.
+    #[serde(default)]
+    pub from_block: Option<BlockNumber>,
+    /// Defaults to `latest`.
+    #[serde(default)]
+    pub to_block: Option<BlockNumber>,
+    #[serde(default)]
+    pub from_address: Option<Vec<Address>>,
+    #[serde(default)]
+    pub to_address: Option<Vec<Address>>,
+    /// Number of matching traces to skip.
+    #[serde(default)]
+    pub after: Option<usize>,
+    /// Maximum number of traces to return.
+    #[serde(default)]
+    pub count: Option<usize>,
+}
+
+/// Kind of an internal operation reported by `ots_getInternalOperations`. Serialized as a number
+/// as expected by Otterscan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OtsOperationType {
+    Transfer = 0,
+    SelfDestruct = 1,
+    Create = 2,
+    Create2 = 3,
+}
+
+impl Serialize for OtsOperationType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// Value transfer or contract creation that happened inside a transaction.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OtsInternalOperation {
+    #[serde(rename = "type")]
+    pub op_type: OtsOperationType,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OtsTraceType {
+    Call,
+    Create,
+}
+
+/// Single call in the flat trace returned by `ots_traceTransaction`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OtsTrace {
+    #[serde(rename = "type")]
+    pub trace_type: OtsTraceType,
+    pub depth: usize,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub input: Bytes,
+    pub output: Bytes,
+}
+
+/// Block along with its transaction count. Transactions are only included by
+/// `ots_getBlockTransactions` (and only the requested page of them).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsBlock {
+    #[serde(flatten)]
+    pub block: zksync_types::api::Block<zksync_types::api::TransactionVariant>,
+    pub transaction_count: usize,
+}
+
+/// Block issuance. Always zero as there are no block rewards on ZKsync.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsBlockIssuance {
+    pub block_reward: U256,
+    pub uncle_reward: U256,
+    pub issuance: U256,
+}
+
+/// Result of `ots_getBlockDetails`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsBlockDetails {
+    pub block: OtsBlock,
+    pub issuance: OtsBlockIssuance,
+    pub total_fees: U256,
+}
+
+/// Result of `ots_getBlockTransactions`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OtsBlockTransactions {
+    pub fullblock: OtsBlock,
+    pub receipts: Vec<zksync_types::api::TransactionReceipt>,
+}
+
+/// Transaction receipt along with the timestamp of its block.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OtsTransactionReceipt {
+    #[serde(flatten)]
+    pub receipt: zksync_types::api::TransactionReceipt,
+    pub timestamp: U256,
+}
+
+/// Result of `ots_searchTransactionsBefore` and `ots_searchTransactionsAfter`. Transactions are
+/// always sorted from the newest to the oldest one. `first_page` is set when the newest matching
+/// transaction is included, `last_page` when the oldest one is.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsSearchTransactions {
+    pub txs: Vec<zksync_types::api::Transaction>,
+    pub receipts: Vec<OtsTransactionReceipt>,
+    pub first_page: bool,
+    pub last_page: bool,
+}
+
+/// Result of `ots_getContractCreator`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OtsContractCreator {
+    pub hash: H256,
+    pub creator: Address,
+}
+
+/// Payload of `eth_simulateV1`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatePayload {
+    /// Blocks to simulate, in order.
+    pub block_state_calls: Vec<SimulateBlock>,
+    /// Accepted for compatibility only. Base token transfers always emit `Transfer` logs on
+    /// ZKsync.
+    #[serde(default)]
+    pub trace_transfers: bool,
+    /// Not supported, calls are always executed without nonce, balance and fee checks.
+    #[serde(default)]
+    pub validation: bool,
+    #[serde(default)]
+    pub return_full_transactions: bool,
+}
+
+/// Single block of `eth_simulateV1` payload. State overrides are applied on top of the state left
+/// by the previous simulated block.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateBlock {
+    #[serde(default)]
+    pub block_overrides: Option<SimulateBlockOverrides>,
+    #[serde(default)]
+    pub state_overrides: Option<StateOverride>,
+    #[serde(default)]
+    pub calls: Vec<CallRequest>,
+}
+
+/// Supported subset of `eth_simulateV1` block overrides.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateBlockOverrides {
+    /// Must be greater than the number of the previous block. Skipped numbers are filled with
+    /// empty blocks.
+    pub number: Option<U64>,
+    /// Must be greater than the timestamp of the previous block.
+    pub time: Option<U64>,
+    pub base_fee_per_gas: Option<U256>,
+}
+
+/// Block produced by `eth_simulateV1` along with the results of its calls.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimulatedBlock {
+    #[serde(flatten)]
+    pub inner: zksync_types::api::Block<zksync_types::api::TransactionVariant>,
+    pub calls: Vec<SimulatedCall>,
+}
+
+/// Result of a single call simulated by `eth_simulateV1`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedCall {
+    pub return_data: Bytes,
+    pub logs: Vec<zksync_types::api::Log>,
+    pub gas_used: U256,
+    pub status: U64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<SimulatedCallError>,
+    /// Call tree of the call (not a part of the standard response). `None` if the call has halted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<DebugCall>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimulatedCallError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
+}
+
+/// Remote transaction whose outcome changed when re-executed on top of the local state while
+/// following the fork (see `anvil_getForkFollowStatus`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkDivergence {
+    pub tx_hash: H256,
+    /// Remote block the transaction belongs to.
+    pub remote_block_number: U64,
+    /// Local block the transaction was re-executed in.
+    pub local_block_number: U64,
+    /// Whether the transaction succeeded on the remote chain.
+    pub remote_success: bool,
+    /// Whether the transaction succeeded locally. `None` if it was not included at all (i.e. it
+    /// has halted).
+    pub local_success: Option<bool>,
+}
+
+/// Progress of following the remote chain head (as returned by `anvil_getForkFollowStatus`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkFollowStatus {
+    pub paused: bool,
+    /// Last remote block that has been applied locally.
+    pub remote_block_number: U64,
+    /// Most recent divergences, oldest first.
+    pub divergences: Vec<ForkDivergence>,
+}
+
+/// Outcome of replaying a range of remote blocks on top of a fork created right before them (see
+/// `anvil-zksync replay_blocks`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockReplayReport {
+    pub from_block: U64,
+    pub to_block: U64,
+    pub blocks: Vec<BlockReplayResult>,
+    /// Checks that could not be performed because the remote chain does not provide the necessary
+    /// data (e.g. it does not support `debug_traceTransaction`).
+    #[serde(default)]
+    pub skipped_checks: Vec<ReplayMismatchKind>,
+}
+
+impl BlockReplayReport {
+    /// Total number of mismatches found across all replayed blocks.
+    pub fn mismatch_count(&self) -> usize {
+        self.blocks.iter().map(|block| block.mismatches.len()).sum()
+    }
+}
+
+/// Outcome of replaying a single remote block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockReplayResult {
+    pub remote_block_number: U64,
+    /// Local block the remote block's transactions were re-executed in.
+    pub local_block_number: U64,
+    pub tx_count: U64,
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+/// Aspect of a replayed block that is compared against the remote chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplayMismatchKind {
+    /// Transaction was included remotely but halted locally (or vice versa).
+    Inclusion,
+    Status,
+    GasUsed,
+    Logs,
+    ReturnData,
+    /// Value of a storage slot at the end of the block.
+    Storage,
+}
+
+/// Single difference between the remote and the local result of a replayed block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayMismatch {
+    pub kind: ReplayMismatchKind,
+    /// Transaction the mismatch was found in; `None` for block-level mismatches (i.e. storage).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<H256>,
+    /// Storage slot that differs, only set for [`ReplayMismatchKind::Storage`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<H256>,
+    pub remote: serde_json::Value,
+    pub local: serde_json::Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_options_default_to_call_tracer() {
+        let options: TraceOptions =
+            serde_json::from_str(r#"{ "tracerConfig": { "onlyTopCall": true } }"#).unwrap();
+        assert!(options.struct_log_config().is_none());
+        let config = options.call_tracer_config().expect("call tracer expected");
+        assert!(config.tracer_config.only_top_call);
+
+        let options: TraceOptions = serde_json::from_str("{}").unwrap();
+        assert!(options.struct_log_config().is_none());
+        assert!(options.call_tracer_config().is_some());
+    }
+
+    #[test]
+    fn test_trace_options_struct_logger() {
+        let options: TraceOptions =
+            serde_json::from_str(r#"{ "tracer": "structLogger", "enableMemory": true }"#).unwrap();
+        assert_eq!(
+            options.struct_log_config(),
+            Some(StructLogConfig {
+                enable_memory: true,
+                ..Default::default()
+            })
+        );
+        assert!(options.call_tracer_config().is_none());
+
+        let options: TraceOptions = serde_json::from_str(r#"{ "tracer": "callTracer" }"#).unwrap();
+        assert!(options.struct_log_config().is_none());
+        assert!(options.call_tracer_config().is_some());
+    }
+}
@@ -50,6 +50,16 @@ impl L2TxBuilder {
         self
     }
 
+    pub fn with_value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn with_factory_deps(mut self, factory_deps: Vec<Vec<u8>>) -> Self {
+        self.factory_deps = factory_deps;
+        self
+    }
+
     pub fn with_max_priority_fee_per_gas(mut self, max_priority_fee_per_gas: U256) -> Self {
         self.max_priority_fee_per_gas = max_priority_fee_per_gas;
         self
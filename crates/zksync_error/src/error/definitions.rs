@@ -336,6 +336,16 @@ pub enum AnvilNode {
         to: Box<zksync_basic_types::H256>,
         reason: String,
     } = 30u32,
+    #[doc = "# Summary "]
+    #[doc = "Failed to fetch data from the forked network."]
+    #[doc = ""]
+    #[doc = "# Description"]
+    #[doc = "This error occurs when anvil-zksync runs in fork mode and cannot fetch state (storage slots, bytecodes, blocks or transactions) from the remote RPC endpoint it forked from."]
+    #[doc = "Transient failures such as timeouts, connection errors and rate limiting (HTTP 429) are retried with exponential backoff; this error is only reported once all retries are exhausted or the endpoint returned a non-retryable error."]
+    ForkRequestFailed {
+        method: String,
+        reason: String,
+    } = 40u32,
     GenericError {
         message: String,
     } = 0u32,
@@ -421,6 +431,9 @@ impl CustomErrorMessage for AnvilNode {
             } => {
                 format ! ("[anvil_zksync-node-30] Failed to parse a {transaction_type} transaction from request (from={from}, to={to}): {reason}.")
             }
+            AnvilNode::ForkRequestFailed { method, reason } => {
+                format!("[anvil_zksync-node-40] Request `{method}` to the forked network failed: {reason}")
+            }
             AnvilNode::GenericError { message } => {
                 format!("[anvil_zksync-node-0] Generic error: {message}")
             }
@@ -68,6 +68,7 @@ pub mod anvil_zksync {
     pub mod node {
         pub use crate::error::definitions::AnvilNode as AnvilNodeError;
         pub type AnvilNodeResult<T> = core::result::Result<T, AnvilNodeError>;
+        pub use crate::error::definitions::AnvilNode::ForkRequestFailed;
         pub use crate::error::definitions::AnvilNode::GenericError;
         pub use crate::error::definitions::AnvilNode::SerializationError;
         pub use crate::error::definitions::AnvilNode::TimestampBackwardsError;
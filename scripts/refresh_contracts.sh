@@ -36,6 +36,8 @@ BUILTIN_CONTRACTS_OUTPUT_PATH="crates/core/src/deps/contracts/builtin-contracts-
 L1_ARTIFACTS_SRC_DIR=contracts/l1-contracts/zkout
 L2_ARTIFACTS_SRC_DIR=contracts/l2-contracts/zkout
 SYSTEM_ARTIFACTS_SRC_DIR=contracts/system-contracts/zkout
+# Checked in as they are not part of era-contracts
+PREDEPLOY_ARTIFACTS_SRC_DIR=etc/predeploys
 
 l1_artifacts=("MessageRoot" "Bridgehub" "L2AssetRouter" "L2NativeTokenVault" "L2WrappedBaseToken")
 l2_artifacts=("TimestampAsserter")
@@ -80,6 +82,17 @@ for artifact in "${l2_artifacts[@]}"; do
   FILES="$FILES $L2_ARTIFACTS_SRC_DIR/$artifact.sol/$artifact.json"
 done
 
+# EVM helper contracts installed by `--predeploys` and `--testnet-paymaster` (need the EVM emulator
+# added in v27)
+if [[ ! $PROTOCOL_VERSION < v27 ]]; then
+  predeploy_artifacts=(
+    "Multicall3" "DeterministicDeploymentProxy" "MyERC20" "ERC20FixedPaymaster" "TestnetPaymaster"
+  )
+  for artifact in "${predeploy_artifacts[@]}"; do
+    FILES="$FILES $PREDEPLOY_ARTIFACTS_SRC_DIR/$artifact.json"
+  done
+fi
+
 for artifact in "${system_contracts_sol[@]}"; do
   FILES="$FILES $SYSTEM_ARTIFACTS_SRC_DIR/$artifact.sol/$artifact.json"
 done